@@ -21,6 +21,16 @@
 //!
 //! We translate `format` (preferred) and fall back to `type` when the format
 //! is unknown. Columns absent from `required` are wrapped in `Option<T>`.
+//!
+//! Each table also gets write payloads: `<Name>Insert`, where a column is a
+//! plain field only when it is `required` (NOT NULL) and has no `default`,
+//! and `<Name>Update`, where every column is optional. Both are tied to the
+//! row struct through `Writable`.
+//!
+//! Functions appear under `paths` as `/rpc/<name>`. The `post` operation's
+//! body parameter describes the arguments with the same `required` /
+//! `properties` shape as a table; a `get` operation is only listed for
+//! `STABLE` / `IMMUTABLE` functions. Return types are not described at all.
 
 use std::collections::BTreeMap;
 
@@ -30,6 +40,36 @@ use serde::Deserialize;
 pub struct OpenApi {
     #[serde(default)]
     pub definitions: BTreeMap<String, TableDef>,
+    #[serde(default)]
+    pub paths: BTreeMap<String, PathItem>,
+}
+
+/// One entry of `paths`. Only `/rpc/<name>` entries are read.
+#[derive(Debug, Default, Deserialize)]
+pub struct PathItem {
+    #[serde(default)]
+    pub get: Option<PathOperation>,
+    #[serde(default)]
+    pub post: Option<PathOperation>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct PathOperation {
+    #[serde(default)]
+    pub summary: Option<String>,
+    #[serde(default)]
+    pub parameters: Vec<Parameter>,
+}
+
+/// An operation parameter. `$ref` entries (shared `Prefer` headers etc.)
+/// deserialize with every field defaulted and are skipped.
+#[derive(Debug, Default, Deserialize)]
+pub struct Parameter {
+    #[serde(default, rename = "in")]
+    pub location: Option<String>,
+    /// Argument object for the `in: body` parameter of an RPC `post`.
+    #[serde(default)]
+    pub schema: Option<TableDef>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -52,6 +92,10 @@ pub struct ColumnDef {
     /// type is a user-defined enum. Element-level for arrays of enum.
     #[serde(default, rename = "enum")]
     pub variants: Option<Vec<String>>,
+    /// Column default as PostgREST reports it (`"now()"`, `0`, …). Only its
+    /// presence matters: defaulted columns are optional in `<Name>Insert`.
+    #[serde(default)]
+    pub default: Option<serde_json::Value>,
 }
 
 /// Resolved Postgres enum, after de-duping by `format` name across all tables.
@@ -66,6 +110,20 @@ pub struct EnumInfo {
     pub variants: Vec<String>,
 }
 
+/// Foreign-key relation discovered from PostgREST's `<fk table='…' column='…'/>`
+/// column notes, seen from the table that receives the relation constant.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RelationInfo {
+    /// Embed alias; also the Rust constant and composite-field name.
+    pub name: String,
+    /// Table being embedded.
+    pub target: String,
+    /// Foreign-key column, passed to PostgREST as the disambiguation hint.
+    pub hint: String,
+    /// `true` for one-to-many (the foreign key lives on `target`).
+    pub many: bool,
+}
+
 /// Codegen settings, surfaced as CLI flags.
 #[derive(Debug, Clone)]
 pub struct Options {
@@ -115,18 +173,111 @@ pub fn emit(api: &OpenApi, opts: &Options) -> String {
     // Collect every distinct Postgres enum referenced by an included table,
     // emit a single Rust enum per format up-front, then thread the map into
     // table emission so column types resolve to the enum name.
+    let relations = collect_relations(&tables);
+    if relations.values().any(|r| !r.is_empty()) {
+        out.push_str(RELATION_IMPORTS);
+    }
+
+    if tables.iter().any(|(_, def)| !primary_key_columns(def).is_empty()) {
+        out.push_str(KEYED_IMPORTS);
+    }
+
     let enums = collect_enums(&tables, opts);
     for info in enums.values() {
         emit_enum(&mut out, info);
     }
 
     for (table_name, def) in tables {
+        let rels = relations.get(table_name.as_str()).map_or(&[][..], Vec::as_slice);
         emit_table(&mut out, &opts.schema, table_name, def, opts, &enums);
+        emit_relations(&mut out, table_name, rels);
     }
 
+    emit_functions(&mut out, api, opts, &enums);
+
     out
 }
 
+/// Emit `pub mod rpc { … }` with an `RpcFunction` impl and an args struct per
+/// `/rpc/<name>` path. PostgREST doesn't describe return types, so `Returns`
+/// is `serde_json::Value`; narrow it by hand-writing the impl instead.
+fn emit_functions(
+    out: &mut String,
+    api: &OpenApi,
+    opts: &Options,
+    enums: &BTreeMap<String, EnumInfo>,
+) {
+    let functions: Vec<(&str, &PathItem)> = api
+        .paths
+        .iter()
+        .filter_map(|(path, item)| Some((path.strip_prefix("/rpc/")?, item)))
+        .filter(|(_, item)| item.post.is_some() || item.get.is_some())
+        .collect();
+    if functions.is_empty() {
+        return;
+    }
+
+    out.push_str("/// Postgres functions exposed under `/rest/v1/rpc`.\n");
+    out.push_str("pub mod rpc {\n");
+    out.push_str("    use super::*;\n");
+    out.push_str("    use rust_supabase_sdk::rpc::RpcFunction;\n\n");
+
+    for (name, item) in functions {
+        let type_name = to_struct_name(name);
+        let op = item.post.as_ref().or(item.get.as_ref());
+        let args = op
+            .and_then(|op| {
+                op.parameters
+                    .iter()
+                    .find(|p| p.location.as_deref() == Some("body"))
+            })
+            .and_then(|p| p.schema.as_ref());
+
+        out.push_str(&format!("    /// Arguments of `{}.{name}`.\n", opts.schema));
+        out.push_str("    #[derive(Debug, Clone, Serialize, Deserialize)]\n");
+        out.push_str(&format!("    pub struct {type_name}Args {{\n"));
+        if let Some(def) = args {
+            for (arg, col) in &def.properties {
+                let rust_ty = map_type_with_enums(col, opts, enums);
+                let required = def.required.iter().any(|r| r == arg);
+                let safe = to_field_name(arg);
+                let mut attrs = Vec::new();
+                if safe != *arg {
+                    attrs.push(format!("rename = \"{arg}\""));
+                }
+                if !required {
+                    attrs.push("default, skip_serializing_if = \"Option::is_none\"".to_string());
+                }
+                if !attrs.is_empty() {
+                    out.push_str(&format!("        #[serde({})]\n", attrs.join(", ")));
+                }
+                let ty = if required { rust_ty } else { format!("Option<{rust_ty}>") };
+                out.push_str(&format!("        pub {safe}: {ty},\n"));
+            }
+        }
+        out.push_str("    }\n\n");
+
+        if let Some(summary) = op.and_then(|op| op.summary.as_deref()) {
+            for line in summary.lines() {
+                out.push_str(&format!("    /// {line}\n"));
+            }
+        } else {
+            out.push_str(&format!("    /// `{}.{name}`.\n", opts.schema));
+        }
+        out.push_str(&format!("    pub struct {type_name};\n\n"));
+        out.push_str(&format!("    impl RpcFunction for {type_name} {{\n"));
+        out.push_str(&format!("        const NAME: &'static str = \"{name}\";\n"));
+        if item.get.is_some() {
+            out.push_str("        const READ_ONLY: bool = true;\n");
+        }
+        out.push_str(&format!("        type Args = {type_name}Args;\n"));
+        out.push_str("        type Returns = serde_json::Value;\n");
+        out.push_str("    }\n\n");
+    }
+    out.pop();
+    out.push_str("}\n\n");
+}
+
 /// Walk every column in the included tables, group enum columns by their
 /// PostgREST `format` string, and pick a Rust type name for each. When two
 /// schemas expose enums with the same simple name (e.g. `public.status` vs
@@ -198,6 +349,172 @@ fn last_segment(fmt: &str) -> &str {
     fmt.rsplit('.').next().unwrap_or(fmt)
 }
 
+/// Walk every column note for `<fk …/>` markers and derive both directions of
+/// each foreign key between included tables: a to-one relation on the
+/// referencing table and a to-many relation on the referenced one.
+///
+/// Naming: the to-one side drops an `_id` suffix from the column
+/// (`author_id` → `author`), falling back to the target table name; the
+/// to-many side uses the referencing table name, qualified with
+/// `_by_<column>` when that table has several keys to the same target. Any
+/// name that would clash with a column or an earlier relation gets `_rel`
+/// appended, since embeds share the row's JSON namespace.
+fn collect_relations(tables: &[(&String, &TableDef)]) -> BTreeMap<String, Vec<RelationInfo>> {
+    let included: Vec<&str> = tables.iter().map(|(name, _)| name.as_str()).collect();
+
+    // (source table, fk column, target table), in table/column order.
+    let mut fks: Vec<(&str, &str, String)> = Vec::new();
+    for (table, def) in tables {
+        for (col, col_def) in &def.properties {
+            let Some(target) = col_def.description.as_deref().and_then(parse_fk_note) else {
+                continue;
+            };
+            if included.contains(&target.as_str()) {
+                fks.push((table.as_str(), col.as_str(), target));
+            }
+        }
+    }
+
+    let mut out: BTreeMap<String, Vec<RelationInfo>> = BTreeMap::new();
+    for (source, col, target) in &fks {
+        let stripped = col.strip_suffix("_id").filter(|s| !s.is_empty());
+        out.entry((*source).to_string()).or_default().push(RelationInfo {
+            name: stripped.unwrap_or(target).to_string(),
+            target: target.clone(),
+            hint: (*col).to_string(),
+            many: false,
+        });
+
+        let siblings = fks.iter().filter(|(s, _, t)| s == source && t == target).count();
+        let name = if siblings > 1 {
+            format!("{source}_by_{}", stripped.unwrap_or(col))
+        } else {
+            (*source).to_string()
+        };
+        out.entry(target.clone()).or_default().push(RelationInfo {
+            name,
+            target: (*source).to_string(),
+            hint: (*col).to_string(),
+            many: true,
+        });
+    }
+
+    for (table, rels) in out.iter_mut() {
+        // To-one relations first; stable, so source order is kept within each.
+        rels.sort_by_key(|r| r.many);
+        let columns: Vec<&String> = tables
+            .iter()
+            .find(|(name, _)| *name == table)
+            .map(|(_, def)| def.properties.keys().collect())
+            .unwrap_or_default();
+        let mut taken: Vec<String> = columns.into_iter().cloned().collect();
+        for rel in rels.iter_mut() {
+            while taken.contains(&rel.name) {
+                rel.name.push_str("_rel");
+            }
+            taken.push(rel.name.clone());
+        }
+    }
+    out
+}
+
+/// Primary-key columns, in column-name order (the key order of
+/// `PRIMARY_KEY` and of composite `PrimaryKey` tuples).
+fn primary_key_columns(def: &TableDef) -> Vec<(&String, &ColumnDef)> {
+    def.properties
+        .iter()
+        .filter(|(_, col)| col.description.as_deref().is_some_and(is_pk_note))
+        .collect()
+}
+
+/// `true` for a primary-key column note: ``"Note:\nThis is a Primary Key.<pk/>"``.
+fn is_pk_note(description: &str) -> bool {
+    description.contains("<pk/>")
+}
+
+/// Extract the referenced table from a PostgREST column note such as
+/// ``"Note:\nThis is a Foreign Key to `users.id`.<fk table='users' column='id'/>"``.
+fn parse_fk_note(description: &str) -> Option<String> {
+    let start = description.find("<fk ")?;
+    let tag = &description[start..];
+    let tag = &tag[..tag.find("/>")?];
+    let value_start = tag.find("table='")? + "table='".len();
+    let value_len = tag[value_start..].find('\'')?;
+    let table = &tag[value_start..value_start + value_len];
+    Some(last_segment(table).to_string())
+}
+
+/// Emit relation constants, `Related` impls (for targets reached by exactly
+/// one relation), the `<Table>WithRelations` composite and its `Embeddable`
+/// impl. No-op for tables without relations.
+fn emit_relations(out: &mut String, table: &str, rels: &[RelationInfo]) {
+    if rels.is_empty() {
+        return;
+    }
+    let struct_name = to_struct_name(table);
+
+    out.push_str(&format!(
+        "#[allow(non_upper_case_globals)]\nimpl {struct_name} {{\n"
+    ));
+    for rel in rels {
+        let target = to_struct_name(&rel.target);
+        let ctor = if rel.many { "to_many" } else { "to_one" };
+        out.push_str(&format!(
+            "    pub const {}: Relation<{struct_name}, {target}> = Relation::{ctor}(\"{}\", Some(\"{}\"));\n",
+            to_field_name(&rel.name),
+            rel.name,
+            rel.hint,
+        ));
+    }
+    out.push_str("}\n\n");
+
+    for rel in rels {
+        if rels.iter().filter(|r| r.target == rel.target).count() != 1 {
+            continue;
+        }
+        let target = to_struct_name(&rel.target);
+        out.push_str(&format!("impl Related<{target}> for {struct_name} {{\n"));
+        out.push_str(&format!(
+            "    const RELATION: Relation<{struct_name}, {target}> = {struct_name}::{};\n",
+            to_field_name(&rel.name)
+        ));
+        out.push_str("}\n\n");
+    }
+
+    out.push_str(&format!(
+        "/// `{table}` row plus its embeddable relations; each stays `None` unless embedded.\n"
+    ));
+    out.push_str("#[derive(Debug, Clone, Serialize, Deserialize)]\n");
+    out.push_str(&format!("pub struct {struct_name}WithRelations {{\n"));
+    out.push_str("    #[serde(flatten)]\n");
+    out.push_str(&format!("    pub row: {struct_name},\n"));
+    for rel in rels {
+        let target = to_struct_name(&rel.target);
+        let ty = if rel.many {
+            format!("Option<Vec<{target}>>")
+        } else {
+            format!("Option<{target}>")
+        };
+        let safe = to_field_name(&rel.name);
+        if safe != rel.name {
+            out.push_str(&format!(
+                "    #[serde(rename = \"{}\", default, skip_serializing_if = \"Option::is_none\")]\n",
+                rel.name
+            ));
+        } else {
+            out.push_str("    #[serde(default, skip_serializing_if = \"Option::is_none\")]\n");
+        }
+        out.push_str(&format!("    pub {safe}: {ty},\n"));
+    }
+    out.push_str("}\n\n");
+
+    out.push_str(&format!("impl Embeddable for {struct_name} {{\n"));
+    out.push_str(&format!(
+        "    type WithRelations = {struct_name}WithRelations;\n"
+    ));
+    out.push_str("}\n\n");
+}
+
 fn emit_enum(out: &mut String, info: &EnumInfo) {
     out.push_str(&format!("/// Postgres enum `{}`.\n", info.format));
     out.push_str(
@@ -250,11 +567,19 @@ const HEADER: &str = "//! Auto-generated by `cargo supabase gen types`. DO NOT E
 #![allow(clippy::module_name_repetitions, dead_code)]
 #![allow(non_camel_case_types, non_snake_case)]
 
-use rust_supabase_sdk::{postgrest::Column, Row};
+use rust_supabase_sdk::{postgrest::{Column, Writable}, Row};
 use serde::{Deserialize, Serialize};
 
 ";
 
+/// Extra import, emitted only when at least one relation was discovered so
+/// relation-free schemas don't trip `unused_imports`.
+const RELATION_IMPORTS: &str =
+    "use rust_supabase_sdk::postgrest::{Embeddable, Related, Relation};\n\n";
+
+/// Extra import, emitted only when at least one table has a primary key.
+const KEYED_IMPORTS: &str = "use rust_supabase_sdk::postgrest::Keyed;\n\n";
+
 fn emit_table(
     out: &mut String,
     schema: &str,
@@ -312,8 +637,31 @@ fn emit_table(
         out.push('"');
     }
     out.push_str("];\n");
+    let primary_key = primary_key_columns(def);
+    let key_names: Vec<String> = primary_key
+        .iter()
+        .map(|(name, _)| format!("\"{name}\""))
+        .collect();
+    out.push_str(&format!(
+        "    const PRIMARY_KEY: &'static [&'static str] = &[{}];\n",
+        key_names.join(", ")
+    ));
     out.push_str("}\n\n");
 
+    if !primary_key.is_empty() {
+        let key_types: Vec<String> = primary_key
+            .iter()
+            .map(|(_, col)| map_type_with_enums(col, opts, enums))
+            .collect();
+        let key_ty = match key_types.as_slice() {
+            [single] => single.clone(),
+            many => format!("({})", many.join(", ")),
+        };
+        out.push_str(&format!(
+            "impl Keyed for {struct_name} {{\n    type PrimaryKey = {key_ty};\n}}\n\n"
+        ));
+    }
+
     // ------------------------------------------------------------
     // Typed column constants:
     //   impl Posts {
@@ -338,6 +686,83 @@ fn emit_table(
         ));
     }
     out.push_str("}\n\n");
+
+    emit_writes(out, schema, table, def, &columns, opts, enums);
+}
+
+/// Emit `<Name>Insert` / `<Name>Update` and the `Writable` impl tying them
+/// to the row struct.
+///
+/// Insert: NOT NULL columns without a default are plain fields; the rest are
+/// `Option` and skipped when `None`, so the database fills them in.
+/// Update: everything is skipped when `None`; nullable columns are
+/// `Option<Option<T>>` so `Some(None)` can write `NULL`.
+fn emit_writes(
+    out: &mut String,
+    schema: &str,
+    table: &str,
+    def: &TableDef,
+    columns: &[(&String, &ColumnDef)],
+    opts: &Options,
+    enums: &BTreeMap<String, EnumInfo>,
+) {
+    const SKIP_NONE: &str = "skip_serializing_if = \"Option::is_none\"";
+    let struct_name = to_struct_name(table);
+
+    let field_attr = |col_name: &str, skip: bool| -> String {
+        let mut args = Vec::new();
+        if to_field_name(col_name) != col_name {
+            args.push(format!("rename = \"{col_name}\""));
+        }
+        if skip {
+            args.push(SKIP_NONE.to_string());
+        }
+        if args.is_empty() {
+            String::new()
+        } else {
+            format!("    #[serde({})]\n", args.join(", "))
+        }
+    };
+
+    out.push_str(&format!(
+        "/// Insert payload for `{schema}.{table}`. `None` fields are left out so\n\
+         /// the column default (or `NULL`) applies.\n"
+    ));
+    out.push_str("#[derive(Debug, Clone, Serialize)]\n");
+    out.push_str(&format!("pub struct {struct_name}Insert {{\n"));
+    for (col_name, col) in columns {
+        let rust_ty = map_type_with_enums(col, opts, enums);
+        let not_null = def.required.iter().any(|r| r == *col_name);
+        let optional = !not_null || col.default.is_some();
+        out.push_str(&field_attr(col_name, optional));
+        let ty = if optional { format!("Option<{rust_ty}>") } else { rust_ty };
+        out.push_str(&format!("    pub {}: {ty},\n", to_field_name(col_name)));
+    }
+    out.push_str("}\n\n");
+
+    out.push_str(&format!(
+        "/// Update payload for `{schema}.{table}`. `None` fields are left\n\
+         /// unchanged; `Some(None)` sets a nullable column to `NULL`.\n"
+    ));
+    out.push_str("#[derive(Debug, Clone, Default, Serialize)]\n");
+    out.push_str(&format!("pub struct {struct_name}Update {{\n"));
+    for (col_name, col) in columns {
+        let rust_ty = map_type_with_enums(col, opts, enums);
+        let nullable = !def.required.iter().any(|r| r == *col_name);
+        out.push_str(&field_attr(col_name, true));
+        let ty = if nullable {
+            format!("Option<Option<{rust_ty}>>")
+        } else {
+            format!("Option<{rust_ty}>")
+        };
+        out.push_str(&format!("    pub {}: {ty},\n", to_field_name(col_name)));
+    }
+    out.push_str("}\n\n");
+
+    out.push_str(&format!("impl Writable for {struct_name} {{\n"));
+    out.push_str(&format!("    type Insert = {struct_name}Insert;\n"));
+    out.push_str(&format!("    type Update = {struct_name}Update;\n"));
+    out.push_str("}\n\n");
 }
 
 /// Map a PostgREST column descriptor to a Rust type name. Equivalent to
@@ -428,6 +853,20 @@ fn map_scalar_format(format: &str, kind: &str, opts: &Options) -> String {
                 "String".into()
             }
         }
+        // Ranges — PostgREST sends them in Postgres' text form.
+        "int4range" => "rust_supabase_sdk::postgrest::PgRange<i32>".into(),
+        "int8range" => "rust_supabase_sdk::postgrest::PgRange<i64>".into(),
+        "numrange" => "rust_supabase_sdk::postgrest::PgRange<f64>".into(),
+        "daterange" => {
+            if opts.chrono {
+                "rust_supabase_sdk::postgrest::PgRange<chrono::NaiveDate>".into()
+            } else {
+                "rust_supabase_sdk::postgrest::PgRange<String>".into()
+            }
+        }
+        "tsrange" | "tstzrange" => "rust_supabase_sdk::postgrest::PgRange<String>".into(),
+        // Full-text search
+        "tsvector" => "rust_supabase_sdk::postgrest::TsVector".into(),
         // JSON
         "json" | "jsonb" => "serde_json::Value".into(),
         // Bytes
@@ -604,6 +1043,7 @@ mod tests {
             kind: Some("string".into()),
             description: None,
             variants: None,
+            default: None,
         };
         let opts = Options { chrono: true, ..Options::default() };
         assert_eq!(map_type(&col, &opts), "chrono::DateTime<chrono::Utc>");
@@ -619,6 +1059,7 @@ mod tests {
             kind: Some("string".into()),
             description: None,
             variants: None,
+            default: None,
         };
         let off = Options::default();
         assert_eq!(map_type(&col, &off), "String");
@@ -760,6 +1201,7 @@ mod tests {
             kind: if kind.is_empty() { None } else { Some(kind.to_string()) },
             description: None,
             variants: None,
+            default: None,
         }
     }
 
@@ -879,6 +1321,32 @@ mod tests {
         assert_eq!(map_type(&mk("jsonb", "object"), &opts_default()), "serde_json::Value");
     }
 
+    // Ranges / tsvector
+    #[test]
+    fn map_range_types() {
+        let range = |fmt| map_type(&mk(fmt, "string"), &opts_default());
+        assert_eq!(range("int4range"), "rust_supabase_sdk::postgrest::PgRange<i32>");
+        assert_eq!(range("int8range"), "rust_supabase_sdk::postgrest::PgRange<i64>");
+        assert_eq!(range("numrange"), "rust_supabase_sdk::postgrest::PgRange<f64>");
+        assert_eq!(range("tstzrange"), "rust_supabase_sdk::postgrest::PgRange<String>");
+        assert_eq!(
+            range("daterange"),
+            "rust_supabase_sdk::postgrest::PgRange<chrono::NaiveDate>"
+        );
+        assert_eq!(
+            map_type(&mk("daterange", "string"), &opts_no_chrono()),
+            "rust_supabase_sdk::postgrest::PgRange<String>"
+        );
+    }
+
+    #[test]
+    fn map_tsvector() {
+        assert_eq!(
+            map_type(&mk("tsvector", "string"), &opts_default()),
+            "rust_supabase_sdk::postgrest::TsVector"
+        );
+    }
+
     // Bytea
     #[test]
     fn map_bytea() {
@@ -893,6 +1361,7 @@ mod tests {
             kind: Some("array".into()),
             description: None,
             variants: None,
+            default: None,
         };
         assert_eq!(map_type(&col, &opts_default()), "Vec<i32>");
     }
@@ -904,6 +1373,7 @@ mod tests {
             kind: Some("array".into()),
             description: None,
             variants: None,
+            default: None,
         };
         assert_eq!(map_type(&col, &opts_default()), "Vec<String>");
     }
@@ -915,6 +1385,7 @@ mod tests {
             kind: Some("array".into()),
             description: None,
             variants: None,
+            default: None,
         };
         assert_eq!(map_type(&col, &opts_uuid()), "Vec<uuid::Uuid>");
     }
@@ -940,7 +1411,7 @@ mod tests {
 
     #[test]
     fn emit_empty_definitions_contains_only_header() {
-        let api = OpenApi { definitions: BTreeMap::new() };
+        let api = OpenApi { definitions: BTreeMap::new(), paths: BTreeMap::new() };
         let out = emit(&api, &Options::default());
         assert!(out.contains("Auto-generated by"), "header should be present");
         assert!(!out.contains("pub struct"), "no structs should be emitted");
@@ -961,6 +1432,7 @@ mod tests {
                 });
                 m
             },
+            paths: BTreeMap::new(),
         };
         let opts = Options { schema: "inventory".into(), ..Options::default() };
         let out = emit(&api, &opts);
@@ -983,6 +1455,7 @@ mod tests {
                 });
                 m
             },
+            paths: BTreeMap::new(),
         };
         let out = emit(&api, &Options::default());
         assert!(out.contains("pub data: Option<Vec<u8>>,"), "bytea should map to Vec<u8>: {out}");
@@ -1002,12 +1475,14 @@ mod tests {
                             kind: Some("integer".into()),
                             description: Some("Primary key".into()),
                             variants: None,
+                            default: None,
                         });
                         p
                     },
                 });
                 m
             },
+            paths: BTreeMap::new(),
         };
         let out = emit(&api, &Options::default());
         assert!(out.contains("/// Primary key"), "description should become doc comment: {out}");
@@ -1030,6 +1505,7 @@ mod tests {
                 });
                 m
             },
+            paths: BTreeMap::new(),
         };
         let out = emit(&api, &Options::default());
         let a = out.find("a_col").unwrap();
@@ -1156,11 +1632,75 @@ mod tests {
     fn emit_includes_column_import_in_header() {
         let out = emit(&fixture(), &Options::default());
         assert!(
-            out.contains("use rust_supabase_sdk::{postgrest::Column, Row};"),
+            out.contains("use rust_supabase_sdk::{postgrest::{Column, Writable}, Row};"),
             "expected Column to be imported in the header:\n{out}"
         );
     }
 
+    // -----------------------------------------------------------------------
+    // Insert / Update payloads
+    // -----------------------------------------------------------------------
+
+    fn fixture_with_defaults() -> OpenApi {
+        let json = serde_json::json!({
+            "definitions": {
+                "posts": {
+                    "required": ["id", "title", "created_at"],
+                    "properties": {
+                        "id":         { "format": "bigint", "type": "integer", "default": 0 },
+                        "title":      { "format": "text", "type": "string" },
+                        "created_at": { "format": "timestamp with time zone", "type": "string", "default": "now()" },
+                        "body":       { "format": "text", "type": "string" },
+                        "type":       { "format": "text", "type": "string", "default": "'post'::text" }
+                    }
+                }
+            }
+        });
+        serde_json::from_value(json).unwrap()
+    }
+
+    #[test]
+    fn emit_insert_struct_requires_only_undefaulted_not_null_columns() {
+        let out = emit(&fixture_with_defaults(), &Options::default());
+        let start = out.find("pub struct PostsInsert {").unwrap();
+        let block = &out[start..out[start..].find("}\n").unwrap() + start];
+        assert!(block.contains("\n    pub title: String,"), "{block}");
+        assert!(block.contains(
+            "    #[serde(skip_serializing_if = \"Option::is_none\")]\n    pub id: Option<i64>,"
+        ));
+        assert!(block.contains("    pub created_at: Option<chrono::DateTime<chrono::Utc>>,"));
+        assert!(block.contains("    pub body: Option<String>,"));
+        assert!(block.contains(
+            "    #[serde(rename = \"type\", skip_serializing_if = \"Option::is_none\")]\n    pub r#type: Option<String>,"
+        ));
+        assert!(out.contains("/// Insert payload for `public.posts`."));
+    }
+
+    #[test]
+    fn emit_update_struct_makes_every_column_optional() {
+        let out = emit(&fixture_with_defaults(), &Options::default());
+        assert!(out.contains("#[derive(Debug, Clone, Default, Serialize)]\npub struct PostsUpdate {"));
+        let start = out.find("pub struct PostsUpdate {").unwrap();
+        let block = &out[start..out[start..].find("}\n").unwrap() + start];
+        assert!(block.contains(
+            "    #[serde(skip_serializing_if = \"Option::is_none\")]\n    pub title: Option<String>,"
+        ));
+        // Nullable columns can be set back to NULL with `Some(None)`.
+        assert!(block.contains("    pub body: Option<Option<String>>,"), "{block}");
+        assert!(block.contains("    pub r#type: Option<Option<String>>,"));
+        assert!(out.contains(
+            "impl Writable for Posts {\n    type Insert = PostsInsert;\n    type Update = PostsUpdate;\n}"
+        ));
+    }
+
+    #[test]
+    fn column_default_parses_from_openapi() {
+        let api = fixture_with_defaults();
+        let posts = &api.definitions["posts"];
+        assert_eq!(posts.properties["created_at"].default, Some(serde_json::json!("now()")));
+        assert_eq!(posts.properties["title"].default, None);
+    }
+
     // -----------------------------------------------------------------------
     // Enum codegen
     // -----------------------------------------------------------------------
@@ -1171,6 +1711,7 @@ mod tests {
             kind: Some("string".to_string()),
             description: None,
             variants: Some(variants.iter().map(|v| (*v).to_string()).collect()),
+            default: None,
         }
     }
 
@@ -1180,6 +1721,7 @@ mod tests {
             kind: Some("array".to_string()),
             description: None,
             variants: Some(variants.iter().map(|v| (*v).to_string()).collect()),
+            default: None,
         }
     }
 
@@ -1711,4 +2253,232 @@ mod tests {
         assert!(out.contains("pub enum BinState"), "missing stripped enum name:\n{out}");
         assert!(out.contains("pub s: BinState,"));
     }
+
+    // -----------------------------------------------------------------------
+    // Relation codegen
+    // -----------------------------------------------------------------------
+
+    fn fk(table: &str) -> String {
+        format!("Note:\nThis is a Foreign Key to `{table}.id`.<fk table='{table}' column='id'/>")
+    }
+
+    fn fixture_with_relations() -> OpenApi {
+        let json = serde_json::json!({
+            "definitions": {
+                "users": {
+                    "required": ["id"],
+                    "properties": {
+                        "id": { "format": "uuid", "type": "string" }
+                    }
+                },
+                "posts": {
+                    "required": ["id", "author_id"],
+                    "properties": {
+                        "id":        { "format": "uuid", "type": "string" },
+                        "author_id": { "format": "uuid", "type": "string", "description": fk("users") },
+                        "editor_id": { "format": "uuid", "type": "string", "description": fk("users") }
+                    }
+                },
+                "comments": {
+                    "required": ["id"],
+                    "properties": {
+                        "id":      { "format": "bigint", "type": "integer" },
+                        "post_id": { "format": "uuid", "type": "string", "description": fk("posts") },
+                        // Column literally named like the derived relation.
+                        "post":    { "format": "text", "type": "string" }
+                    }
+                }
+            }
+        });
+        serde_json::from_value(json).unwrap()
+    }
+
+    fn relations_for(api: &OpenApi) -> BTreeMap<String, Vec<RelationInfo>> {
+        let tables: Vec<(&String, &TableDef)> = api.definitions.iter().collect();
+        collect_relations(&tables)
+    }
+
+    #[test]
+    fn emit_primary_key_from_pk_notes() {
+        let json = serde_json::json!({
+            "definitions": {
+                "memberships": {
+                    "required": ["org_id", "user_id"],
+                    "properties": {
+                        "user_id": { "format": "uuid", "type": "string", "description": "Note:\nThis is a Primary Key.<pk/>" },
+                        "org_id":  { "format": "bigint", "type": "integer", "description": "Note:\nThis is a Primary Key.<pk/>" },
+                        "role":    { "format": "text", "type": "string" }
+                    }
+                },
+                "audit_log": { "properties": { "data": { "format": "json", "type": "object" } } }
+            }
+        });
+        let out = emit(&serde_json::from_value(json).unwrap(), &Options::default());
+        assert!(out.contains("    const PRIMARY_KEY: &'static [&'static str] = &[\"org_id\", \"user_id\"];"), "{out}");
+        assert!(out.contains("    const PRIMARY_KEY: &'static [&'static str] = &[];"));
+        assert!(out.contains("use rust_supabase_sdk::postgrest::Keyed;\n"));
+        assert!(out.contains("impl Keyed for Memberships {\n    type PrimaryKey = (i64, String);\n}"));
+        assert!(!out.contains("impl Keyed for AuditLog"));
+    }
+
+    #[test]
+    fn emit_single_column_key_and_no_keyed_import_without_keys() {
+        let json = serde_json::json!({
+            "definitions": {
+                "posts": {
+                    "required": ["id"],
+                    "properties": {
+                        "id": { "format": "bigint", "type": "integer", "description": "Note:\nThis is a Primary Key.<pk/>" }
+                    }
+                }
+            }
+        });
+        let out = emit(&serde_json::from_value(json).unwrap(), &Options::default());
+        assert!(out.contains("impl Keyed for Posts {\n    type PrimaryKey = i64;\n}"), "{out}");
+        assert!(!emit(&fixture(), &Options::default()).contains("postgrest::Keyed"));
+    }
+
+    #[test]
+    fn parse_fk_note_extracts_table() {
+        assert_eq!(parse_fk_note(&fk("users")), Some("users".into()));
+        assert_eq!(parse_fk_note("Note:\nThis is a Primary Key.<pk/>"), None);
+        assert_eq!(parse_fk_note("<fk table='public.users' column='id'/>"), Some("users".into()));
+    }
+
+    #[test]
+    fn collect_relations_names_both_directions() {
+        let rels = relations_for(&fixture_with_relations());
+        let posts: Vec<(&str, &str, bool)> = rels["posts"]
+            .iter()
+            .map(|r| (r.name.as_str(), r.target.as_str(), r.many))
+            .collect();
+        assert_eq!(
+            posts,
+            vec![("author", "users", false), ("editor", "users", false), ("comments", "comments", true)]
+        );
+        // Two keys from posts to users → qualified reverse names.
+        let users: Vec<&str> = rels["users"].iter().map(|r| r.name.as_str()).collect();
+        assert_eq!(users, vec!["posts_by_author", "posts_by_editor"]);
+    }
+
+    #[test]
+    fn collect_relations_avoids_column_name_clash() {
+        let rels = relations_for(&fixture_with_relations());
+        assert_eq!(rels["comments"][0].name, "post_rel");
+        assert_eq!(rels["comments"][0].hint, "post_id");
+    }
+
+    #[test]
+    fn collect_relations_skips_excluded_targets() {
+        let opts = Options { exclude: vec!["users".into()], ..Options::default() };
+        let out = emit(&fixture_with_relations(), &opts);
+        assert!(!out.contains("Relation<Posts, Users>"), "{out}");
+        assert!(out.contains("Relation<Posts, Comments>"), "{out}");
+    }
+
+    #[test]
+    fn emit_relations_block() {
+        let out = emit(&fixture_with_relations(), &Options::default());
+        assert!(out.contains("use rust_supabase_sdk::postgrest::{Embeddable, Related, Relation};"));
+        assert!(out.contains(
+            "pub const author: Relation<Posts, Users> = Relation::to_one(\"author\", Some(\"author_id\"));"
+        ));
+        assert!(out.contains(
+            "pub const comments: Relation<Posts, Comments> = Relation::to_many(\"comments\", Some(\"post_id\"));"
+        ));
+        // Unique target → Related impl; ambiguous target (users ×2) → none.
+        assert!(out.contains("impl Related<Comments> for Posts {"));
+        assert!(!out.contains("impl Related<Users> for Posts"));
+        assert!(out.contains("pub struct PostsWithRelations {"));
+        assert!(out.contains("    pub author: Option<Users>,"));
+        assert!(out.contains("    pub comments: Option<Vec<Comments>>,"));
+        assert!(out.contains("impl Embeddable for Posts {\n    type WithRelations = PostsWithRelations;"));
+    }
+
+    #[test]
+    fn emit_without_relations_skips_relation_import() {
+        let out = emit(&fixture(), &Options::default());
+        assert!(!out.contains("Relation"), "{out}");
+        assert!(!out.contains("WithRelations"));
+    }
+
+    // -----------------------------------------------------------------
+    // RPC functions
+    // -----------------------------------------------------------------
+
+    fn fixture_with_functions() -> OpenApi {
+        let json = serde_json::json!({
+            "paths": {
+                "/": {},
+                "/posts": { "get": { "parameters": [] } },
+                "/rpc/search_posts": {
+                    "get": { "parameters": [] },
+                    "post": {
+                        "summary": "Full-text post search",
+                        "parameters": [
+                            {
+                                "in": "body",
+                                "name": "args",
+                                "required": true,
+                                "schema": {
+                                    "type": "object",
+                                    "required": ["query"],
+                                    "properties": {
+                                        "query": { "format": "text", "type": "string" },
+                                        "max_rows": { "format": "integer", "type": "integer" },
+                                        "type": { "format": "text", "type": "string" }
+                                    }
+                                }
+                            },
+                            { "$ref": "#/parameters/preferParams" }
+                        ]
+                    }
+                },
+                "/rpc/touch": {
+                    "post": {
+                        "parameters": [{
+                            "in": "body",
+                            "name": "args",
+                            "schema": { "type": "object", "properties": {} }
+                        }]
+                    }
+                }
+            }
+        });
+        serde_json::from_value(json).unwrap()
+    }
+
+    #[test]
+    fn emit_functions_generates_rpc_impls() {
+        let out = emit(&fixture_with_functions(), &Options::default());
+        assert!(out.contains("pub mod rpc {"), "{out}");
+        assert!(out.contains("use rust_supabase_sdk::rpc::RpcFunction;"));
+        assert!(out.contains("    pub struct SearchPostsArgs {"));
+        assert!(out.contains("        pub query: String,"));
+        assert!(out.contains(
+            "        #[serde(default, skip_serializing_if = \"Option::is_none\")]\n        pub max_rows: Option<i32>,"
+        ));
+        assert!(out.contains(
+            "#[serde(rename = \"type\", default, skip_serializing_if = \"Option::is_none\")]\n        pub r#type: Option<String>,"
+        ));
+        assert!(out.contains("    /// Full-text post search\n    pub struct SearchPosts;"));
+        assert!(out.contains("        const NAME: &'static str = \"search_posts\";"));
+        assert!(out.contains("        type Returns = serde_json::Value;"));
+    }
+
+    #[test]
+    fn emit_functions_marks_only_get_capable_functions_read_only() {
+        let out = emit(&fixture_with_functions(), &Options::default());
+        let search = out.find("impl RpcFunction for SearchPosts").unwrap();
+        let touch = out.find("impl RpcFunction for Touch").unwrap();
+        assert!(out[search..touch].contains("const READ_ONLY: bool = true;"));
+        assert!(!out[touch..].contains("READ_ONLY"));
+        assert!(out.contains("    pub struct TouchArgs {\n    }"));
+    }
+
+    #[test]
+    fn emit_without_functions_has_no_rpc_module() {
+        let out = emit(&fixture(), &Options::default());
+        assert!(!out.contains("pub mod rpc"));
+    }
 }
@@ -7,6 +7,7 @@ use crate::error::{Result, SupabaseError};
 use crate::universals::{HttpMethod, RequestOptions};
 use crate::SupabaseClient;
 
+use super::mfa::{decode, Factor};
 use super::types::{AdminUserAttributes, OtpType, User};
 
 #[derive(Debug, Clone)]
@@ -152,6 +153,33 @@ impl AuthAdmin {
         Ok(())
     }
 
+    /// List the MFA factors enrolled on a user.
+    pub async fn list_factors(&self, user_id: &str) -> Result<Vec<Factor>> {
+        let value = self
+            .client
+            .request_with(
+                &format!("/auth/v1/admin/users/{user_id}/factors"),
+                HttpMethod::Get,
+                None,
+                &RequestOptions::auth(),
+            )
+            .await?;
+        decode(value)
+    }
+
+    /// Remove an MFA factor from a user, e.g. after they lost the device.
+    pub async fn delete_factor(&self, user_id: &str, factor_id: &str) -> Result<()> {
+        self.client
+            .request_with(
+                &format!("/auth/v1/admin/users/{user_id}/factors/{factor_id}"),
+                HttpMethod::Delete,
+                None,
+                &RequestOptions::auth(),
+            )
+            .await?;
+        Ok(())
+    }
+
     /// Invite a new user by email.
     pub async fn invite_user_by_email(
         &self,
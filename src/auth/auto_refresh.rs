@@ -0,0 +1,291 @@
+//! Background session refresh for long-running workers.
+//!
+//! ```no_run
+//! # use rust_supabase_sdk::SupabaseClient;
+//! # use rust_supabase_sdk::auth::AutoRefreshConfig;
+//! # async fn demo(client: SupabaseClient) -> rust_supabase_sdk::Result<()> {
+//! client.auth().sign_in_with_password("worker@example.com", "secret").await?;
+//! let refresh = client.auth().start_auto_refresh(AutoRefreshConfig::default())?;
+//! // … requests keep using a fresh access token …
+//! refresh.cancel();
+//! # Ok(()) }
+//! ```
+//!
+//! The task sleeps until `threshold` (minus up to `jitter`) before the
+//! stored session's `expires_at`, refreshes it, and goes back to sleep. It
+//! ends on [`Auth::sign_out`], when the session store is emptied, on a
+//! refresh the server rejects (e.g. a revoked refresh token), or on
+//! [`AutoRefreshHandle::cancel`].
+//!
+//! A refresh token is spent by the refresh that uses it. When several
+//! clients (or tasks) in one process share a
+//! [`SessionStore`](super::session_store::SessionStore), only one of them refreshes a given
+//! token — this task or an [`Auth::refresh_session`] call — and the others
+//! wait for it and pick the new session up from the store. The guard is
+//! in-memory: processes sharing a store through an external backend can
+//! still race.
+
+use std::sync::Mutex;
+use std::time::Duration;
+
+use tokio::task::AbortHandle;
+use tracing::{debug, warn};
+
+use crate::error::{AuthError, Result, SupabaseError};
+
+use super::{Auth, Session};
+
+const MIN_BACKOFF: Duration = Duration::from_secs(1);
+const MAX_BACKOFF: Duration = Duration::from_secs(60);
+/// How often a task waiting on another's refresh looks at the store again.
+const IN_FLIGHT_POLL: Duration = Duration::from_millis(100);
+
+/// Refresh tokens currently being exchanged, across every client in the
+/// process. Not shared with other processes.
+static IN_FLIGHT: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+/// Timing for [`Auth::start_auto_refresh`].
+#[derive(Debug, Clone, Copy)]
+pub struct AutoRefreshConfig {
+    /// Refresh this long before the access token expires. Defaults to 60
+    /// seconds.
+    pub threshold: Duration,
+    /// Refresh up to this much earlier still, at random, so workers that
+    /// signed in together don't all refresh at once. Defaults to 10 seconds.
+    pub jitter: Duration,
+}
+
+impl Default for AutoRefreshConfig {
+    fn default() -> Self {
+        Self {
+            threshold: Duration::from_secs(60),
+            jitter: Duration::from_secs(10),
+        }
+    }
+}
+
+/// Handle to a running auto-refresh task. Dropping it leaves the task
+/// running; call [`cancel`](Self::cancel) to stop it.
+#[derive(Debug, Clone)]
+pub struct AutoRefreshHandle {
+    abort: AbortHandle,
+}
+
+impl AutoRefreshHandle {
+    /// Stop the task. A refresh in progress is abandoned before it touches
+    /// the session store.
+    pub fn cancel(&self) {
+        self.abort.abort();
+    }
+
+    /// `true` until the task is cancelled or has stopped on its own.
+    pub fn is_running(&self) -> bool {
+        !self.abort.is_finished()
+    }
+}
+
+impl Auth {
+    /// Keep the stored session fresh from a background task. Must be called
+    /// from within a Tokio runtime.
+    pub fn start_auto_refresh(&self, config: AutoRefreshConfig) -> Result<AutoRefreshHandle> {
+        let runtime = tokio::runtime::Handle::try_current().map_err(|_| {
+            SupabaseError::Unexpected("start_auto_refresh needs a Tokio runtime".into())
+        })?;
+        let auth = self.clone();
+        let abort = runtime.spawn(auth.auto_refresh_loop(config)).abort_handle();
+        let mut tasks = match self.client.refresh_tasks.lock() {
+            Ok(guard) => guard,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        tasks.retain(|task| !task.is_finished());
+        tasks.push(abort.clone());
+        Ok(AutoRefreshHandle { abort })
+    }
+
+    async fn auto_refresh_loop(self, config: AutoRefreshConfig) {
+        let mut backoff = MIN_BACKOFF;
+        // The jitter is drawn once per refresh token: redrawing it on every
+        // wake-up would keep moving the deadline.
+        let mut jitter: Option<(String, Duration)> = None;
+        loop {
+            let Some(session) = self.client.session_store.get() else {
+                debug!(target: "supabase", "no session; auto-refresh stopped");
+                return;
+            };
+            let extra = match &jitter {
+                Some((token, extra)) if *token == session.refresh_token => *extra,
+                _ => {
+                    let extra = random_up_to(config.jitter);
+                    jitter = Some((session.refresh_token.clone(), extra));
+                    extra
+                }
+            };
+            // Never wake before half the token's lifetime: a lifetime shorter
+            // than the threshold would otherwise refresh in a tight loop.
+            let half_life = Duration::from_secs(session.expires_in.max(0) as u64 / 2);
+            let lead = (config.threshold + extra).min(half_life);
+            let wait = u64::try_from(session.seconds_until_expiry())
+                .map(|secs| Duration::from_secs(secs).saturating_sub(lead))
+                .unwrap_or_default();
+            tokio::time::sleep(wait).await;
+
+            // Another client sharing the store may have refreshed (or signed
+            // out) while we slept.
+            let Some(current) = self.client.session_store.get() else {
+                continue;
+            };
+            if current.refresh_token != session.refresh_token {
+                continue;
+            }
+            // Rounded up: `wait` was worked out in whole seconds.
+            let due = lead.as_secs() as i64 + i64::from(lead.subsec_nanos() > 0);
+            if !current.expires_within(due) {
+                continue;
+            }
+            match self.refresh_exclusive(&session.refresh_token, true).await {
+                Ok(_) => backoff = MIN_BACKOFF,
+                Err(e) if is_transient(&e) => {
+                    warn!(target: "supabase", error = %e, ?backoff, "session refresh failed; retrying");
+                    tokio::time::sleep(backoff).await;
+                    backoff = (backoff * 2).min(MAX_BACKOFF);
+                }
+                Err(e) => {
+                    warn!(target: "supabase", error = %e, "session refresh rejected; auto-refresh stopped");
+                    return;
+                }
+            }
+        }
+    }
+
+    /// Exchange `refresh_token` while holding its [`InFlight`] claim,
+    /// waiting out a refresh of the same token already in progress.
+    /// `from_store` says the token was just read from the session store: a
+    /// refresh that stored a newer session before the claim was taken has
+    /// spent it, and that session is returned instead.
+    pub(super) async fn refresh_exclusive(&self, refresh_token: &str, from_store: bool) -> Result<Session> {
+        let mut waited = false;
+        let _claim = loop {
+            match InFlight::claim(refresh_token) {
+                Some(claim) => break claim,
+                None => {
+                    waited = true;
+                    tokio::time::sleep(IN_FLIGHT_POLL).await;
+                }
+            }
+        };
+        if from_store || waited {
+            match self.client.session_store.get() {
+                Some(current) if current.refresh_token != refresh_token => return Ok(current),
+                None if from_store => {
+                    return Err(SupabaseError::Auth(AuthError::from_message(
+                        "signed out while the session was being refreshed",
+                    )))
+                }
+                _ => {}
+            }
+        }
+        self.exchange_refresh_token(refresh_token).await
+    }
+
+    /// Cancel every auto-refresh task started from this client (or a clone).
+    pub(crate) fn stop_auto_refresh(&self) {
+        let mut tasks = match self.client.refresh_tasks.lock() {
+            Ok(guard) => guard,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        for task in tasks.drain(..) {
+            task.abort();
+        }
+    }
+}
+
+/// Marks a refresh token as being exchanged until dropped — including when
+/// the task is cancelled mid-refresh.
+struct InFlight(String);
+
+impl InFlight {
+    fn claim(refresh_token: &str) -> Option<Self> {
+        let mut tokens = match IN_FLIGHT.lock() {
+            Ok(guard) => guard,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        if tokens.iter().any(|t| t == refresh_token) {
+            return None;
+        }
+        tokens.push(refresh_token.to_string());
+        Some(Self(refresh_token.to_string()))
+    }
+}
+
+impl Drop for InFlight {
+    fn drop(&mut self) {
+        let mut tokens = match IN_FLIGHT.lock() {
+            Ok(guard) => guard,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        tokens.retain(|t| *t != self.0);
+    }
+}
+
+/// Network trouble and server-side failures are worth retrying; a 4xx
+/// (revoked or already-used refresh token) is not.
+fn is_transient(e: &SupabaseError) -> bool {
+    match e {
+        SupabaseError::Transport(_) | SupabaseError::RetryExhausted { .. } => true,
+        SupabaseError::Auth(auth) => matches!(auth.status, Some(s) if s >= 500 || s == 429),
+        _ => false,
+    }
+}
+
+fn random_up_to(max: Duration) -> Duration {
+    let millis = max.as_millis() as u64;
+    if millis == 0 {
+        return Duration::ZERO;
+    }
+    Duration::from_millis(getrandom::u64().unwrap_or(0) % (millis + 1))
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+    use crate::error::AuthError;
+
+    #[test]
+    fn refresh_tokens_are_claimed_once() {
+        let first = InFlight::claim("rt-claim-test").unwrap();
+        assert!(InFlight::claim("rt-claim-test").is_none());
+        assert!(InFlight::claim("rt-other-test").is_some());
+        drop(first);
+        assert!(InFlight::claim("rt-claim-test").is_some());
+    }
+
+    #[test]
+    fn only_server_and_network_failures_are_retried() {
+        let status = |s| {
+            SupabaseError::Auth(AuthError {
+                status: Some(s),
+                ..AuthError::from_message("x")
+            })
+        };
+        assert!(is_transient(&status(503)));
+        assert!(is_transient(&status(429)));
+        assert!(!is_transient(&status(400)));
+        assert!(!is_transient(&SupabaseError::Unexpected("x".into())));
+    }
+
+    #[test]
+    fn jitter_stays_within_bounds() {
+        assert_eq!(random_up_to(Duration::ZERO), Duration::ZERO);
+        for _ in 0..100 {
+            assert!(random_up_to(Duration::from_millis(5)) <= Duration::from_millis(5));
+        }
+    }
+
+    #[test]
+    fn starting_outside_a_runtime_fails() {
+        let client = crate::SupabaseClient::new("https://x.supabase.co", "anon", None);
+        let err = client.auth().start_auto_refresh(AutoRefreshConfig::default()).unwrap_err();
+        assert!(err.to_string().contains("Tokio runtime"), "{err}");
+    }
+}
@@ -0,0 +1,110 @@
+//! Auth state change notifications — `supabase-js`'s `onAuthStateChange`.
+//!
+//! ```no_run
+//! # use rust_supabase_sdk::SupabaseClient;
+//! # use rust_supabase_sdk::auth::AuthEvent;
+//! # async fn demo(client: SupabaseClient) {
+//! let mut events = client.auth().on_state_change();
+//! tokio::spawn(async move {
+//!     while let Ok(event) = events.recv().await {
+//!         match event {
+//!             AuthEvent::TokenRefreshed(session) => { /* re-authenticate sockets */ }
+//!             AuthEvent::SignedOut => { /* purge caches */ }
+//!             _ => {}
+//!         }
+//!     }
+//! });
+//! # }
+//! ```
+//!
+//! Events are sent after the session store has been updated, to every
+//! receiver subscribed on this client or a clone of it. Clients that only
+//! share a [`SessionStore`](super::SessionStore) don't see each other's
+//! events. A receiver that falls more than 16 events behind gets
+//! [`RecvError::Lagged`](tokio::sync::broadcast::error::RecvError::Lagged)
+//! and then resumes with the oldest event still buffered.
+
+use tokio::sync::broadcast;
+
+use super::types::{Session, User};
+use super::Auth;
+
+/// Events buffered per receiver before it starts lagging.
+const CAPACITY: usize = 16;
+
+/// A change to the client's auth state.
+#[derive(Debug, Clone)]
+pub enum AuthEvent {
+    /// A session was stored by a sign-in, sign-up, OTP or code exchange, or
+    /// by [`Auth::set_session`].
+    SignedIn(Session),
+    /// The session was cleared by [`Auth::sign_out`] or
+    /// [`Auth::clear_session`].
+    SignedOut,
+    /// [`Auth::refresh_session`] (or an auto-refresh task) stored a new
+    /// session.
+    TokenRefreshed(Session),
+    /// [`Auth::update_user`] succeeded.
+    UserUpdated(User),
+    /// A [`OtpType::Recovery`](super::OtpType::Recovery) OTP was verified.
+    /// The user should now be asked for a new password.
+    PasswordRecovery(Session),
+    /// An MFA challenge was verified and the session upgraded to `aal2`.
+    MfaChallengeVerified(Session),
+}
+
+pub(crate) fn channel() -> broadcast::Sender<AuthEvent> {
+    broadcast::channel(CAPACITY).0
+}
+
+impl Auth {
+    /// Subscribe to auth state changes. Only events sent after this call
+    /// are received.
+    pub fn on_state_change(&self) -> broadcast::Receiver<AuthEvent> {
+        self.client.auth_events.subscribe()
+    }
+
+    pub(crate) fn emit(&self, event: AuthEvent) {
+        // No receivers is not an error: nobody is listening.
+        let _ = self.client.auth_events.send(event);
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+    use crate::SupabaseClient;
+    use tokio::sync::broadcast::error::TryRecvError;
+
+    fn session() -> Session {
+        serde_json::from_value(serde_json::json!({
+            "access_token": "tok",
+            "token_type": "bearer",
+            "expires_in": 3600,
+            "refresh_token": "rtok",
+            "user": { "id": "u1", "created_at": "2024-01-01T00:00:00Z" }
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn set_and_clear_session_are_broadcast_to_clones() {
+        let client = SupabaseClient::new("https://x.supabase.co", "anon", None);
+        let mut events = client.auth().on_state_change();
+        let clone = client.clone();
+        clone.auth().set_session(session());
+        clone.auth().clear_session();
+        assert!(matches!(events.try_recv(), Ok(AuthEvent::SignedIn(s)) if s.access_token == "tok"));
+        assert!(matches!(events.try_recv(), Ok(AuthEvent::SignedOut)));
+        assert_eq!(events.try_recv().unwrap_err(), TryRecvError::Empty);
+    }
+
+    #[test]
+    fn emitting_without_receivers_is_fine() {
+        let client = SupabaseClient::new("https://x.supabase.co", "anon", None);
+        client.auth().set_session(session());
+        let mut late = client.auth().on_state_change();
+        assert_eq!(late.try_recv().unwrap_err(), TryRecvError::Empty);
+    }
+}
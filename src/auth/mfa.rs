@@ -0,0 +1,401 @@
+//! Multi-factor authentication — modeled on `supabase-js`'s `auth.mfa`.
+//!
+//! ```no_run
+//! # use rust_supabase_sdk::SupabaseClient;
+//! # use rust_supabase_sdk::auth::mfa::{AuthenticatorAssuranceLevel, EnrollParams};
+//! # async fn demo(client: SupabaseClient) -> rust_supabase_sdk::Result<()> {
+//! let mfa = client.auth().mfa();
+//! let factor = mfa
+//!     .enroll(EnrollParams::Totp { friendly_name: None, issuer: Some("Console".into()) })
+//!     .await?;
+//! // Show `factor.totp` (QR code / secret) to the user, then:
+//! let session = mfa.challenge_and_verify(&factor.id, "123456").await?;
+//!
+//! let aal = mfa.get_authenticator_assurance_level()?;
+//! assert_eq!(aal.current_level, Some(AuthenticatorAssuranceLevel::Aal2));
+//! # let _ = session; Ok(()) }
+//! ```
+//!
+//! Every call acts for the signed-in user (the stored [`Session`]); a
+//! successful [`Mfa::verify`] stores the upgraded `aal2` session.
+
+use chrono::{DateTime, Utc};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use crate::error::{AuthError, Result, SupabaseError};
+use crate::universals::{HttpMethod, RequestOptions};
+use crate::SupabaseClient;
+
+use super::types::{Session, User};
+use super::{parse_session, Auth, AuthEvent};
+
+/// The kind of second factor.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FactorType {
+    Totp,
+    Phone,
+    /// A factor type this SDK doesn't know (e.g. WebAuthn).
+    #[serde(other)]
+    Other,
+}
+
+/// A factor enrolled on a user, as listed on [`User::factors`] and by
+/// [`AuthAdmin::list_factors`](super::AuthAdmin::list_factors).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Factor {
+    pub id: String,
+    #[serde(default)]
+    pub friendly_name: Option<String>,
+    pub factor_type: FactorType,
+    /// `"verified"` once a challenge for it has been verified, else
+    /// `"unverified"`.
+    pub status: String,
+    #[serde(default)]
+    pub phone: Option<String>,
+    #[serde(default)]
+    pub created_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+impl Factor {
+    /// `true` once the factor can be used to reach `aal2`.
+    pub fn is_verified(&self) -> bool {
+        self.status == "verified"
+    }
+}
+
+/// What to enroll with [`Mfa::enroll`].
+#[derive(Debug, Clone)]
+pub enum EnrollParams {
+    /// An authenticator app. `issuer` is shown in the app next to the account.
+    Totp {
+        friendly_name: Option<String>,
+        issuer: Option<String>,
+    },
+    /// Codes sent by SMS (or WhatsApp) to `phone`.
+    Phone {
+        friendly_name: Option<String>,
+        phone: String,
+    },
+}
+
+/// The TOTP secret of a newly enrolled factor.
+#[derive(Debug, Clone, Deserialize)]
+pub struct TotpEnrollment {
+    /// An SVG QR code (as a `data:` URI) for authenticator apps to scan.
+    pub qr_code: String,
+    pub secret: String,
+    /// The `otpauth://` URI the QR code encodes.
+    pub uri: String,
+}
+
+/// Response of [`Mfa::enroll`]. The factor stays `unverified` until a
+/// challenge for it is verified.
+#[derive(Debug, Clone, Deserialize)]
+pub struct EnrolledFactor {
+    pub id: String,
+    #[serde(rename = "type")]
+    pub factor_type: FactorType,
+    #[serde(default)]
+    pub friendly_name: Option<String>,
+    /// Set for TOTP factors.
+    #[serde(default)]
+    pub totp: Option<TotpEnrollment>,
+    /// Set for phone factors.
+    #[serde(default)]
+    pub phone: Option<String>,
+}
+
+/// A pending challenge, answered with [`Mfa::verify`] before `expires_at`.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Challenge {
+    pub id: String,
+    #[serde(rename = "type", default)]
+    pub factor_type: Option<FactorType>,
+    /// Unix timestamp in seconds.
+    pub expires_at: i64,
+}
+
+/// Response of [`Mfa::list_factors`].
+#[derive(Debug, Clone, Default)]
+pub struct Factors {
+    /// Every factor, verified or not.
+    pub all: Vec<Factor>,
+    /// Verified TOTP factors.
+    pub totp: Vec<Factor>,
+    /// Verified phone factors.
+    pub phone: Vec<Factor>,
+}
+
+/// Authenticator assurance level, the `aal` claim of an access token.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum AuthenticatorAssuranceLevel {
+    /// Signed in with one factor (password, OTP, OAuth, …).
+    #[serde(rename = "aal1")]
+    Aal1,
+    /// Signed in and verified a second factor.
+    #[serde(rename = "aal2")]
+    Aal2,
+}
+
+/// One entry of the `amr` claim: how and when the user authenticated.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AmrEntry {
+    /// `password`, `otp`, `oauth`, `totp`, `mfa/phone`, …
+    pub method: String,
+    /// Unix timestamp in seconds.
+    pub timestamp: i64,
+}
+
+/// Response of [`Mfa::get_authenticator_assurance_level`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AssuranceLevel {
+    /// The session's level; `None` without a session.
+    pub current_level: Option<AuthenticatorAssuranceLevel>,
+    /// The level the user can reach: `Aal2` once they have a verified
+    /// factor, else the current level.
+    pub next_level: Option<AuthenticatorAssuranceLevel>,
+    pub current_authentication_methods: Vec<AmrEntry>,
+}
+
+/// The `auth.mfa` namespace. Obtain one via [`Auth::mfa`].
+#[derive(Debug, Clone)]
+pub struct Mfa {
+    pub(crate) client: SupabaseClient,
+}
+
+impl Auth {
+    /// Multi-factor authentication for the signed-in user.
+    pub fn mfa(&self) -> Mfa {
+        Mfa {
+            client: self.client.clone(),
+        }
+    }
+}
+
+impl Mfa {
+    /// Start enrolling a factor. For TOTP, show the returned QR code or
+    /// secret to the user; the factor becomes usable once a challenge for
+    /// it is verified.
+    pub async fn enroll(&self, params: EnrollParams) -> Result<EnrolledFactor> {
+        let body = match params {
+            EnrollParams::Totp { friendly_name, issuer } => {
+                let mut body = json!({ "factor_type": "totp" });
+                if let Some(name) = friendly_name {
+                    body["friendly_name"] = json!(name);
+                }
+                if let Some(issuer) = issuer {
+                    body["issuer"] = json!(issuer);
+                }
+                body
+            }
+            EnrollParams::Phone { friendly_name, phone } => {
+                let mut body = json!({ "factor_type": "phone", "phone": phone });
+                if let Some(name) = friendly_name {
+                    body["friendly_name"] = json!(name);
+                }
+                body
+            }
+        };
+        let value = self
+            .request("/factors", HttpMethod::Post, Some(body))
+            .await?;
+        decode(value)
+    }
+
+    /// Create a challenge for `factor_id`. Phone factors send the code now,
+    /// over `channel` (`"sms"` by default, or `"whatsapp"`); TOTP factors
+    /// ignore `channel`.
+    pub async fn challenge(&self, factor_id: &str, channel: Option<&str>) -> Result<Challenge> {
+        let body = match channel {
+            Some(channel) => json!({ "channel": channel }),
+            None => json!({}),
+        };
+        let value = self
+            .request(&format!("/factors/{factor_id}/challenge"), HttpMethod::Post, Some(body))
+            .await?;
+        decode(value)
+    }
+
+    /// Answer challenge `challenge_id` with `code`. Returns the upgraded
+    /// session and writes it to the client's session store.
+    pub async fn verify(&self, factor_id: &str, challenge_id: &str, code: &str) -> Result<Session> {
+        let body = json!({ "challenge_id": challenge_id, "code": code });
+        let value = self
+            .request(&format!("/factors/{factor_id}/verify"), HttpMethod::Post, Some(body))
+            .await?;
+        let session = parse_session(value)?;
+        self.client.session_store.set(session.clone());
+        self.client.auth().emit(AuthEvent::MfaChallengeVerified(session.clone()));
+        Ok(session)
+    }
+
+    /// [`challenge`](Self::challenge) then [`verify`](Self::verify) in one
+    /// call — for TOTP factors, where the code is already on the user's
+    /// device.
+    pub async fn challenge_and_verify(&self, factor_id: &str, code: &str) -> Result<Session> {
+        let challenge = self.challenge(factor_id, None).await?;
+        self.verify(factor_id, &challenge.id, code).await
+    }
+
+    /// Remove a factor. Removing a verified factor requires an `aal2`
+    /// session.
+    pub async fn unenroll(&self, factor_id: &str) -> Result<()> {
+        self.request(&format!("/factors/{factor_id}"), HttpMethod::Delete, None)
+            .await?;
+        Ok(())
+    }
+
+    /// The signed-in user's factors, fetched fresh from `/user`.
+    pub async fn list_factors(&self) -> Result<Factors> {
+        let user = Auth {
+            client: self.client.clone(),
+        }
+        .get_user()
+        .await?;
+        let all = user_factors(&user)?;
+        let verified = |kind: FactorType| -> Vec<Factor> {
+            all.iter()
+                .filter(|f| f.factor_type == kind && f.is_verified())
+                .cloned()
+                .collect()
+        };
+        Ok(Factors {
+            totp: verified(FactorType::Totp),
+            phone: verified(FactorType::Phone),
+            all,
+        })
+    }
+
+    /// The current session's assurance level and authentication methods,
+    /// read from the `aal` and `amr` claims of its access token, plus the
+    /// level the user could reach. Does not hit the network; without a
+    /// session every field is empty.
+    pub fn get_authenticator_assurance_level(&self) -> Result<AssuranceLevel> {
+        let Some(session) = self.client.session_store.get() else {
+            return Ok(AssuranceLevel::default());
+        };
+        let claims = session.claims()?;
+        let current_level = claims.aal;
+        let current_authentication_methods = claims.amr;
+        let has_verified_factor = user_factors(&session.user)?.iter().any(Factor::is_verified);
+        let next_level = if has_verified_factor {
+            Some(AuthenticatorAssuranceLevel::Aal2)
+        } else {
+            current_level
+        };
+        Ok(AssuranceLevel {
+            current_level,
+            next_level,
+            current_authentication_methods,
+        })
+    }
+
+    async fn request(&self, path: &str, method: HttpMethod, body: Option<Value>) -> Result<Value> {
+        let session = self.client.session_store.get().ok_or_else(|| {
+            SupabaseError::Auth(AuthError::from_message("MFA requires a signed-in session"))
+        })?;
+        let opts = RequestOptions {
+            bearer_override: Some(session.access_token),
+            ..RequestOptions::auth()
+        };
+        self.client
+            .request_with(&format!("/auth/v1{path}"), method, body, &opts)
+            .await
+    }
+}
+
+/// Decode [`User::factors`] (kept as raw JSON on `User`).
+fn user_factors(user: &User) -> Result<Vec<Factor>> {
+    match &user.factors {
+        Some(factors) => decode(Value::Array(factors.clone())),
+        None => Ok(Vec::new()),
+    }
+}
+
+pub(crate) fn decode<T: DeserializeOwned>(value: Value) -> Result<T> {
+    serde_json::from_value(value.clone()).map_err(|e| SupabaseError::Decode {
+        message: e.to_string(),
+        body: value.to_string(),
+    })
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+    use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+    use base64::Engine;
+
+    fn jwt(claims: Value) -> String {
+        format!("e30.{}.sig", URL_SAFE_NO_PAD.encode(claims.to_string()))
+    }
+
+    fn session(token: String, factors: Value) -> Session {
+        serde_json::from_value(json!({
+            "access_token": token,
+            "token_type": "bearer",
+            "expires_in": 3600,
+            "refresh_token": "rt",
+            "user": {
+                "id": "u1", "created_at": "2024-01-01T00:00:00Z", "factors": factors
+            }
+        }))
+        .unwrap()
+    }
+
+    fn mfa_with(session: Option<Session>) -> Mfa {
+        let client = SupabaseClient::new("https://x.supabase.co", "anon", None);
+        if let Some(s) = session {
+            client.session_store.set(s);
+        }
+        client.auth().mfa()
+    }
+
+    #[test]
+    fn assurance_level_reads_aal_and_amr_claims() {
+        let token = jwt(json!({
+            "sub": "u1", "aal": "aal1",
+            "amr": [{ "method": "password", "timestamp": 1700000000 }]
+        }));
+        let factors = json!([{
+            "id": "f1", "factor_type": "totp", "status": "verified",
+            "created_at": "2024-01-01T00:00:00Z", "updated_at": "2024-01-01T00:00:00Z"
+        }]);
+        let level = mfa_with(Some(session(token, factors)))
+            .get_authenticator_assurance_level()
+            .unwrap();
+        assert_eq!(level.current_level, Some(AuthenticatorAssuranceLevel::Aal1));
+        assert_eq!(level.next_level, Some(AuthenticatorAssuranceLevel::Aal2));
+        assert_eq!(
+            level.current_authentication_methods,
+            [AmrEntry { method: "password".into(), timestamp: 1_700_000_000 }]
+        );
+    }
+
+    #[test]
+    fn next_level_stays_without_verified_factors() {
+        let token = jwt(json!({ "aal": "aal1" }));
+        let factors = json!([{ "id": "f1", "factor_type": "phone", "status": "unverified" }]);
+        let level = mfa_with(Some(session(token, factors)))
+            .get_authenticator_assurance_level()
+            .unwrap();
+        assert_eq!(level.next_level, Some(AuthenticatorAssuranceLevel::Aal1));
+        assert_eq!(mfa_with(None).get_authenticator_assurance_level().unwrap(), AssuranceLevel::default());
+
+        let err = mfa_with(Some(session("opaque".into(), Value::Null)))
+            .get_authenticator_assurance_level()
+            .unwrap_err();
+        assert!(err.to_string().contains("JWT"), "{err}");
+    }
+
+    #[test]
+    fn unknown_factor_types_still_decode() {
+        let f: Factor = decode(json!({ "id": "f", "factor_type": "webauthn", "status": "verified" })).unwrap();
+        assert_eq!(f.factor_type, FactorType::Other);
+    }
+}
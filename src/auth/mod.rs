@@ -22,14 +22,22 @@ use crate::error::{AuthError, Result, SupabaseError};
 use crate::universals::{HttpMethod, RequestOptions};
 use crate::SupabaseClient;
 
+use oauth::Pkce;
+
 pub mod admin;
+pub mod auto_refresh;
+pub mod events;
+pub mod mfa;
 pub mod oauth;
 pub mod session_store;
 pub mod types;
 
 pub use admin::AuthAdmin;
+pub use auto_refresh::{AutoRefreshConfig, AutoRefreshHandle};
+pub use events::AuthEvent;
+pub use mfa::Mfa;
 pub use types::{
-    AdminUserAttributes, Identity, OAuthFlow, OAuthOptions, OAuthProvider, OtpOptions,
+    AdminUserAttributes, AuthFlow, Identity, OAuthFlow, OAuthOptions, OAuthProvider, OtpOptions,
     OtpRecipient, OtpType, ResetPasswordOptions, Session, SignOutScope, SignUpOptions,
     UpdateUserAttributes, User, VerifyOtpParams,
 };
@@ -62,15 +70,17 @@ impl Auth {
         self.client.session_store.get()
     }
 
-    /// Replace the active session.
+    /// Replace the active session. Sends [`AuthEvent::SignedIn`].
     pub fn set_session(&self, session: Session) {
-        self.client.session_store.set(session);
+        self.client.session_store.set(session.clone());
+        self.emit(AuthEvent::SignedIn(session));
     }
 
     /// Clear the cached session locally (no network call). See [`Auth::sign_out`]
-    /// to also revoke the session on the server.
+    /// to also revoke the session on the server. Sends [`AuthEvent::SignedOut`].
     pub fn clear_session(&self) {
         self.client.session_store.clear();
+        self.emit(AuthEvent::SignedOut);
     }
 
     /// Register a new user. Equivalent to `supabase.auth.signUp`.
@@ -96,6 +106,7 @@ impl Auth {
         if value.get("access_token").is_some() {
             let session = parse_session(value)?;
             self.client.session_store.set(session.clone());
+            self.emit(AuthEvent::SignedIn(session.clone()));
             Ok(session)
         } else {
             Err(SupabaseError::Auth(AuthError::from_message(
@@ -117,6 +128,7 @@ impl Auth {
         };
         let session = self.token_request("password", body).await?;
         self.client.session_store.set(session.clone());
+        self.emit(AuthEvent::SignedIn(session.clone()));
         Ok(session)
     }
 
@@ -147,6 +159,9 @@ impl Auth {
         if let Some(channel) = options.channel {
             body["channel"] = json!(channel);
         }
+        if body.get("email").is_some() {
+            self.attach_pkce(&mut body)?;
+        }
         self.client
             .request_with(
                 &self.endpoint("/otp"),
@@ -160,6 +175,11 @@ impl Auth {
 
     /// Verify an OTP / magic-link code and exchange it for a session.
     pub async fn verify_otp(&self, params: VerifyOtpParams) -> Result<Session> {
+        let otp_type = match &params {
+            VerifyOtpParams::Email { otp_type, .. }
+            | VerifyOtpParams::Phone { otp_type, .. }
+            | VerifyOtpParams::TokenHash { otp_type, .. } => *otp_type,
+        };
         let body = match params {
             VerifyOtpParams::Email { email, token, otp_type } => json!({
                 "email": email, "token": token, "type": otp_type.as_str()
@@ -182,6 +202,10 @@ impl Auth {
             .await?;
         let session = parse_session(value)?;
         self.client.session_store.set(session.clone());
+        self.emit(match otp_type {
+            OtpType::Recovery => AuthEvent::PasswordRecovery(session.clone()),
+            _ => AuthEvent::SignedIn(session.clone()),
+        });
         Ok(session)
     }
 
@@ -220,6 +244,7 @@ impl Auth {
             .await?;
         let session = parse_session(value)?;
         self.client.session_store.set(session.clone());
+        self.emit(AuthEvent::SignedIn(session.clone()));
         Ok(session)
     }
 
@@ -236,25 +261,58 @@ impl Auth {
         }
         let session = self.token_request("id_token", body).await?;
         self.client.session_store.set(session.clone());
+        self.emit(AuthEvent::SignedIn(session.clone()));
         Ok(session)
     }
 
     /// Build the authorization URL for an OAuth flow. The caller is responsible
     /// for directing the user there and (eventually) exchanging the returned
     /// `code` via [`Auth::exchange_code_for_session`].
+    ///
+    /// This is the implicit flow whatever the client's [`AuthFlow`]; see
+    /// [`Auth::sign_in_with_oauth_pkce`] for PKCE.
     pub fn sign_in_with_oauth(
         &self,
         provider: impl Into<String>,
         options: OAuthOptions,
     ) -> OAuthFlow {
-        oauth::build_authorize_url(&self.client.url, provider.into(), options)
+        oauth::build_authorize_url(&self.client.url, provider.into(), options, None)
     }
 
-    /// Exchange a PKCE/OAuth `code` for a session.
+    /// Build the authorization URL for a PKCE OAuth flow — for CLIs and
+    /// desktop apps, which can't keep a client secret. A fresh code verifier
+    /// goes to the client's [`CodeVerifierStore`](session_store::CodeVerifierStore)
+    /// and its S256 challenge into the URL. After the redirect, pass the
+    /// `code` to [`Auth::exchange_code_for_session`].
+    pub fn sign_in_with_oauth_pkce(
+        &self,
+        provider: impl Into<String>,
+        options: OAuthOptions,
+    ) -> Result<OAuthFlow> {
+        let pkce = Pkce::generate()?;
+        let flow = oauth::build_authorize_url(&self.client.url, provider.into(), options, Some(&pkce));
+        self.client.code_verifier_store.set(pkce.verifier);
+        Ok(flow)
+    }
+
+    /// Exchange a PKCE/OAuth `code` for a session. Sends the stored code
+    /// verifier along, if any. A verifier is good for one exchange, so it is
+    /// cleared once the server has answered: on success, or when it rejects
+    /// the code with a 4xx. Transport failures, 5xx and 429 keep it so the
+    /// exchange can be retried.
     pub async fn exchange_code_for_session(&self, code: &str) -> Result<Session> {
-        let body = json!({ "auth_code": code });
-        let session = self.token_request("pkce", body).await?;
+        let mut body = json!({ "auth_code": code });
+        let verifier = self.client.code_verifier_store.get();
+        if let Some(verifier) = &verifier {
+            body["code_verifier"] = json!(verifier);
+        }
+        let result = self.token_request("pkce", body).await;
+        if verifier.is_some() && verifier_spent(&result) {
+            self.client.code_verifier_store.clear();
+        }
+        let session = result?;
         self.client.session_store.set(session.clone());
+        self.emit(AuthEvent::SignedIn(session.clone()));
         Ok(session)
     }
 
@@ -294,10 +352,16 @@ impl Auth {
             .client
             .request_with(&self.endpoint("/user"), HttpMethod::Put, Some(body), &opts)
             .await?;
-        serde_json::from_value(value.clone()).map_err(|e| SupabaseError::Decode {
+        let user: User = serde_json::from_value(value.clone()).map_err(|e| SupabaseError::Decode {
             message: e.to_string(),
             body: value.to_string(),
-        })
+        })?;
+        if let Some(mut session) = session {
+            session.user = user.clone();
+            self.client.session_store.set(session);
+        }
+        self.emit(AuthEvent::UserUpdated(user.clone()));
+        Ok(user)
     }
 
     /// Send a password-recovery email.
@@ -313,6 +377,7 @@ impl Auth {
         if let Some(captcha) = options.captcha_token {
             body["gotrue_meta_security"] = json!({ "captcha_token": captcha });
         }
+        self.attach_pkce(&mut body)?;
         self.client
             .request_with(
                 &self.endpoint("/recover"),
@@ -326,24 +391,36 @@ impl Auth {
 
     /// Refresh the access token using a refresh token. Defaults to the
     /// currently stored session's refresh token when `refresh_token` is `None`.
+    ///
+    /// A refresh token can only be spent once. If another refresh in this
+    /// process (an [auto-refresh](Auth::start_auto_refresh) task, or a
+    /// client sharing the [`SessionStore`](session_store::SessionStore)) is
+    /// already exchanging the same token, this waits for it and returns the
+    /// session it stored. Processes that share a store through an external
+    /// backend aren't coordinated.
     pub async fn refresh_session(&self, refresh_token: Option<&str>) -> Result<Session> {
-        let token = match refresh_token {
-            Some(t) => t.to_string(),
-            None => self
-                .client
-                .session_store
-                .get()
-                .map(|s| s.refresh_token)
-                .ok_or_else(|| {
-                    SupabaseError::Auth(AuthError::from_message(
-                        "No refresh token available — call sign_in_with_password first",
-                    ))
-                })?,
+        let stored = self.client.session_store.get().map(|s| s.refresh_token);
+        let token = match (refresh_token, &stored) {
+            (Some(t), _) => t,
+            (None, Some(t)) => t.as_str(),
+            (None, None) => {
+                return Err(SupabaseError::Auth(AuthError::from_message(
+                    "No refresh token available — call sign_in_with_password first",
+                )))
+            }
         };
+        let from_store = stored.as_deref() == Some(token);
+        self.refresh_exclusive(token, from_store).await
+    }
+
+    /// Spend `refresh_token` and store the session it returns. Only called
+    /// with the token claimed; see [`refresh_exclusive`](Self::refresh_exclusive).
+    async fn exchange_refresh_token(&self, refresh_token: &str) -> Result<Session> {
         let session = self
-            .token_request("refresh_token", json!({ "refresh_token": token }))
+            .token_request("refresh_token", json!({ "refresh_token": refresh_token }))
             .await?;
         self.client.session_store.set(session.clone());
+        self.emit(AuthEvent::TokenRefreshed(session.clone()));
         Ok(session)
     }
 
@@ -363,7 +440,10 @@ impl Auth {
     }
 
     /// Revoke the current session on the server and clear local state.
+    /// Also stops any [auto-refresh](Auth::start_auto_refresh) task started
+    /// from this client.
     pub async fn sign_out(&self, scope: SignOutScope) -> Result<()> {
+        self.stop_auto_refresh();
         let session = self.client.session_store.get();
         if let Some(s) = &session {
             let opts = RequestOptions {
@@ -378,6 +458,21 @@ impl Auth {
                 .await?;
         }
         self.client.session_store.clear();
+        self.emit(AuthEvent::SignedOut);
+        Ok(())
+    }
+
+    /// Internal: on a [`AuthFlow::Pkce`] client, store a fresh code verifier
+    /// and add its challenge to an email-link request body.
+    fn attach_pkce(&self, body: &mut Value) -> Result<()> {
+        if self.client.auth_flow != AuthFlow::Pkce {
+            return Ok(());
+        }
+        let pkce = Pkce::generate()?;
+        for (k, v) in pkce.params() {
+            body[k] = json!(v);
+        }
+        self.client.code_verifier_store.set(pkce.verifier);
         Ok(())
     }
 
@@ -392,6 +487,16 @@ impl Auth {
     }
 }
 
+/// Whether a code exchange used up its verifier: the server took the code,
+/// or definitively rejected it.
+fn verifier_spent(result: &Result<Session>) -> bool {
+    match result {
+        Ok(_) => true,
+        Err(SupabaseError::Auth(e)) => matches!(e.status, Some(s @ 400..=499) if s != 429),
+        Err(_) => false,
+    }
+}
+
 /// Decode a GoTrue session payload, filling in `expires_at` if absent.
 pub(crate) fn parse_session(value: Value) -> Result<Session> {
     let mut session: Session =
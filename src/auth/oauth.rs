@@ -1,15 +1,52 @@
-//! OAuth helpers — constructs the authorization URL for the `authorize` flow.
+//! OAuth helpers — constructs the authorization URL for the `authorize` flow,
+//! and the PKCE code verifier / challenge pair.
+
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use sha2::{Digest, Sha256};
+
+use crate::error::{Result, SupabaseError};
 
 use super::types::{OAuthFlow, OAuthOptions, OAuthProvider};
 
+/// A PKCE code verifier and its S256 challenge (RFC 7636).
+#[derive(Debug, Clone)]
+pub(crate) struct Pkce {
+    pub(crate) verifier: String,
+    pub(crate) challenge: String,
+}
+
+impl Pkce {
+    /// A fresh verifier: 64 random bytes, base64url-encoded (86 characters).
+    pub(crate) fn generate() -> Result<Self> {
+        let mut bytes = [0u8; 64];
+        getrandom::fill(&mut bytes).map_err(|e| {
+            SupabaseError::Unexpected(format!("failed to generate a PKCE code verifier: {e}"))
+        })?;
+        Ok(Self::from_verifier(URL_SAFE_NO_PAD.encode(bytes)))
+    }
+
+    fn from_verifier(verifier: String) -> Self {
+        let challenge = URL_SAFE_NO_PAD.encode(Sha256::digest(verifier.as_bytes()));
+        Self { verifier, challenge }
+    }
+
+    /// `code_challenge` / `code_challenge_method` as GoTrue expects them.
+    pub(crate) fn params(&self) -> [(&'static str, &str); 2] {
+        [("code_challenge", &self.challenge), ("code_challenge_method", "s256")]
+    }
+}
+
 /// Build the URL the user should be redirected to in order to start an OAuth flow.
 ///
-/// This is the non-PKCE flow. After the provider redirects back to your app,
-/// pass the resulting `code` to [`Auth::exchange_code_for_session`](super::Auth::exchange_code_for_session).
+/// Without `pkce` this is the implicit flow. With it, the URL carries the
+/// code challenge and the provider redirects back with a `code` for
+/// [`Auth::exchange_code_for_session`](super::Auth::exchange_code_for_session).
 pub(crate) fn build_authorize_url(
     supabase_url: &str,
     provider: OAuthProvider,
     options: OAuthOptions,
+    pkce: Option<&Pkce>,
 ) -> OAuthFlow {
     let mut params: Vec<(String, String)> = vec![("provider".to_string(), provider.clone())];
 
@@ -22,6 +59,9 @@ pub(crate) fn build_authorize_url(
     for (k, v) in options.query_params {
         params.push((k, v));
     }
+    for (k, v) in pkce.iter().flat_map(|p| p.params()) {
+        params.push((k.to_string(), v.to_string()));
+    }
 
     let query: Vec<String> = params
         .into_iter()
@@ -33,6 +73,7 @@ pub(crate) fn build_authorize_url(
 }
 
 #[cfg(test)]
+#[allow(clippy::unwrap_used)]
 mod tests {
     use super::*;
     use std::collections::HashMap;
@@ -43,6 +84,7 @@ mod tests {
             "https://example.supabase.co",
             "github".into(),
             OAuthOptions::default(),
+            None,
         );
         assert_eq!(flow.provider, "github");
         assert_eq!(
@@ -61,6 +103,7 @@ mod tests {
                 scopes: Some("openid email profile".into()),
                 ..Default::default()
             },
+            None,
         );
         assert!(flow.url.starts_with("https://example.supabase.co/auth/v1/authorize?"));
         assert!(flow.url.contains("provider=google"));
@@ -82,8 +125,34 @@ mod tests {
                 query_params: params,
                 ..Default::default()
             },
+            None,
         );
         assert!(flow.url.contains("access_type=offline"));
         assert!(flow.url.contains("prompt=consent"));
     }
+
+    #[test]
+    fn pkce_challenge_is_s256_of_verifier() {
+        // base64url(sha256("test-verifier")), unpadded.
+        let pkce = Pkce::from_verifier("test-verifier".into());
+        assert_eq!(pkce.challenge, "JBbiqONGWPaAmwXk_8bT6UnlPfrn65D32eZlJS-zGG0");
+
+        let pkce = Pkce::generate().unwrap();
+        assert_eq!(pkce.verifier.len(), 86);
+        assert_ne!(pkce.verifier, Pkce::generate().unwrap().verifier);
+        let flow = build_authorize_url(
+            "https://example.supabase.co",
+            "github".into(),
+            OAuthOptions::default(),
+            Some(&pkce),
+        );
+        assert_eq!(
+            flow.url,
+            format!(
+                "https://example.supabase.co/auth/v1/authorize?provider=github\
+                 &code_challenge={}&code_challenge_method=s256",
+                pkce.challenge
+            )
+        );
+    }
 }
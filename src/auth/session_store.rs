@@ -3,6 +3,12 @@
 //! The default is [`InMemorySessionStore`] — a process-local `RwLock`. Plug in
 //! your own implementation via [`ClientBuilder::session_store`](crate::ClientBuilder::session_store)
 //! to persist to disk, the OS keyring, or a custom KV store.
+//!
+//! [`CodeVerifierStore`] does the same for the PKCE code verifier, which has
+//! to survive from starting a flow until
+//! [`Auth::exchange_code_for_session`](super::Auth::exchange_code_for_session)
+//! — possibly across a process restart when the redirect lands in a fresh
+//! CLI invocation.
 
 use std::sync::RwLock;
 
@@ -53,6 +59,52 @@ impl SessionStore for InMemorySessionStore {
     }
 }
 
+/// A backing store for the PKCE code verifier of the flow in progress.
+///
+/// Set by [`Auth::sign_in_with_oauth_pkce`](super::Auth::sign_in_with_oauth_pkce)
+/// (and by magic-link / password-recovery requests on a PKCE client), read
+/// and cleared by [`Auth::exchange_code_for_session`](super::Auth::exchange_code_for_session).
+pub trait CodeVerifierStore: Send + Sync + std::fmt::Debug {
+    fn get(&self) -> Option<String>;
+    fn set(&self, verifier: String);
+    fn clear(&self);
+}
+
+/// Default in-memory verifier store. Not persisted across restarts.
+#[derive(Debug, Default)]
+pub struct InMemoryCodeVerifierStore {
+    inner: RwLock<Option<String>>,
+}
+
+impl InMemoryCodeVerifierStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl CodeVerifierStore for InMemoryCodeVerifierStore {
+    fn get(&self) -> Option<String> {
+        match self.inner.read() {
+            Ok(guard) => guard.clone(),
+            Err(poisoned) => poisoned.into_inner().clone(),
+        }
+    }
+
+    fn set(&self, verifier: String) {
+        match self.inner.write() {
+            Ok(mut guard) => *guard = Some(verifier),
+            Err(poisoned) => *poisoned.into_inner() = Some(verifier),
+        }
+    }
+
+    fn clear(&self) {
+        match self.inner.write() {
+            Ok(mut guard) => *guard = None,
+            Err(poisoned) => *poisoned.into_inner() = None,
+        }
+    }
+}
+
 #[cfg(test)]
 #[allow(clippy::unwrap_used)]
 mod tests {
@@ -199,4 +251,14 @@ mod tests {
         store.clear();
         assert!(store.get().is_none());
     }
+
+    #[test]
+    fn code_verifier_store_round_trips() {
+        let store = InMemoryCodeVerifierStore::new();
+        assert!(store.get().is_none());
+        store.set("verifier".into());
+        assert_eq!(store.get().as_deref(), Some("verifier"));
+        store.clear();
+        assert!(store.get().is_none());
+    }
 }
@@ -241,6 +241,20 @@ impl SignOutScope {
     }
 }
 
+/// How email links (magic links, password recovery) hand the session back.
+/// Set per client with [`ClientBuilder::auth_flow`](crate::ClientBuilder::auth_flow).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum AuthFlow {
+    /// Tokens arrive in the redirect URL fragment.
+    #[default]
+    Implicit,
+    /// A `code` arrives in the redirect URL; exchange it with
+    /// [`Auth::exchange_code_for_session`](super::Auth::exchange_code_for_session),
+    /// which proves the request came from this client with the stored code
+    /// verifier.
+    Pkce,
+}
+
 /// OAuth provider name. Strings are passed through to GoTrue so any provider
 /// you've enabled in the dashboard is valid.
 pub type OAuthProvider = String;
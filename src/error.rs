@@ -34,6 +34,11 @@ pub enum SupabaseError {
     #[error("Not found: {resource}")]
     NotFound { resource: String },
 
+    /// A guarded write matched no row: it was changed or deleted since it
+    /// was read.
+    #[error("Conflict: no row matched {resource}")]
+    Conflict { resource: String },
+
     #[error("Exceeded {attempts} retries (last status: {last_status:?})")]
     RetryExhausted { attempts: u32, last_status: Option<u16> },
 
@@ -292,6 +297,12 @@ mod tests {
         assert!(s.contains("users"), "display={s}");
     }
 
+    #[test]
+    fn supabase_error_conflict_variant() {
+        let e = SupabaseError::Conflict { resource: "posts (id=1)".into() };
+        assert_eq!(e.to_string(), "Conflict: no row matched posts (id=1)");
+    }
+
     #[test]
     fn supabase_error_decode_variant() {
         let e = SupabaseError::Decode { message: "bad json".into(), body: "{}".into() };
@@ -0,0 +1,157 @@
+//! Supabase access-token claims, and (feature `jwt`) local verification.
+//!
+//! [`Session::claims`] and [`decode_unverified`] read a token's payload
+//! without checking its signature — fine for a token this client just got
+//! from GoTrue, never for one a caller handed you. A backend receiving
+//! tokens from browsers verifies them with [`JwtVerifier`] instead of
+//! calling [`Auth::get_user`](crate::Auth::get_user) on every request:
+//!
+//! ```no_run
+//! # #[cfg(feature = "jwt")]
+//! # async fn demo(client: rust_supabase_sdk::SupabaseClient, bearer: &str) -> rust_supabase_sdk::Result<()> {
+//! use rust_supabase_sdk::jwt::JwtVerifier;
+//!
+//! // Once, at startup. Clones share the JWKS cache.
+//! let verifier = JwtVerifier::new(&client);
+//!
+//! // Per request.
+//! let claims = verifier.verify(bearer).await?;
+//! println!("{} ({})", claims.sub, claims.role);
+//! # Ok(()) }
+//! ```
+
+use chrono::Utc;
+use serde::{Deserialize, Deserializer, Serialize};
+use serde_json::{Map, Value};
+
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+
+use crate::auth::mfa::{AmrEntry, AuthenticatorAssuranceLevel};
+use crate::auth::Session;
+use crate::error::{AuthError, Result, SupabaseError};
+
+#[cfg(feature = "jwt")]
+mod verify;
+
+#[cfg(feature = "jwt")]
+pub use verify::JwtVerifier;
+
+/// The claims of a Supabase access token. Claims this type doesn't name
+/// (custom claims added by an auth hook, for one) land in `extra`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Claims {
+    /// The user id.
+    #[serde(default)]
+    pub sub: String,
+    /// Postgres role: `authenticated`, `anon`, `service_role`, …
+    #[serde(default)]
+    pub role: String,
+    #[serde(default)]
+    pub aal: Option<AuthenticatorAssuranceLevel>,
+    #[serde(default)]
+    pub amr: Vec<AmrEntry>,
+    #[serde(default)]
+    pub session_id: Option<String>,
+    #[serde(default)]
+    pub email: Option<String>,
+    #[serde(default)]
+    pub phone: Option<String>,
+    #[serde(default)]
+    pub is_anonymous: bool,
+    #[serde(default)]
+    pub app_metadata: Value,
+    #[serde(default)]
+    pub user_metadata: Value,
+    /// Audiences; a single-string `aud` decodes as one entry.
+    #[serde(default, deserialize_with = "one_or_many")]
+    pub aud: Vec<String>,
+    #[serde(default)]
+    pub iss: Option<String>,
+    /// Issued-at, Unix timestamp in seconds.
+    #[serde(default)]
+    pub iat: Option<i64>,
+    /// Expiry, Unix timestamp in seconds.
+    #[serde(default)]
+    pub exp: i64,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+impl Claims {
+    /// Has `exp` passed? A token without `exp` counts as expired.
+    pub fn is_expired(&self) -> bool {
+        self.exp <= Utc::now().timestamp()
+    }
+}
+
+fn one_or_many<'de, D: Deserializer<'de>>(de: D) -> std::result::Result<Vec<String>, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum OneOrMany {
+        One(String),
+        Many(Vec<String>),
+    }
+    Ok(match Option::<OneOrMany>::deserialize(de)? {
+        Some(OneOrMany::One(aud)) => vec![aud],
+        Some(OneOrMany::Many(aud)) => aud,
+        None => Vec::new(),
+    })
+}
+
+/// Decode the claims of `token` **without** checking its signature, expiry
+/// or audience.
+pub fn decode_unverified(token: &str) -> Result<Claims> {
+    let invalid = || SupabaseError::Auth(AuthError::from_message("access token is not a valid JWT"));
+    let payload = token.split('.').nth(1).ok_or_else(invalid)?;
+    let bytes = URL_SAFE_NO_PAD
+        .decode(payload.trim_end_matches('='))
+        .map_err(|_| invalid())?;
+    serde_json::from_slice(&bytes).map_err(|_| invalid())
+}
+
+impl Session {
+    /// The claims of this session's access token, via [`decode_unverified`].
+    pub fn claims(&self) -> Result<Claims> {
+        decode_unverified(&self.access_token)
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn jwt(claims: Value) -> String {
+        format!("e30.{}.sig", URL_SAFE_NO_PAD.encode(claims.to_string()))
+    }
+
+    #[test]
+    fn supabase_claims_decode() {
+        let claims = decode_unverified(&jwt(json!({
+            "sub": "u1", "role": "authenticated", "aud": "authenticated",
+            "exp": 4_000_000_000_i64, "aal": "aal2", "session_id": "s1",
+            "amr": [{ "method": "totp", "timestamp": 1_700_000_000 }],
+            "app_metadata": { "provider": "email" }, "tenant": "acme"
+        })))
+        .unwrap();
+        assert_eq!(claims.sub, "u1");
+        assert_eq!(claims.aud, ["authenticated"]);
+        assert_eq!(claims.aal, Some(AuthenticatorAssuranceLevel::Aal2));
+        assert_eq!(claims.amr[0].method, "totp");
+        assert_eq!(claims.session_id.as_deref(), Some("s1"));
+        assert_eq!(claims.app_metadata["provider"], "email");
+        assert_eq!(claims.extra["tenant"], "acme");
+        assert!(!claims.is_expired());
+    }
+
+    #[test]
+    fn audience_lists_and_missing_claims_decode() {
+        let claims = decode_unverified(&jwt(json!({ "aud": ["a", "b"] }))).unwrap();
+        assert_eq!(claims.aud, ["a", "b"]);
+        assert!(claims.is_expired(), "no exp");
+        assert!(decode_unverified("opaque").is_err());
+        assert!(decode_unverified("e30.bm90IGpzb24.sig").is_err());
+    }
+}
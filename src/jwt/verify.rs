@@ -0,0 +1,367 @@
+//! Signature, expiry and audience checks for access tokens.
+//!
+//! Projects on asymmetric signing keys publish them at
+//! `/auth/v1/.well-known/jwks.json`. The verifier caches that set for
+//! [`jwks_ttl`](JwtVerifier::jwks_ttl) and refetches it early when a token
+//! names a key id it hasn't seen — that is how a rotated-in key is picked
+//! up. Projects still on the legacy shared secret verify HS256 with
+//! [`with_secret`](JwtVerifier::with_secret).
+
+use std::fmt;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+use jsonwebtoken::errors::ErrorKind;
+use jsonwebtoken::jwk::{AlgorithmParameters, EllipticCurve, Jwk};
+use jsonwebtoken::{Algorithm, DecodingKey, Validation};
+use serde_json::Value;
+use tracing::{debug, warn};
+
+use crate::error::{AuthError, Result, SupabaseError};
+use crate::universals::{HttpMethod, RequestOptions};
+use crate::SupabaseClient;
+
+use super::Claims;
+
+const JWKS_PATH: &str = "/auth/v1/.well-known/jwks.json";
+
+/// Refetches for an unknown key id are at most this frequent, so tokens
+/// with made-up ids can't hammer the JWKS endpoint.
+const UNKNOWN_KID_COOLDOWN: Duration = Duration::from_secs(30);
+
+/// Verifies Supabase access tokens locally. Cheap to clone; clones share
+/// the JWKS cache.
+#[derive(Clone)]
+pub struct JwtVerifier {
+    secret: Option<DecodingKey>,
+    jwks: Option<Arc<JwksCache>>,
+    jwks_ttl: Duration,
+    audience: Vec<String>,
+    issuer: Option<String>,
+    leeway: Duration,
+}
+
+impl fmt::Debug for JwtVerifier {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("JwtVerifier")
+            .field("secret", &self.secret.as_ref().map(|_| "<redacted>"))
+            .field("jwks", &self.jwks.as_ref().map(|jwks| &jwks.client.url))
+            .field("jwks_ttl", &self.jwks_ttl)
+            .field("audience", &self.audience)
+            .field("issuer", &self.issuer)
+            .field("leeway", &self.leeway)
+            .finish()
+    }
+}
+
+impl JwtVerifier {
+    /// Verify RS256 and ES256 tokens against `client`'s project JWKS.
+    pub fn new(client: &SupabaseClient) -> Self {
+        Self::with_keys(
+            None,
+            Some(Arc::new(JwksCache {
+                client: client.clone(),
+                state: Mutex::default(),
+            })),
+        )
+    }
+
+    /// Verify HS256 tokens with the project's JWT secret only.
+    pub fn hs256(secret: impl AsRef<[u8]>) -> Self {
+        Self::with_keys(Some(DecodingKey::from_secret(secret.as_ref())), None)
+    }
+
+    fn with_keys(secret: Option<DecodingKey>, jwks: Option<Arc<JwksCache>>) -> Self {
+        Self {
+            secret,
+            jwks,
+            jwks_ttl: Duration::from_secs(600),
+            audience: vec!["authenticated".into()],
+            issuer: None,
+            leeway: Duration::from_secs(60),
+        }
+    }
+
+    /// Also accept HS256 tokens signed with `secret` — for projects moving
+    /// from the legacy secret to signing keys.
+    pub fn with_secret(mut self, secret: impl AsRef<[u8]>) -> Self {
+        self.secret = Some(DecodingKey::from_secret(secret.as_ref()));
+        self
+    }
+
+    /// Required `aud`. Defaults to `authenticated`.
+    pub fn audience(mut self, audience: impl Into<String>) -> Self {
+        self.audience = vec![audience.into()];
+        self
+    }
+
+    /// Accept any `aud`, e.g. to also let `anon` tokens through.
+    pub fn any_audience(mut self) -> Self {
+        self.audience.clear();
+        self
+    }
+
+    /// Required `iss`, e.g. `https://<ref>.supabase.co/auth/v1`. Not checked
+    /// by default.
+    pub fn issuer(mut self, issuer: impl Into<String>) -> Self {
+        self.issuer = Some(issuer.into());
+        self
+    }
+
+    /// Clock skew tolerated on `exp`. Defaults to 60 seconds.
+    pub fn leeway(mut self, leeway: Duration) -> Self {
+        self.leeway = leeway;
+        self
+    }
+
+    /// How long a fetched JWKS is used before it is fetched again. Defaults
+    /// to 10 minutes.
+    pub fn jwks_ttl(mut self, ttl: Duration) -> Self {
+        self.jwks_ttl = ttl;
+        self
+    }
+
+    /// Check `token`'s signature, expiry, audience and (when set) issuer,
+    /// and return its claims. Fails with [`SupabaseError::Auth`] (error code
+    /// `bad_jwt`) when the token is rejected; fetching the JWKS can also
+    /// fail with the usual transport errors.
+    pub async fn verify(&self, token: &str) -> Result<Claims> {
+        let header = jsonwebtoken::decode_header(token).map_err(rejected)?;
+        let key = match header.alg {
+            Algorithm::HS256 => self
+                .secret
+                .clone()
+                .ok_or_else(|| bad_jwt("HS256 tokens need JwtVerifier::with_secret"))?,
+            Algorithm::RS256 | Algorithm::ES256 => match &self.jwks {
+                Some(jwks) => self.jwks_key(jwks, header.alg, header.kid.as_deref()).await?,
+                None => return Err(bad_jwt(format!("{:?} tokens need a JWKS", header.alg))),
+            },
+            other => return Err(bad_jwt(format!("unsupported JWT algorithm {other:?}"))),
+        };
+        let mut validation = Validation::new(header.alg);
+        validation.leeway = self.leeway.as_secs();
+        if self.audience.is_empty() {
+            validation.validate_aud = false;
+        } else {
+            validation.set_audience(&self.audience);
+        }
+        if let Some(issuer) = &self.issuer {
+            validation.set_issuer(&[issuer]);
+        }
+        jsonwebtoken::decode::<Claims>(token, &key, &validation)
+            .map(|data| data.claims)
+            .map_err(rejected)
+    }
+
+    async fn jwks_key(
+        &self,
+        jwks: &JwksCache,
+        alg: Algorithm,
+        kid: Option<&str>,
+    ) -> Result<DecodingKey> {
+        let now = Instant::now();
+        let stale = {
+            let mut state = jwks.lock();
+            let fresh = state
+                .fetched
+                .is_some_and(|at| now.duration_since(at) < self.jwks_ttl);
+            match state.find(alg, kid) {
+                Some(key) if fresh => return Ok(key),
+                Some(key) => Some(key),
+                None if fresh => {
+                    let cooling = state
+                        .forced
+                        .is_some_and(|at| now.duration_since(at) < UNKNOWN_KID_COOLDOWN);
+                    if cooling {
+                        return Err(unknown_key(kid));
+                    }
+                    state.forced = Some(now);
+                    None
+                }
+                None => None,
+            }
+        };
+        match jwks.fetch().await {
+            Ok(keys) => {
+                let mut state = jwks.lock();
+                state.keys = keys;
+                state.fetched = Some(Instant::now());
+                state.find(alg, kid).ok_or_else(|| unknown_key(kid))
+            }
+            Err(e) => match stale {
+                Some(key) => {
+                    warn!(target: "supabase", error = %e, "JWKS refresh failed; using cached keys");
+                    Ok(key)
+                }
+                None => Err(e),
+            },
+        }
+    }
+}
+
+struct JwksCache {
+    client: SupabaseClient,
+    state: Mutex<JwksState>,
+}
+
+#[derive(Default)]
+struct JwksState {
+    keys: Vec<Key>,
+    fetched: Option<Instant>,
+    /// Last refetch caused by an unknown key id.
+    forced: Option<Instant>,
+}
+
+struct Key {
+    kid: Option<String>,
+    alg: Algorithm,
+    key: DecodingKey,
+}
+
+impl JwksState {
+    /// The key for `kid`, or the first key for `alg` when the token names
+    /// none.
+    fn find(&self, alg: Algorithm, kid: Option<&str>) -> Option<DecodingKey> {
+        self.keys
+            .iter()
+            .find(|k| k.alg == alg && (kid.is_none() || k.kid.as_deref() == kid))
+            .map(|k| k.key.clone())
+    }
+}
+
+impl JwksCache {
+    fn lock(&self) -> MutexGuard<'_, JwksState> {
+        self.state.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    async fn fetch(&self) -> Result<Vec<Key>> {
+        let value = self
+            .client
+            .request_with(JWKS_PATH, HttpMethod::Get, None, &RequestOptions::auth())
+            .await?;
+        let Some(keys) = value.get("keys").and_then(Value::as_array) else {
+            return Err(SupabaseError::Decode {
+                message: "JWKS response has no `keys` array".into(),
+                body: value.to_string(),
+            });
+        };
+        Ok(keys.iter().filter_map(Key::from_jwk).collect())
+    }
+}
+
+impl Key {
+    /// RSA and P-256 keys; anything else in the set is skipped.
+    fn from_jwk(value: &Value) -> Option<Self> {
+        let jwk: Jwk = match serde_json::from_value(value.clone()) {
+            Ok(jwk) => jwk,
+            Err(e) => {
+                debug!(target: "supabase", error = %e, "skipping unreadable JWK");
+                return None;
+            }
+        };
+        let alg = match &jwk.algorithm {
+            AlgorithmParameters::RSA(_) => Algorithm::RS256,
+            AlgorithmParameters::EllipticCurve(ec) if ec.curve == EllipticCurve::P256 => {
+                Algorithm::ES256
+            }
+            _ => {
+                debug!(target: "supabase", kid = ?jwk.common.key_id, "skipping unsupported JWK");
+                return None;
+            }
+        };
+        let key = DecodingKey::from_jwk(&jwk).ok()?;
+        Some(Self {
+            kid: jwk.common.key_id,
+            alg,
+            key,
+        })
+    }
+}
+
+fn bad_jwt(message: impl Into<String>) -> SupabaseError {
+    SupabaseError::Auth(AuthError {
+        error_code: Some("bad_jwt".into()),
+        ..AuthError::from_message(message)
+    })
+}
+
+fn unknown_key(kid: Option<&str>) -> SupabaseError {
+    bad_jwt(format!("no JWKS key matches kid {kid:?}"))
+}
+
+fn rejected(e: jsonwebtoken::errors::Error) -> SupabaseError {
+    bad_jwt(match e.kind() {
+        ErrorKind::ExpiredSignature => "JWT has expired".to_string(),
+        ErrorKind::InvalidAudience => "JWT audience doesn't match".to_string(),
+        ErrorKind::InvalidIssuer => "JWT issuer doesn't match".to_string(),
+        ErrorKind::InvalidSignature => "JWT signature is invalid".to_string(),
+        _ => format!("invalid JWT: {e}"),
+    })
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+    use jsonwebtoken::{EncodingKey, Header};
+    use serde_json::json;
+
+    fn token(secret: &[u8], claims: Value) -> String {
+        jsonwebtoken::encode(&Header::default(), &claims, &EncodingKey::from_secret(secret)).unwrap()
+    }
+
+    fn claims(exp_in: i64) -> Value {
+        json!({
+            "sub": "u1", "role": "authenticated", "aud": "authenticated",
+            "exp": Utc::now().timestamp() + exp_in, "session_id": "s1"
+        })
+    }
+
+    #[tokio::test]
+    async fn hs256_tokens_verify_with_the_project_secret() {
+        let verifier = JwtVerifier::hs256("secret");
+        let verified = verifier.verify(&token(b"secret", claims(60))).await.unwrap();
+        assert_eq!(verified.sub, "u1");
+        assert_eq!(verified.session_id.as_deref(), Some("s1"));
+
+        let err = verifier.verify(&token(b"other", claims(60))).await.unwrap_err();
+        assert!(err.to_string().contains("signature"), "{err}");
+    }
+
+    #[tokio::test]
+    async fn expiry_audience_and_issuer_are_enforced() {
+        let verifier = JwtVerifier::hs256("secret").leeway(Duration::ZERO);
+        let err = verifier.verify(&token(b"secret", claims(-10))).await.unwrap_err();
+        assert!(err.to_string().contains("expired"), "{err}");
+
+        let anon = token(b"secret", json!({ "aud": "anon", "exp": Utc::now().timestamp() + 60 }));
+        let err = verifier.verify(&anon).await.unwrap_err();
+        assert!(err.to_string().contains("audience"), "{err}");
+        assert!(verifier.clone().any_audience().verify(&anon).await.is_ok());
+
+        let mut elsewhere = claims(60);
+        elsewhere["iss"] = json!("https://other.supabase.co/auth/v1");
+        let err = verifier
+            .issuer("https://x.supabase.co/auth/v1")
+            .verify(&token(b"secret", elsewhere))
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("issuer"), "{err}");
+    }
+
+    #[tokio::test]
+    async fn algorithms_without_a_key_are_rejected() {
+        let client = SupabaseClient::new("https://x.supabase.co", "anon", None);
+        let err = JwtVerifier::new(&client)
+            .verify(&token(b"secret", claims(60)))
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("with_secret"), "{err}");
+        let err = JwtVerifier::hs256("secret").verify("not.a.jwt").await.unwrap_err();
+        let SupabaseError::Auth(auth) = err else {
+            panic!("expected an auth error, got {err:?}");
+        };
+        assert_eq!(auth.error_code.as_deref(), Some("bad_jwt"));
+    }
+}
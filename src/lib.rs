@@ -82,6 +82,7 @@
 //!   admin user management, and the [`SessionStore`] trait.
 //! - [`storage`] — buckets, object CRUD, signed URLs, image transforms.
 //! - [`rpc`] — call Postgres functions.
+//! - [`jwt`] — access-token claims, and local verification (feature `jwt`).
 //! - [`functions`] — invoke Supabase Edge Functions (feature `functions`).
 //! - [`realtime`] — websocket subscriptions to `postgres_changes`,
 //!   broadcast, and presence (feature `realtime`, off by default).
@@ -95,6 +96,7 @@
 //! | `storage`    | ✅      | Buckets, objects, signed URLs.                |
 //! | `functions`  | ✅      | Edge Functions invocation.                    |
 //! | `realtime`   | —       | Websocket subscriptions (opt-in).             |
+//! | `jwt`        | —       | Local access-token verification (opt-in).     |
 //! | `rustls`     | ✅      | TLS via `rustls` (default).                   |
 //! | `native-tls` | —       | OS-native TLS instead of `rustls`.            |
 //!
@@ -143,15 +145,21 @@
 #![deny(clippy::expect_used)]
 #![warn(clippy::all)]
 
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 use std::time::Duration;
 
+use tokio::sync::broadcast;
+use tokio::task::AbortHandle;
 use uuid::Uuid;
 
+use crate::auth::AuthFlow;
+use crate::postgrest::{CacheConfig, QueryCache};
+
 pub mod auth;
 pub mod error;
 #[cfg(feature = "functions")]
 pub mod functions;
+pub mod jwt;
 pub mod postgrest;
 #[cfg(feature = "realtime")]
 pub mod realtime;
@@ -160,9 +168,11 @@ pub mod storage;
 pub mod universals;
 
 pub use auth::{
-    session_store::{InMemorySessionStore, SessionStore},
+    session_store::{
+        CodeVerifierStore, InMemoryCodeVerifierStore, InMemorySessionStore, SessionStore,
+    },
     types::{Identity, Session, User},
-    Auth, AuthAdmin,
+    Auth, AuthAdmin, AuthEvent,
 };
 pub use error::{AuthError, PostgrestError, Result, StorageError, SupabaseError};
 pub use postgrest::Row;
@@ -203,7 +213,13 @@ pub struct SupabaseClient {
     pub(crate) extra_headers: Vec<(String, String)>,
     pub(crate) http: reqwest::Client,
     pub(crate) session_store: Arc<dyn SessionStore>,
+    pub(crate) code_verifier_store: Arc<dyn CodeVerifierStore>,
+    pub(crate) auth_flow: AuthFlow,
+    /// Auto-refresh tasks started from this client, stopped by `sign_out`.
+    pub(crate) refresh_tasks: Arc<Mutex<Vec<AbortHandle>>>,
+    pub(crate) auth_events: broadcast::Sender<AuthEvent>,
     pub(crate) retry: RetryConfig,
+    pub(crate) query_cache: Option<QueryCache>,
 }
 
 impl SupabaseClient {
@@ -221,7 +237,12 @@ impl SupabaseClient {
             extra_headers: Vec::new(),
             http: reqwest::Client::new(),
             session_store: Arc::new(InMemorySessionStore::new()),
+            code_verifier_store: Arc::new(InMemoryCodeVerifierStore::new()),
+            auth_flow: AuthFlow::default(),
+            refresh_tasks: Arc::default(),
+            auth_events: auth::events::channel(),
             retry: RetryConfig::default(),
+            query_cache: None,
         }
     }
 
@@ -235,9 +256,12 @@ impl SupabaseClient {
             extra_headers: Vec::new(),
             http: None,
             session_store: None,
+            code_verifier_store: None,
+            auth_flow: AuthFlow::default(),
             retry: RetryConfig::default(),
             timeout: None,
             user_agent: None,
+            query_cache: None,
         }
     }
 
@@ -255,6 +279,13 @@ impl SupabaseClient {
         next
     }
 
+    /// The query cache, when enabled with [`ClientBuilder::query_cache`].
+    /// Clients derived with [`schema`](Self::schema) or
+    /// [`with_access_token`](Self::with_access_token) share it.
+    pub fn query_cache(&self) -> Option<&QueryCache> {
+        self.query_cache.as_ref()
+    }
+
     /// The bearer token applied to outgoing requests when no per-request override
     /// is supplied. Prefers the live session, then the legacy `access_token` field,
     /// then the api key (anon role).
@@ -278,9 +309,12 @@ pub struct ClientBuilder {
     extra_headers: Vec<(String, String)>,
     http: Option<reqwest::Client>,
     session_store: Option<Arc<dyn SessionStore>>,
+    code_verifier_store: Option<Arc<dyn CodeVerifierStore>>,
+    auth_flow: AuthFlow,
     retry: RetryConfig,
     timeout: Option<Duration>,
     user_agent: Option<String>,
+    query_cache: Option<CacheConfig>,
 }
 
 impl std::fmt::Debug for ClientBuilder {
@@ -289,8 +323,10 @@ impl std::fmt::Debug for ClientBuilder {
             .field("url", &self.url)
             .field("schema", &self.schema)
             .field("extra_headers", &self.extra_headers)
+            .field("auth_flow", &self.auth_flow)
             .field("retry", &self.retry)
             .field("timeout", &self.timeout)
+            .field("query_cache", &self.query_cache)
             .finish()
     }
 }
@@ -322,6 +358,20 @@ impl ClientBuilder {
         self
     }
 
+    /// Plug in a custom [`CodeVerifierStore`] for PKCE flows. If unset, an
+    /// in-memory store is used.
+    pub fn code_verifier_store<S: CodeVerifierStore + 'static>(mut self, store: S) -> Self {
+        self.code_verifier_store = Some(Arc::new(store));
+        self
+    }
+
+    /// Use PKCE for magic links and password recovery. Defaults to
+    /// [`AuthFlow::Implicit`].
+    pub fn auth_flow(mut self, flow: AuthFlow) -> Self {
+        self.auth_flow = flow;
+        self
+    }
+
     /// Override the default retry policy.
     pub fn retry(mut self, retry: RetryConfig) -> Self {
         self.retry = retry;
@@ -346,6 +396,13 @@ impl ClientBuilder {
         self
     }
 
+    /// Cache PostgREST select responses in memory. Off by default; see
+    /// [`QueryCache`] for what is cached and when entries are dropped.
+    pub fn query_cache(mut self, config: CacheConfig) -> Self {
+        self.query_cache = Some(config);
+        self
+    }
+
     pub fn build(self) -> SupabaseClient {
         let http = self.http.unwrap_or_else(|| {
             let mut b = reqwest::Client::builder();
@@ -367,7 +424,14 @@ impl ClientBuilder {
             session_store: self
                 .session_store
                 .unwrap_or_else(|| Arc::new(InMemorySessionStore::new())),
+            code_verifier_store: self
+                .code_verifier_store
+                .unwrap_or_else(|| Arc::new(InMemoryCodeVerifierStore::new())),
+            auth_flow: self.auth_flow,
+            refresh_tasks: Arc::default(),
+            auth_events: auth::events::channel(),
             retry: self.retry,
+            query_cache: self.query_cache.map(QueryCache::new),
         }
     }
 }
@@ -0,0 +1,307 @@
+//! Aggregate functions in `select` (PostgREST v12+).
+//!
+//! [`Agg`] renders `count()`, `col.sum()`, `col.avg()`, `col.min()` and
+//! `col.max()` into the select list. PostgREST groups by every other
+//! selected column, so pairing aggregates with
+//! [`TypedBuilder::group_by`] gives `GROUP BY` semantics. Rows no longer
+//! have `R`'s shape, so the builder can't be awaited until
+//! [`aggregate_as`](TypedBuilder::aggregate_as) names the result row:
+//!
+//! ```no_run
+//! # use rust_supabase_sdk::{SupabaseClient, Row, postgrest::{Agg, Column}};
+//! # use serde::{Serialize, Deserialize};
+//! # #[derive(Debug, Clone, Serialize, Deserialize)]
+//! # struct Posts { id: String, status: String, view_count: i32 }
+//! # impl Row for Posts { const TABLE: &'static str = "posts"; }
+//! # #[allow(non_upper_case_globals)]
+//! # impl Posts {
+//! #     pub const status: Column<Posts, String> = Column::new("status");
+//! #     pub const view_count: Column<Posts, i32> = Column::new("view_count");
+//! # }
+//! #[derive(Debug, Deserialize)]
+//! struct StatusTotals {
+//!     status: String,
+//!     views: i64,
+//!     count: i64,
+//! }
+//!
+//! # async fn demo(client: SupabaseClient) -> rust_supabase_sdk::Result<()> {
+//! // select=status,views:view_count.sum(),count()
+//! let totals: Vec<StatusTotals> = client
+//!     .from_row::<Posts>()
+//!     .group_by(Posts::status)
+//!     .aggregate(Agg::sum(Posts::view_count).alias("views"))
+//!     .aggregate(Agg::count())
+//!     .aggregate_as::<StatusTotals>()
+//!     .await?;
+//! # Ok(()) }
+//! ```
+//!
+//! `sum` / `avg` only accept columns whose value type implements
+//! [`Numeric`]. Aggregates must be enabled on the server
+//! (`db-aggregates-enabled = true`); otherwise PostgREST answers `400`.
+
+use std::fmt;
+use std::marker::PhantomData;
+
+use serde::de::DeserializeOwned;
+
+use super::builder::PostgrestBuilder;
+use super::column::{Column, IntoColumnName};
+use super::row::Row;
+use super::typed_builder::TypedBuilder;
+
+/// Output type of a [`TypedBuilder`] with aggregates or grouping columns
+/// in its select list. It names no row shape, so the builder can't be
+/// awaited until [`aggregate_as`](TypedBuilder::aggregate_as) picks one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Aggregated;
+
+/// Marker for column value types that `sum()` / `avg()` accept.
+///
+/// Implemented for the primitive integer and float types and `Option` of
+/// them. Implement it for your own numeric newtypes (e.g. a decimal type
+/// mapped from `numeric`).
+pub trait Numeric {}
+
+macro_rules! numeric {
+    ($($t:ty),*) => { $(impl Numeric for $t {})* };
+}
+numeric!(i8, i16, i32, i64, i128, u8, u16, u32, u64, u128, f32, f64);
+impl<T: Numeric> Numeric for Option<T> {}
+
+/// An aggregate function PostgREST understands.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AggFn {
+    Count,
+    Sum,
+    Avg,
+    Min,
+    Max,
+}
+
+impl AggFn {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Count => "count",
+            Self::Sum => "sum",
+            Self::Avg => "avg",
+            Self::Min => "min",
+            Self::Max => "max",
+        }
+    }
+}
+
+/// One aggregate in a select list, e.g. `views:view_count.sum()`.
+///
+/// Build with the typed constructors ([`Agg::sum`], [`Agg::count`], …) or,
+/// for the string-typed builder, [`Agg::untyped`]. The result comes back
+/// under the function name (`sum`, `count`, …) unless [`alias`](Self::alias)
+/// is set.
+pub struct Agg<R = ()> {
+    func: AggFn,
+    column: Option<String>,
+    alias: Option<String>,
+    _row: PhantomData<fn() -> R>,
+}
+
+impl<R> Agg<R> {
+    fn new(func: AggFn, column: Option<String>) -> Self {
+        Self {
+            func,
+            column,
+            alias: None,
+            _row: PhantomData,
+        }
+    }
+
+    /// `count()` — number of rows in each group.
+    pub fn count() -> Self {
+        Self::new(AggFn::Count, None)
+    }
+
+    /// Rename the result key: `alias:col.sum()`.
+    pub fn alias(mut self, alias: impl Into<String>) -> Self {
+        self.alias = Some(alias.into());
+        self
+    }
+
+    fn render(&self) -> String {
+        let call = match &self.column {
+            Some(col) => format!("{col}.{}()", self.func.as_str()),
+            None => format!("{}()", self.func.as_str()),
+        };
+        match &self.alias {
+            Some(alias) => format!("{alias}:{call}"),
+            None => call,
+        }
+    }
+}
+
+impl<R: Row> Agg<R> {
+    /// `col.count()` — number of non-null values of `col` in each group.
+    pub fn count_of<V>(col: Column<R, V>) -> Self {
+        Self::new(AggFn::Count, Some(col.name().to_string()))
+    }
+
+    /// `col.sum()`.
+    pub fn sum<V: Numeric>(col: Column<R, V>) -> Self {
+        Self::new(AggFn::Sum, Some(col.name().to_string()))
+    }
+
+    /// `col.avg()`.
+    pub fn avg<V: Numeric>(col: Column<R, V>) -> Self {
+        Self::new(AggFn::Avg, Some(col.name().to_string()))
+    }
+
+    /// `col.min()`.
+    pub fn min<V>(col: Column<R, V>) -> Self {
+        Self::new(AggFn::Min, Some(col.name().to_string()))
+    }
+
+    /// `col.max()`.
+    pub fn max<V>(col: Column<R, V>) -> Self {
+        Self::new(AggFn::Max, Some(col.name().to_string()))
+    }
+}
+
+impl Agg {
+    /// An aggregate over a column named by string, for
+    /// [`PostgrestBuilder::aggregate`]. Not type-checked.
+    pub fn untyped(func: AggFn, column: impl Into<String>) -> Self {
+        Self::new(func, Some(column.into()))
+    }
+}
+
+impl<R> Clone for Agg<R> {
+    fn clone(&self) -> Self {
+        Self {
+            func: self.func,
+            column: self.column.clone(),
+            alias: self.alias.clone(),
+            _row: PhantomData,
+        }
+    }
+}
+
+impl<R> fmt::Debug for Agg<R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Agg").field(&self.render()).finish()
+    }
+}
+
+impl<T> PostgrestBuilder<T> {
+    /// Add an aggregate to the select list. Non-aggregate columns already
+    /// selected become the grouping key; a bare `*` select is replaced.
+    pub fn aggregate(mut self, agg: Agg) -> Self {
+        self.push_select_item(&agg.render());
+        self
+    }
+
+    /// Append `item` to the select list, replacing the `*` default.
+    pub(crate) fn push_select_item(&mut self, item: &str) {
+        match &mut self.state.select_cols {
+            Some(cols) if cols != "*" && !cols.is_empty() => {
+                cols.push(',');
+                cols.push_str(item);
+            }
+            slot => *slot = Some(item.to_string()),
+        }
+    }
+}
+
+impl<R: Row, O> TypedBuilder<R, O> {
+    /// Add an aggregate over one of `R`'s columns. Name the result row with
+    /// [`aggregate_as`](TypedBuilder::aggregate_as) before awaiting.
+    pub fn aggregate(mut self, agg: Agg<R>) -> TypedBuilder<R, Aggregated> {
+        self.inner.push_select_item(&agg.render());
+        self.output()
+    }
+
+    /// Select `col` as a grouping key for the aggregates in this query.
+    pub fn group_by(mut self, col: impl IntoColumnName<R>) -> TypedBuilder<R, Aggregated> {
+        self.inner.push_select_item(&col.into_column_name());
+        self.output()
+    }
+
+    fn output<T>(self) -> TypedBuilder<R, T> {
+        TypedBuilder {
+            inner: self.inner,
+            _row: PhantomData,
+        }
+    }
+}
+
+impl<R: Row> TypedBuilder<R, Aggregated> {
+    /// Decode the aggregated rows as `T`, whose fields are the grouping
+    /// columns and the aggregates' result keys (`sum`, `count`, or the
+    /// [`alias`](Agg::alias)).
+    pub fn aggregate_as<T: DeserializeOwned>(self) -> TypedBuilder<R, T> {
+        self.output()
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+    use crate::SupabaseClient;
+    use serde::{Deserialize, Serialize};
+    use serde_json::Value;
+
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    struct Orders {
+        status: String,
+        amount: f64,
+        quantity: Option<i32>,
+    }
+
+    impl Row for Orders {
+        const TABLE: &'static str = "orders";
+    }
+
+    #[allow(non_upper_case_globals)]
+    impl Orders {
+        const status: Column<Orders, String> = Column::new("status");
+        const amount: Column<Orders, f64> = Column::new("amount");
+        const quantity: Column<Orders, Option<i32>> = Column::new("quantity");
+    }
+
+    fn client() -> SupabaseClient {
+        SupabaseClient::new("https://x.supabase.co", "anon", None)
+    }
+
+    #[test]
+    fn aggregates_render_function_calls() {
+        assert_eq!(Agg::<Orders>::count().render(), "count()");
+        assert_eq!(Agg::sum(Orders::amount).render(), "amount.sum()");
+        assert_eq!(Agg::avg(Orders::quantity).render(), "quantity.avg()");
+        assert_eq!(Agg::max(Orders::status).alias("last").render(), "last:status.max()");
+        assert_eq!(Agg::count_of(Orders::quantity).render(), "quantity.count()");
+    }
+
+    #[test]
+    fn typed_aggregate_replaces_star_and_groups() {
+        let p = client()
+            .from_row::<Orders>()
+            .group_by(Orders::status)
+            .aggregate(Agg::sum(Orders::amount))
+            .aggregate(Agg::count())
+            .aggregate_as::<Value>()
+            .build_path();
+        assert_eq!(p, "/rest/v1/orders?select=status%2Camount.sum%28%29%2Ccount%28%29");
+    }
+
+    #[test]
+    fn untyped_aggregate_appends_to_select() {
+        let p = client()
+            .from("orders")
+            .select("status")
+            .aggregate(Agg::untyped(AggFn::Min, "amount"))
+            .build_path();
+        assert_eq!(p, "/rest/v1/orders?select=status%2Camount.min%28%29");
+
+        let p = client().from("orders").select("*").aggregate(Agg::count()).build_path();
+        assert_eq!(p, "/rest/v1/orders?select=count%28%29");
+    }
+}
@@ -0,0 +1,253 @@
+//! Chunked bulk inserts / upserts.
+//!
+//! [`TableBuilder::insert_batched`] splits a large slice of rows into
+//! fixed-size chunks and sends them with bounded concurrency, each chunk as
+//! its own `POST` through the client's normal retry path. Failures don't
+//! abort the other chunks (unless asked to) — the returned [`BatchReport`]
+//! says which row ranges made it and which didn't:
+//!
+//! ```no_run
+//! # use rust_supabase_sdk::SupabaseClient;
+//! # use rust_supabase_sdk::postgrest::BatchOptions;
+//! # async fn demo(client: SupabaseClient, rows: Vec<serde_json::Value>) -> rust_supabase_sdk::Result<()> {
+//! let report = client
+//!     .from("events")
+//!     .insert_batched(
+//!         &rows,
+//!         BatchOptions { chunk_size: 1_000, concurrency: 4, ..Default::default() },
+//!     )
+//!     .await?;
+//! for failure in &report.failed {
+//!     eprintln!("rows {:?} failed: {}", failure.rows, failure.error);
+//! }
+//! # Ok(()) }
+//! ```
+
+use std::future::Future;
+use std::ops::Range;
+
+use futures_util::stream::{FuturesUnordered, StreamExt};
+use serde::Serialize;
+
+use crate::error::{PostgrestError, Result, SupabaseError};
+
+use super::builder::TableBuilder;
+
+/// Options for [`TableBuilder::insert_batched`].
+#[derive(Debug, Clone)]
+pub struct BatchOptions {
+    /// Rows per request. Defaults to 500; `0` is treated as `1`.
+    pub chunk_size: usize,
+    /// Maximum number of chunk requests in flight at once. Defaults to 4;
+    /// `0` is treated as `1`.
+    pub concurrency: usize,
+    /// Conflict target column(s). When set, chunks are sent as upserts
+    /// (`Prefer: resolution=merge-duplicates`, `on_conflict=<columns>`).
+    pub on_conflict: Option<String>,
+    /// Stop sending new chunks after the first failure. Chunks already in
+    /// flight still finish and are reported; the rest are listed in
+    /// [`BatchReport::skipped`].
+    pub stop_on_first_failure: bool,
+}
+
+impl Default for BatchOptions {
+    fn default() -> Self {
+        Self {
+            chunk_size: 500,
+            concurrency: 4,
+            on_conflict: None,
+            stop_on_first_failure: false,
+        }
+    }
+}
+
+/// A chunk that failed, with the error PostgREST (or the transport) returned.
+#[derive(Debug)]
+pub struct ChunkFailure {
+    /// Chunk number, in input order.
+    pub chunk: usize,
+    /// Indices of the input rows carried by this chunk.
+    pub rows: Range<usize>,
+    pub error: SupabaseError,
+}
+
+impl ChunkFailure {
+    /// The PostgREST error body, when the failure was a PostgREST error
+    /// response (constraint violation, bad column, …) rather than a
+    /// transport or retry-exhaustion error.
+    pub fn postgrest_error(&self) -> Option<&PostgrestError> {
+        match &self.error {
+            SupabaseError::Postgrest(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+/// Outcome of [`TableBuilder::insert_batched`]. All chunk lists are sorted by
+/// chunk number.
+#[derive(Debug, Default)]
+pub struct BatchReport {
+    /// Input row range of every chunk, indexed by chunk number.
+    pub chunks: Vec<Range<usize>>,
+    /// Chunks that were written.
+    pub succeeded: Vec<usize>,
+    /// Chunks that were rejected.
+    pub failed: Vec<ChunkFailure>,
+    /// Chunks never sent because
+    /// [`stop_on_first_failure`](BatchOptions::stop_on_first_failure) tripped.
+    pub skipped: Vec<usize>,
+}
+
+impl BatchReport {
+    /// `true` when every chunk was written.
+    pub fn is_complete(&self) -> bool {
+        self.failed.is_empty() && self.skipped.is_empty()
+    }
+
+    /// Number of input rows in successful chunks.
+    pub fn rows_written(&self) -> usize {
+        self.succeeded.iter().map(|&i| self.chunks[i].len()).sum()
+    }
+}
+
+impl TableBuilder {
+    /// Insert `rows` in chunks of [`BatchOptions::chunk_size`], with at most
+    /// [`BatchOptions::concurrency`] requests in flight. Each chunk goes
+    /// through the client's retry policy independently.
+    ///
+    /// Returns `Ok` with a [`BatchReport`] even when chunks fail — inspect
+    /// [`BatchReport::failed`]. Rows aren't returned (`Prefer:
+    /// return=minimal`), and chunks are not atomic with each other.
+    pub async fn insert_batched<B: Serialize + Sync>(
+        self,
+        rows: &[B],
+        options: BatchOptions,
+    ) -> Result<BatchReport> {
+        let chunk_size = options.chunk_size.max(1);
+        let concurrency = options.concurrency.max(1);
+        let chunks: Vec<Range<usize>> = (0..rows.len())
+            .step_by(chunk_size)
+            .map(|start| start..(start + chunk_size).min(rows.len()))
+            .collect();
+
+        let send = |index: usize| {
+            let body = &rows[chunks[index].clone()];
+            let table = self.clone();
+            let on_conflict = options.on_conflict.clone();
+            async move {
+                let query = match on_conflict {
+                    Some(columns) => table.upsert(body).on_conflict(columns),
+                    None => table.insert(body),
+                };
+                (index, query.execute().await.map(drop))
+            }
+        };
+
+        let mut report = BatchReport::default();
+        report.skipped = run_bounded(chunks.len(), concurrency, send, |index, outcome| {
+            match outcome {
+                Ok(()) => report.succeeded.push(index),
+                Err(error) => report.failed.push(ChunkFailure {
+                    chunk: index,
+                    rows: chunks[index].clone(),
+                    error,
+                }),
+            }
+            !options.stop_on_first_failure || report.failed.is_empty()
+        })
+        .await;
+
+        report.succeeded.sort_unstable();
+        report.failed.sort_by_key(|f| f.chunk);
+        report.chunks = chunks;
+        Ok(report)
+    }
+}
+
+/// Drive `send(0..n)` with at most `concurrency` futures in flight. `on_done`
+/// returns whether to keep launching new work. Returns the indices that were
+/// never launched.
+async fn run_bounded<F, Fut>(
+    n: usize,
+    concurrency: usize,
+    send: F,
+    mut on_done: impl FnMut(usize, Result<()>) -> bool,
+) -> Vec<usize>
+where
+    F: Fn(usize) -> Fut,
+    Fut: Future<Output = (usize, Result<()>)>,
+{
+    let mut next = 0;
+    let mut in_flight = FuturesUnordered::new();
+    while next < n && in_flight.len() < concurrency {
+        in_flight.push(send(next));
+        next += 1;
+    }
+    let mut keep_going = true;
+    while let Some((index, outcome)) = in_flight.next().await {
+        keep_going &= on_done(index, outcome);
+        if keep_going && next < n {
+            in_flight.push(send(next));
+            next += 1;
+        }
+    }
+    (next..n).collect()
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn report_counts_rows_from_successful_chunks() {
+        let report = BatchReport {
+            chunks: vec![0..3, 3..6, 6..7],
+            succeeded: vec![0, 2],
+            failed: vec![ChunkFailure {
+                chunk: 1,
+                rows: 3..6,
+                error: SupabaseError::Unexpected("boom".into()),
+            }],
+            skipped: vec![],
+        };
+        assert_eq!(report.rows_written(), 4);
+        assert!(!report.is_complete());
+        assert!(report.failed[0].postgrest_error().is_none());
+    }
+
+    #[tokio::test]
+    async fn run_bounded_respects_concurrency_and_stops() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        let live = AtomicUsize::new(0);
+        let peak = AtomicUsize::new(0);
+        let send = |i: usize| {
+            let (live, peak) = (&live, &peak);
+            async move {
+                let now = live.fetch_add(1, Ordering::SeqCst) + 1;
+                peak.fetch_max(now, Ordering::SeqCst);
+                tokio::task::yield_now().await;
+                live.fetch_sub(1, Ordering::SeqCst);
+                let outcome = if i == 3 {
+                    Err(SupabaseError::Unexpected("chunk 3".into()))
+                } else {
+                    Ok(())
+                };
+                (i, outcome)
+            }
+        };
+        let mut done = Vec::new();
+        let skipped = run_bounded(10, 2, send, |i, outcome| {
+            done.push(i);
+            outcome.is_ok()
+        })
+        .await;
+        assert!(peak.load(Ordering::SeqCst) <= 2);
+        // Chunk 3 fails while chunk 4 may already be in flight; nothing
+        // after that is launched.
+        assert!(done.contains(&3));
+        assert_eq!(done.len() + skipped.len(), 10);
+        assert!(skipped.iter().all(|&i| i > 3));
+        assert!(!skipped.is_empty());
+    }
+}
@@ -9,10 +9,12 @@ use serde::Serialize;
 use serde_json::Value;
 
 use crate::error::{Result, SupabaseError};
+use crate::rpc::RpcMode;
 use crate::universals::{HttpMethod, RequestOptions};
 use crate::SupabaseClient;
 
-use super::value::encode_value;
+use super::cache::{embeds_resources, CacheKey};
+use super::value::{encode_column, encode_value};
 
 /// Entry point for building a query against a table.
 ///
@@ -47,6 +49,17 @@ impl TableBuilder {
         q
     }
 
+    /// Build an `INSERT` from CSV text. The first line is the header naming
+    /// the columns; PostgREST parses the rest (`Content-Type: text/csv`).
+    pub fn insert_csv(self, csv: impl Into<Vec<u8>>) -> PostgrestBuilder<Value> {
+        let mut q = PostgrestBuilder::new(self.client, self.table, Operation::Insert);
+        q.state.raw_body = Some(RawBody {
+            bytes: csv.into(),
+            content_type: "text/csv",
+        });
+        q
+    }
+
     /// Build an `UPSERT` (INSERT with conflict resolution).
     pub fn upsert<B: Serialize>(self, body: B) -> PostgrestBuilder<Value> {
         let mut q = PostgrestBuilder::new(self.client, self.table, Operation::Upsert);
@@ -75,7 +88,26 @@ impl TableBuilder {
     }
 }
 
-fn serialize_body<B: Serialize>(body: B) -> (Option<Value>, Option<String>) {
+/// Text form of a JSON value as a `GET` RPC argument.
+fn render_rpc_arg(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        Value::Array(items) => {
+            let elems: Vec<String> = items
+                .iter()
+                .map(|item| match item {
+                    Value::String(s) => format!("\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\"")),
+                    Value::Null => "NULL".to_string(),
+                    other => render_rpc_arg(other),
+                })
+                .collect();
+            format!("{{{}}}", elems.join(","))
+        }
+        other => other.to_string(),
+    }
+}
+
+pub(crate) fn serialize_body<B: Serialize>(body: B) -> (Option<Value>, Option<String>) {
     match serde_json::to_value(&body) {
         Ok(v) => (Some(v), None),
         Err(e) => (None, Some(e.to_string())),
@@ -91,12 +123,18 @@ pub(crate) enum Operation {
     Upsert,
     Update,
     Delete,
+    /// A call to `/rest/v1/rpc/<fn>` (the builder's `table` is `rpc/<fn>`).
+    /// Except with [`RpcMode::Post`], the arguments travel as query
+    /// parameters instead of the body.
+    Rpc(RpcMode),
 }
 
 impl Operation {
-    fn method(self) -> HttpMethod {
+    pub(crate) fn method(self) -> HttpMethod {
         match self {
-            Self::Select => HttpMethod::Get,
+            Self::Select | Self::Rpc(RpcMode::Get) => HttpMethod::Get,
+            Self::Rpc(RpcMode::Post) => HttpMethod::Post,
+            Self::Rpc(RpcMode::Head) => HttpMethod::Head,
             Self::Insert | Self::Upsert => HttpMethod::Post,
             Self::Update => HttpMethod::Patch,
             Self::Delete => HttpMethod::Delete,
@@ -181,6 +219,11 @@ pub(crate) struct State {
     /// Captured when serialization of the body failed at builder time. Surfaced
     /// when the request is awaited so the user doesn't silently send `null`.
     pub(crate) body_error: Option<String>,
+    /// Captured when a builder argument can't be turned into a request (a
+    /// misordered keyset, a malformed primary key). Surfaced the same way.
+    pub(crate) build_error: Option<String>,
+    /// Non-JSON request body (`text/csv` inserts). Takes precedence over `body`.
+    pub(crate) raw_body: Option<RawBody>,
     pub(crate) limit: Option<u64>,
     pub(crate) offset: Option<u64>,
     pub(crate) range: Option<(u64, u64)>,
@@ -194,6 +237,30 @@ pub(crate) struct State {
     /// [`execute_inner`]: PostgrestBuilder::execute_inner
     /// [`is_in`]: PostgrestBuilder::is_in
     pub(crate) short_circuit_empty_result: bool,
+    /// Set by [`no_cache`](PostgrestBuilder::no_cache): bypass the client's
+    /// query cache for this request.
+    pub(crate) no_cache: bool,
+}
+
+impl State {
+    /// The error captured at builder time, if any. Checked before every
+    /// request is sent.
+    pub(crate) fn check(&self) -> Result<()> {
+        if let Some(msg) = &self.build_error {
+            return Err(SupabaseError::Unexpected(format!("invalid query: {msg}")));
+        }
+        if let Some(msg) = &self.body_error {
+            return Err(SupabaseError::Unexpected(format!("failed to serialize request body: {msg}")));
+        }
+        Ok(())
+    }
+}
+
+/// A request body sent verbatim with its own `Content-Type`.
+#[derive(Debug, Clone)]
+pub(crate) struct RawBody {
+    pub(crate) bytes: Vec<u8>,
+    pub(crate) content_type: &'static str,
 }
 
 /// The main builder. Generic over the row type `T` (defaults to `serde_json::Value`).
@@ -226,6 +293,40 @@ impl PostgrestBuilder<Value> {
             _marker: PhantomData,
         }
     }
+
+    /// A `POST /rest/v1/rpc/<name>` call with `args` as the JSON body. Unit
+    /// or `null` args are sent as `{}`.
+    pub(crate) fn rpc<A: Serialize>(client: SupabaseClient, name: &str, args: A) -> Self {
+        let mut q = Self::new(client, format!("rpc/{name}"), Operation::Rpc(RpcMode::Post));
+        let (val, err) = serialize_body(args);
+        q.state.body = match val {
+            Some(Value::Null) => Some(Value::Object(Default::default())),
+            other => other,
+        };
+        q.state.body_error = err;
+        q
+    }
+
+    /// Switch how an RPC call is sent. `Get` / `Head` move the arguments
+    /// into the query string: strings verbatim, arrays as Postgres array
+    /// literals (`{a,b}`), objects as JSON text; `null` arguments are omitted
+    /// so the function's default applies.
+    pub(crate) fn with_rpc_mode(mut self, mode: RpcMode) -> Self {
+        if self.op != Operation::Rpc(RpcMode::Post) || mode == RpcMode::Post {
+            return self;
+        }
+        self.op = Operation::Rpc(mode);
+        if let Some(Value::Object(args)) = self.state.body.take() {
+            let mut params: Vec<(String, String)> = args
+                .into_iter()
+                .filter(|(_, v)| !v.is_null())
+                .map(|(k, v)| (encode_column(&k), encode_value(&render_rpc_arg(&v))))
+                .collect();
+            params.append(&mut self.state.params);
+            self.state.params = params;
+        }
+        self
+    }
 }
 
 impl<T> PostgrestBuilder<T> {
@@ -298,6 +399,13 @@ impl<T> PostgrestBuilder<T> {
         self
     }
 
+    /// Skip the client's [query cache](super::QueryCache) for this request:
+    /// always go to the server and don't store the result.
+    pub fn no_cache(mut self) -> Self {
+        self.state.no_cache = true;
+        self
+    }
+
     /// On write ops: ask PostgREST to return the affected rows. Equivalent to
     /// calling `.select("*")` on an insert/update/upsert/delete in supabase-js.
     pub fn select_returning(mut self, columns: impl Into<String>) -> Self {
@@ -423,38 +531,10 @@ impl<T: DeserializeOwned + Send + 'static> PostgrestBuilder<T> {
         Ok((rows, count))
     }
 
-    async fn execute_inner(self) -> Result<(Option<u64>, Vec<T>)> {
-        if let Some(msg) = &self.state.body_error {
-            return Err(SupabaseError::Unexpected(format!("failed to serialize request body: {msg}")));
-        }
-        // `is_in([])` (and similar empty-set filters) provably match no rows.
-        // Skip the HTTP call rather than send a request PostgREST would 400 on.
-        if self.state.short_circuit_empty_result {
-            return Ok((Some(0), Vec::new()));
-        }
-        let path = self.build_path();
-        let opts = self.build_options();
-
-        let (_status, headers, body) = self
-            .client
-            .request_full(&path, self.op.method(), self.state.body.clone(), &opts)
-            .await?;
-
-        let count = headers
-            .get("content-range")
-            .and_then(|v| v.to_str().ok())
-            .and_then(parse_count_from_content_range);
-
-        if body.is_empty() {
-            return Ok((count, Vec::new()));
-        }
+    pub(crate) async fn execute_inner(self) -> Result<(Option<u64>, Vec<T>)> {
+        let (count, value) = self.execute_value().await?;
 
         // PostgREST normally returns an array; defensively handle a bare object too.
-        let value: Value = serde_json::from_str(&body).map_err(|e| SupabaseError::Decode {
-            message: e.to_string(),
-            body: body.clone(),
-        })?;
-
         let rows = match value {
             Value::Array(arr) => arr
                 .into_iter()
@@ -478,6 +558,146 @@ impl<T: DeserializeOwned + Send + 'static> PostgrestBuilder<T> {
     }
 }
 
+impl<T> PostgrestBuilder<T> {
+    /// Send the request and return the `Content-Range` count and the
+    /// undecoded body (`Null` when empty).
+    pub(crate) async fn execute_value(self) -> Result<(Option<u64>, Value)> {
+        let Some(cache) = self.client.query_cache.clone() else {
+            return self.send_value().await;
+        };
+        match self.op {
+            Operation::Select => {
+                if self.state.no_cache
+                    || self.state.body_error.is_some()
+                    || self.state.build_error.is_some()
+                    || self.state.short_circuit_empty_result
+                    || self.state.select_cols.as_deref().is_some_and(embeds_resources)
+                {
+                    return self.send_value().await;
+                }
+                let key = CacheKey {
+                    schema: self.client.schema.clone(),
+                    bearer: self.client.effective_bearer(),
+                    prefer: self.state.prefer.clone(),
+                    path: self.build_path(),
+                };
+                if let Some(hit) = cache.get(&key) {
+                    return Ok(hit);
+                }
+                let table = self.table.clone();
+                // A write landing while this select is in flight makes its
+                // response stale; `put` skips it.
+                let generation = cache.generation(&table);
+                let (count, value) = self.send_value().await?;
+                cache.put(key, table, generation, count, value.clone());
+                Ok((count, value))
+            }
+            Operation::Rpc(_) => self.send_value().await,
+            // Invalidate even on error: a timed-out write may still have landed.
+            Operation::Insert | Operation::Upsert | Operation::Update | Operation::Delete => {
+                let table = self.table.clone();
+                let result = self.send_value().await;
+                cache.invalidate_table(&table);
+                result
+            }
+        }
+    }
+
+    async fn send_value(self) -> Result<(Option<u64>, Value)> {
+        self.state.check()?;
+        // `is_in([])` (and similar empty-set filters) provably match no rows.
+        // Skip the HTTP call rather than send a request PostgREST would 400 on.
+        if self.state.short_circuit_empty_result {
+            return Ok((Some(0), Value::Array(Vec::new())));
+        }
+        let path = self.build_path();
+        let opts = self.build_options();
+
+        let (count, value) = if let Some(raw) = &self.state.raw_body {
+            let value = self
+                .client
+                .request_bytes(&path, self.op.method(), raw.bytes.clone(), raw.content_type, &opts)
+                .await?;
+            (None, value)
+        } else {
+            let (_status, headers, body) = self
+                .client
+                .request_full(&path, self.op.method(), self.state.body.clone(), &opts)
+                .await?;
+
+            let count = headers
+                .get("content-range")
+                .and_then(|v| v.to_str().ok())
+                .and_then(parse_count_from_content_range);
+
+            if body.is_empty() {
+                return Ok((count, Value::Null));
+            }
+
+            let value: Value = serde_json::from_str(&body).map_err(|e| SupabaseError::Decode {
+                message: e.to_string(),
+                body: body.clone(),
+            })?;
+            (count, value)
+        };
+        Ok((count, value))
+    }
+}
+
+impl<T> PostgrestBuilder<T> {
+    /// Send the select as a `HEAD` request and return the row count from
+    /// `Content-Range`, without downloading any rows. Uses the mode set with
+    /// [`count`](Self::count), or [`CountMode::Exact`] when none was set.
+    ///
+    /// Only selects and `GET` / `HEAD` [RPC calls](crate::rpc::RpcMode) can
+    /// be counted this way.
+    pub async fn head(mut self) -> Result<u64> {
+        self.state.check()?;
+        match self.op {
+            Operation::Select | Operation::Rpc(RpcMode::Head) => {}
+            Operation::Rpc(RpcMode::Get) => self.op = Operation::Rpc(RpcMode::Head),
+            op => {
+                return Err(SupabaseError::Unexpected(format!(
+                    "head() needs a select or a GET/HEAD RPC call, not {op:?}"
+                )))
+            }
+        }
+        if self.state.short_circuit_empty_result {
+            return Ok(0);
+        }
+        if !self.state.prefer.iter().any(|p| p.starts_with("count=")) {
+            self.state.prefer.push(CountMode::Exact.header_value().to_string());
+        }
+        let path = self.build_path();
+        let (_status, headers, _body) = self
+            .client
+            .request_full(&path, HttpMethod::Head, None, &self.build_options())
+            .await?;
+        headers
+            .get("content-range")
+            .and_then(|v| v.to_str().ok())
+            .and_then(parse_count_from_content_range)
+            .ok_or_else(|| {
+                SupabaseError::Unexpected(format!("no count in the Content-Range of {path}"))
+            })
+    }
+
+    /// Shorthand for `.count(mode).head()`.
+    pub async fn count_only(self, mode: CountMode) -> Result<u64> {
+        self.count(mode).head().await
+    }
+
+    /// `true` when at least one row matches. Fetches with `limit=1`.
+    pub async fn exists(self) -> Result<bool> {
+        let (_count, value) = self.limit(1).execute_value().await?;
+        Ok(match value {
+            Value::Array(rows) => !rows.is_empty(),
+            Value::Null => false,
+            _ => true,
+        })
+    }
+}
+
 fn parse_count_from_content_range(header: &str) -> Option<u64> {
     // Format: "0-9/123" or "*/123"
     let total = header.split('/').nth(1)?;
@@ -534,7 +754,7 @@ impl<T: DeserializeOwned + Send + 'static> IntoFuture for SingleBuilder<T> {
 /// Builder returned by [`PostgrestBuilder::maybe_single`]. Awaits to `Result<Option<T>>`.
 #[must_use = "MaybeSingleBuilder is lazy — `.await` it or call `.execute()`"]
 pub struct MaybeSingleBuilder<T> {
-    inner: PostgrestBuilder<T>,
+    pub(crate) inner: PostgrestBuilder<T>,
 }
 
 impl<T: DeserializeOwned + Send + 'static> MaybeSingleBuilder<T> {
@@ -1154,6 +1374,23 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn build_error_is_not_reported_as_a_body_error() {
+        let mut q = client().from("t").select("*");
+        q.state.build_error = Some("bad key".into());
+        let err = q.execute().await.unwrap_err();
+        match err {
+            SupabaseError::Unexpected(msg) => assert_eq!(msg, "invalid query: bad key"),
+            other => panic!("expected Unexpected, got {other:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn head_short_circuits_empty_in_lists() {
+        let n = client().from("t").select("*").is_in("id", Vec::<i64>::new()).head().await;
+        assert_eq!(n.unwrap(), 0);
+    }
+
     #[test]
     fn serialize_body_failure_captures_error() {
         // Use a Serialize impl that always errors out — proves serialize_body
@@ -0,0 +1,362 @@
+//! Opt-in response cache for PostgREST reads.
+//!
+//! Enabled per client with
+//! [`ClientBuilder::query_cache`](crate::ClientBuilder::query_cache). Only
+//! `GET` selects of a single table are cached: a select that embeds other
+//! resources (`*,author:authors(*)`) always goes to the server, since an
+//! embed can be named by a foreign key rather than its table and a write to
+//! the embedded table couldn't be traced back to the entry. An entry is keyed on the request path
+//! ([`build_path`](super::PostgrestBuilder::build_path)), the schema, the
+//! bearer token and the `Prefer` header, so a different user or count mode
+//! never sees another's result:
+//!
+//! ```no_run
+//! # use std::time::Duration;
+//! # use rust_supabase_sdk::SupabaseClient;
+//! # use rust_supabase_sdk::postgrest::CacheConfig;
+//! # async fn demo() -> rust_supabase_sdk::Result<()> {
+//! let client = SupabaseClient::builder("https://x.supabase.co", "anon")
+//!     .query_cache(CacheConfig { ttl: Duration::from_secs(300), ..Default::default() })
+//!     .build();
+//!
+//! let first: Vec<serde_json::Value> = client.from("countries").select("*").await?;
+//! let again: Vec<serde_json::Value> = client.from("countries").select("*").await?; // cached
+//! # Ok(()) }
+//! ```
+//!
+//! Inserts, upserts, updates and deletes sent through this client (typed
+//! or not, including [`Transaction`](super::Transaction) statements) drop
+//! the cached entries of their table. Writes made elsewhere are only seen
+//! once an entry expires, or after an explicit
+//! [`QueryCache::invalidate_table`]. Writes done inside RPC functions don't
+//! invalidate anything either. With the `realtime` feature,
+//! [`QueryCache::invalidator`] turns `postgres_changes` events into
+//! invalidations.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+use serde_json::Value;
+
+/// Does `select` embed another resource? Aggregate calls (`count()`,
+/// `amount.sum()`) are the only other parentheses a select list has.
+pub(crate) fn embeds_resources(select: &str) -> bool {
+    let bytes = select.as_bytes();
+    bytes.iter().enumerate().any(|(i, &b)| {
+        if b != b'(' {
+            return false;
+        }
+        let name_start = bytes[..i]
+            .iter()
+            .rposition(|c| !(c.is_ascii_alphanumeric() || *c == b'_'))
+            .map_or(0, |p| p + 1);
+        // `col.sum(` — but not a `...spread(` embed.
+        let is_aggregate = match name_start.checked_sub(2).map(|p| &bytes[p..name_start]) {
+            Some([prev, b'.']) => *prev != b'.',
+            _ => &bytes[name_start..i] == b"count" && bytes.get(i + 1) == Some(&b')'),
+        };
+        !is_aggregate
+    })
+}
+
+/// Limits for the query cache.
+#[derive(Debug, Clone)]
+pub struct CacheConfig {
+    /// How long a cached response is served. Defaults to 60 seconds.
+    pub ttl: Duration,
+    /// Maximum number of cached responses. Defaults to 1 000.
+    pub max_entries: usize,
+    /// Maximum combined size of the cached response bodies, in bytes.
+    /// Larger responses aren't cached. Defaults to 16 MiB.
+    pub max_bytes: usize,
+}
+
+impl Default for CacheConfig {
+    fn default() -> Self {
+        Self {
+            ttl: Duration::from_secs(60),
+            max_entries: 1_000,
+            max_bytes: 16 * 1024 * 1024,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub(crate) struct CacheKey {
+    pub(crate) schema: Option<String>,
+    pub(crate) bearer: String,
+    pub(crate) prefer: Vec<String>,
+    pub(crate) path: String,
+}
+
+#[derive(Debug)]
+struct Entry {
+    table: String,
+    count: Option<u64>,
+    value: Value,
+    bytes: usize,
+    expires: Instant,
+    last_used: u64,
+}
+
+#[derive(Debug, Default)]
+struct Entries {
+    map: HashMap<CacheKey, Entry>,
+    bytes: usize,
+    clock: u64,
+    /// Bumped by [`QueryCache::clear`].
+    epoch: u64,
+    /// Bumped per table by [`QueryCache::invalidate_table`].
+    generations: HashMap<String, u64>,
+}
+
+impl Entries {
+    fn generation(&self, table: &str) -> u64 {
+        self.epoch + self.generations.get(table).copied().unwrap_or(0)
+    }
+
+    fn remove_where(&mut self, mut pred: impl FnMut(&Entry) -> bool) {
+        let mut freed = 0;
+        self.map.retain(|_, entry| {
+            let remove = pred(entry);
+            if remove {
+                freed += entry.bytes;
+            }
+            !remove
+        });
+        self.bytes -= freed;
+    }
+}
+
+/// Handle to a client's query cache. Cheap to clone; clones share entries.
+#[derive(Debug, Clone)]
+pub struct QueryCache {
+    config: Arc<CacheConfig>,
+    entries: Arc<Mutex<Entries>>,
+}
+
+impl QueryCache {
+    pub(crate) fn new(config: CacheConfig) -> Self {
+        Self {
+            config: Arc::new(config),
+            entries: Arc::default(),
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Entries> {
+        self.entries.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Drop every cached response for `table`, in any schema.
+    pub fn invalidate_table(&self, table: &str) {
+        let mut entries = self.lock();
+        *entries.generations.entry(table.to_string()).or_default() += 1;
+        entries.remove_where(|entry| entry.table == table);
+    }
+
+    /// Drop every cached response.
+    pub fn clear(&self) {
+        let mut entries = self.lock();
+        entries.map.clear();
+        entries.bytes = 0;
+        entries.epoch += 1;
+    }
+
+    /// Number of cached responses, including expired ones not yet evicted.
+    pub fn len(&self) -> usize {
+        self.lock().map.len()
+    }
+
+    /// `true` when nothing is cached.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// A `postgres_changes` callback that invalidates the changed table, for
+    /// [`ChannelBuilder::on_postgres_changes_callback`](crate::realtime::ChannelBuilder::on_postgres_changes_callback).
+    #[cfg(feature = "realtime")]
+    pub fn invalidator(
+        &self,
+    ) -> impl Fn(crate::realtime::PostgresChangePayload) + Send + Sync + 'static {
+        let cache = self.clone();
+        move |change| cache.invalidate_table(&change.table)
+    }
+
+    pub(crate) fn get(&self, key: &CacheKey) -> Option<(Option<u64>, Value)> {
+        let mut entries = self.lock();
+        entries.clock += 1;
+        let now = entries.clock;
+        let entry = entries.map.get_mut(key)?;
+        if entry.expires <= Instant::now() {
+            let bytes = entry.bytes;
+            entries.map.remove(key);
+            entries.bytes -= bytes;
+            return None;
+        }
+        entry.last_used = now;
+        Some((entry.count, entry.value.clone()))
+    }
+
+    /// Read before sending a select and hand to [`put`](Self::put): a
+    /// response that raced an invalidation of its table isn't cached.
+    pub(crate) fn generation(&self, table: &str) -> u64 {
+        self.lock().generation(table)
+    }
+
+    /// Cache `value` unless `table` was invalidated since `generation` was
+    /// read.
+    pub(crate) fn put(
+        &self,
+        key: CacheKey,
+        table: String,
+        generation: u64,
+        count: Option<u64>,
+        value: Value,
+    ) {
+        let bytes = value.to_string().len();
+        if bytes > self.config.max_bytes || self.config.max_entries == 0 {
+            return;
+        }
+        let mut entries = self.lock();
+        if entries.generation(&table) != generation {
+            return;
+        }
+        let now = Instant::now();
+        entries.remove_where(|entry| entry.expires <= now);
+        if let Some(old) = entries.map.remove(&key) {
+            entries.bytes -= old.bytes;
+        }
+        // Evict least recently used entries until the new one fits.
+        while entries.map.len() >= self.config.max_entries
+            || entries.bytes + bytes > self.config.max_bytes
+        {
+            let Some(oldest) = entries
+                .map
+                .iter()
+                .min_by_key(|(_, entry)| entry.last_used)
+                .map(|(key, _)| key.clone())
+            else {
+                break;
+            };
+            if let Some(evicted) = entries.map.remove(&oldest) {
+                entries.bytes -= evicted.bytes;
+            }
+        }
+        entries.clock += 1;
+        let last_used = entries.clock;
+        entries.bytes += bytes;
+        entries.map.insert(
+            key,
+            Entry {
+                table,
+                count,
+                value,
+                bytes,
+                expires: now + self.config.ttl,
+                last_used,
+            },
+        );
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn key(path: &str) -> CacheKey {
+        CacheKey {
+            schema: None,
+            bearer: "anon".into(),
+            prefer: Vec::new(),
+            path: path.into(),
+        }
+    }
+
+    fn cache(max_entries: usize, max_bytes: usize) -> QueryCache {
+        QueryCache::new(CacheConfig {
+            ttl: Duration::from_secs(60),
+            max_entries,
+            max_bytes,
+        })
+    }
+
+    #[test]
+    fn hits_until_the_table_is_invalidated() {
+        let c = cache(10, 1024);
+        c.put(key("/a"), "a".into(), 0, Some(1), json!([1]));
+        c.put(key("/b"), "b".into(), 0, None, json!([2]));
+        assert_eq!(c.get(&key("/a")), Some((Some(1), json!([1]))));
+        let other_user = CacheKey { bearer: "jwt".into(), ..key("/a") };
+        assert_eq!(c.get(&other_user), None);
+
+        c.invalidate_table("a");
+        assert_eq!(c.get(&key("/a")), None);
+        assert_eq!(c.len(), 1);
+        c.clear();
+        assert!(c.is_empty());
+    }
+
+    #[test]
+    fn evicts_least_recently_used_beyond_limits() {
+        let c = cache(2, 1024);
+        c.put(key("/a"), "t".into(), 0, None, json!([1]));
+        c.put(key("/b"), "t".into(), 0, None, json!([2]));
+        c.get(&key("/a"));
+        c.put(key("/c"), "t".into(), 0, None, json!([3]));
+        assert!(c.get(&key("/a")).is_some());
+        assert!(c.get(&key("/b")).is_none());
+
+        // `[1]` is 3 bytes: a 7-byte budget holds two of them.
+        let c = cache(10, 7);
+        c.put(key("/a"), "t".into(), 0, None, json!([1]));
+        c.put(key("/b"), "t".into(), 0, None, json!([2]));
+        c.put(key("/c"), "t".into(), 0, None, json!([3]));
+        assert_eq!(c.len(), 2);
+        c.put(key("/big"), "t".into(), 0, None, json!(["too large"]));
+        assert!(c.get(&key("/big")).is_none());
+    }
+
+    #[test]
+    fn responses_that_raced_an_invalidation_are_not_cached() {
+        let c = cache(10, 1024);
+        let before = c.generation("a");
+        c.invalidate_table("a");
+        c.put(key("/a"), "a".into(), before, None, json!([1]));
+        assert!(c.is_empty());
+
+        let before = c.generation("a");
+        c.invalidate_table("b");
+        c.put(key("/a"), "a".into(), before, None, json!([1]));
+        assert_eq!(c.len(), 1, "other tables don't matter");
+
+        let before = c.generation("a");
+        c.clear();
+        c.put(key("/a"), "a".into(), before, None, json!([1]));
+        assert!(c.is_empty());
+    }
+
+    #[test]
+    fn embeds_are_told_apart_from_aggregates() {
+        assert!(embeds_resources("*,author:authors(*)"));
+        assert!(embeds_resources("id,authors!inner(name)"));
+        assert!(embeds_resources("id,...profiles(name)"));
+        assert!(embeds_resources("id,author_id(*)"));
+        assert!(!embeds_resources("*"));
+        assert!(!embeds_resources("status,count(),total:amount.sum()"));
+        assert!(!embeds_resources("id,views:view_count::text,data->a"));
+    }
+
+    #[test]
+    fn expired_entries_are_not_served() {
+        let c = QueryCache::new(CacheConfig {
+            ttl: Duration::ZERO,
+            ..CacheConfig::default()
+        });
+        c.put(key("/a"), "a".into(), 0, None, json!([]));
+        assert_eq!(c.get(&key("/a")), None);
+        assert!(c.is_empty());
+    }
+}
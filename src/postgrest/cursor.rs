@@ -0,0 +1,434 @@
+//! Keyset (cursor) pagination for the typed query path.
+//!
+//! [`TypedBuilder::after`] / [`TypedBuilder::before`] return a
+//! [`CursorBuilder`] that pages on a typed sort column, optionally with a
+//! second tie-breaking column for sort keys that aren't unique:
+//!
+//! ```no_run
+//! # use rust_supabase_sdk::{SupabaseClient, Row, postgrest::{Column, Cursor, Page}};
+//! # use serde::{Serialize, Deserialize};
+//! # #[derive(Debug, Clone, Serialize, Deserialize)]
+//! # struct Posts { id: String, created_at: String }
+//! # impl Row for Posts { const TABLE: &'static str = "posts"; }
+//! # #[allow(non_upper_case_globals)]
+//! # impl Posts {
+//! #     pub const id: Column<Posts, String> = Column::new("id");
+//! #     pub const created_at: Column<Posts, String> = Column::new("created_at");
+//! # }
+//! # async fn demo(client: &SupabaseClient, from_request: Option<&str>) -> rust_supabase_sdk::Result<()> {
+//! let cursor = from_request.map(Cursor::decode).transpose()?;
+//! let page: Page<Posts> = client
+//!     .from_row::<Posts>()
+//!     .after(Posts::created_at, cursor)
+//!     .tie_breaker(Posts::id)
+//!     .limit(20)
+//!     .await?;
+//! // `page` serializes as `{ "rows": [...], "next_cursor": "eyJr..." | null }`.
+//! # Ok(()) }
+//! ```
+//!
+//! Unlike `range`/`offset`, each page is a `WHERE (col, tie) > (last seen)`
+//! seek, so rows inserted or deleted between requests never shift the page
+//! boundaries.
+
+use std::fmt;
+use std::future::{Future, IntoFuture};
+use std::marker::PhantomData;
+use std::pin::Pin;
+use std::str::FromStr;
+
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::Value;
+
+use crate::error::{Result, SupabaseError};
+
+use super::builder::PostgrestBuilder;
+use super::column::Column;
+use super::row::Row;
+
+/// Opaque position marker returned in [`Page::next_cursor`].
+///
+/// Serializes as a single URL-safe string token, so it can be handed to API
+/// clients and fed back verbatim via [`Cursor::decode`] or `str::parse`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Cursor {
+    columns: Vec<String>,
+    values: Vec<Value>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct CursorRepr {
+    k: Vec<String>,
+    v: Vec<Value>,
+}
+
+impl Cursor {
+    /// Render the cursor as its opaque string token.
+    pub fn encode(&self) -> String {
+        let repr = CursorRepr {
+            k: self.columns.clone(),
+            v: self.values.clone(),
+        };
+        // Serializing strings and JSON values cannot fail.
+        let json = serde_json::to_vec(&repr).unwrap_or_default();
+        URL_SAFE_NO_PAD.encode(json)
+    }
+
+    /// Parse a token previously produced by [`Cursor::encode`].
+    pub fn decode(token: &str) -> Result<Self> {
+        let invalid = |message: String| SupabaseError::Decode {
+            message,
+            body: token.to_string(),
+        };
+        let bytes = URL_SAFE_NO_PAD
+            .decode(token.trim())
+            .map_err(|e| invalid(format!("invalid cursor: {e}")))?;
+        let repr: CursorRepr =
+            serde_json::from_slice(&bytes).map_err(|e| invalid(format!("invalid cursor: {e}")))?;
+        if repr.k.is_empty() || repr.k.len() != repr.v.len() {
+            return Err(invalid("invalid cursor: malformed key list".into()));
+        }
+        Ok(Self {
+            columns: repr.k,
+            values: repr.v,
+        })
+    }
+}
+
+impl fmt::Display for Cursor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.encode())
+    }
+}
+
+impl FromStr for Cursor {
+    type Err = SupabaseError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Self::decode(s)
+    }
+}
+
+impl Serialize for Cursor {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.encode())
+    }
+}
+
+impl<'de> Deserialize<'de> for Cursor {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        let token = String::deserialize(deserializer)?;
+        Self::decode(&token).map_err(serde::de::Error::custom)
+    }
+}
+
+/// One page of keyset-paginated rows.
+///
+/// `next_cursor` is `None` once a page comes back shorter than the limit,
+/// i.e. there is nothing left to fetch in this direction.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Page<R> {
+    pub rows: Vec<R>,
+    pub next_cursor: Option<Cursor>,
+}
+
+/// Builder returned by [`TypedBuilder::after`] / [`TypedBuilder::before`].
+///
+/// Awaits to a [`Page<R>`].
+///
+/// [`TypedBuilder::after`]: super::TypedBuilder::after
+/// [`TypedBuilder::before`]: super::TypedBuilder::before
+#[must_use = "CursorBuilder is lazy — `.await` it or call `.execute()`"]
+pub struct CursorBuilder<R: Row> {
+    inner: PostgrestBuilder<Value>,
+    columns: Vec<&'static str>,
+    ascending: bool,
+    cursor: Option<Cursor>,
+    _row: PhantomData<fn() -> R>,
+}
+
+impl<R: Row> fmt::Debug for CursorBuilder<R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CursorBuilder")
+            .field("inner", &self.inner)
+            .field("columns", &self.columns)
+            .field("ascending", &self.ascending)
+            .field("cursor", &self.cursor)
+            .finish()
+    }
+}
+
+impl<R: Row> CursorBuilder<R> {
+    pub(crate) fn new<V>(
+        inner: PostgrestBuilder<Value>,
+        col: Column<R, V>,
+        ascending: bool,
+        cursor: Option<Cursor>,
+    ) -> Self {
+        Self {
+            inner,
+            columns: vec![col.name()],
+            ascending,
+            cursor,
+            _row: PhantomData,
+        }
+    }
+
+    /// Break ties on a second column when the sort column isn't unique
+    /// (e.g. `created_at` + `id`). The pair must be unique across the table.
+    pub fn tie_breaker<W>(mut self, col: Column<R, W>) -> Self {
+        self.columns.truncate(1);
+        self.columns.push(col.name());
+        self
+    }
+
+    /// Page size. Without a limit the whole remainder comes back as one page.
+    pub fn limit(mut self, n: u64) -> Self {
+        self.inner = self.inner.limit(n);
+        self
+    }
+
+    /// Assemble the seek filter + order onto the wrapped builder.
+    fn into_query(self) -> Result<(PostgrestBuilder<Value>, Vec<&'static str>)> {
+        let Self {
+            mut inner,
+            columns,
+            ascending,
+            cursor,
+            ..
+        } = self;
+        let dir = if ascending { "asc" } else { "desc" };
+        let op = if ascending { "gt" } else { "lt" };
+
+        // Keyset paging owns the ordering; anything set earlier would make
+        // the seek predicate and the sort disagree.
+        inner.state.params.retain(|(k, _)| k != "order");
+        let order: Vec<String> = columns.iter().map(|c| format!("{c}.{dir}")).collect();
+        inner.push_param("order", order.join(","));
+
+        if let Some(cursor) = cursor {
+            if cursor
+                .columns
+                .iter()
+                .map(String::as_str)
+                .ne(columns.iter().copied())
+            {
+                return Err(SupabaseError::Unexpected(format!(
+                    "cursor was issued for ({}) but the query pages on ({})",
+                    cursor.columns.join(", "),
+                    columns.join(", ")
+                )));
+            }
+            inner = match (columns.as_slice(), cursor.values.as_slice()) {
+                ([col], [value]) => inner.filter(col, op, render_value(value)?),
+                ([col, tie], [value, tie_value]) => {
+                    let value = quote(&render_value(value)?);
+                    let tie_value = quote(&render_value(tie_value)?);
+                    inner.or(&format!(
+                        "{col}.{op}.{value},and({col}.eq.{value},{tie}.{op}.{tie_value})"
+                    ))
+                }
+                _ => {
+                    return Err(SupabaseError::Unexpected(
+                        "cursor key count does not match the query".into(),
+                    ))
+                }
+            };
+        }
+        Ok((inner, columns))
+    }
+
+    /// Render the path this builder would send. Errors only when the cursor
+    /// doesn't match the paging columns.
+    pub fn build_path(self) -> Result<String> {
+        Ok(self.into_query()?.0.build_path())
+    }
+
+    /// Fetch one page and compute the cursor for the next one.
+    pub async fn execute(self) -> Result<Page<R>> {
+        let (query, columns) = self.into_query()?;
+        let limit = query.state.limit;
+        let table = query.table.clone();
+        let (_count, raw) = query.execute_inner().await?;
+
+        let next_cursor = match (limit, raw.last()) {
+            (Some(limit), Some(last)) if raw.len() as u64 >= limit => {
+                let mut values = Vec::with_capacity(columns.len());
+                for col in &columns {
+                    match last.get(*col) {
+                        Some(v) if !v.is_null() => values.push(v.clone()),
+                        _ => {
+                            return Err(SupabaseError::Unexpected(format!(
+                                "cursor column `{col}` missing or null in row from `{table}`"
+                            )))
+                        }
+                    }
+                }
+                Some(Cursor {
+                    columns: columns.iter().map(|c| c.to_string()).collect(),
+                    values,
+                })
+            }
+            _ => None,
+        };
+
+        let rows = raw
+            .into_iter()
+            .map(|v| {
+                serde_json::from_value(v.clone()).map_err(|e| SupabaseError::Decode {
+                    message: e.to_string(),
+                    body: v.to_string(),
+                })
+            })
+            .collect::<Result<Vec<R>>>()?;
+        Ok(Page { rows, next_cursor })
+    }
+}
+
+impl<R: Row> IntoFuture for CursorBuilder<R> {
+    type Output = Result<Page<R>>;
+    type IntoFuture = Pin<Box<dyn Future<Output = Self::Output> + Send>>;
+
+    fn into_future(self) -> Self::IntoFuture {
+        Box::pin(self.execute())
+    }
+}
+
+/// Textual form of a cursor value as PostgREST expects it in a filter.
+fn render_value(value: &Value) -> Result<String> {
+    match value {
+        Value::String(s) => Ok(s.clone()),
+        Value::Number(_) | Value::Bool(_) => Ok(value.to_string()),
+        other => Err(SupabaseError::Unexpected(format!(
+            "unsupported cursor value `{other}`"
+        ))),
+    }
+}
+
+/// Double-quote a value inside a logic tree so `.`, `,`, `:` and parens in
+/// timestamps or text don't get parsed as syntax.
+fn quote(raw: &str) -> String {
+    format!("\"{}\"", raw.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+    use crate::SupabaseClient;
+    use serde_json::json;
+
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    struct Posts {
+        id: i64,
+        created_at: String,
+    }
+
+    impl Row for Posts {
+        const TABLE: &'static str = "posts";
+    }
+
+    #[allow(non_upper_case_globals)]
+    impl Posts {
+        pub const id: Column<Posts, i64> = Column::new("id");
+        pub const created_at: Column<Posts, String> = Column::new("created_at");
+    }
+
+    fn client() -> SupabaseClient {
+        SupabaseClient::new("https://x.supabase.co", "anon", None)
+    }
+
+    fn cursor(columns: &[&str], values: Vec<Value>) -> Cursor {
+        Cursor {
+            columns: columns.iter().map(|c| c.to_string()).collect(),
+            values,
+        }
+    }
+
+    #[test]
+    fn cursor_round_trips_through_token_and_serde() {
+        let c = cursor(
+            &["created_at", "id"],
+            vec![json!("2024-01-01T00:00:00Z"), json!(7)],
+        );
+        assert_eq!(Cursor::decode(&c.encode()).unwrap(), c);
+        assert_eq!(c.to_string().parse::<Cursor>().unwrap(), c);
+        let page = Page::<Posts> {
+            rows: vec![],
+            next_cursor: Some(c.clone()),
+        };
+        let v = serde_json::to_value(&page).unwrap();
+        assert_eq!(v["next_cursor"], json!(c.encode()));
+        let back: Page<Posts> = serde_json::from_value(v).unwrap();
+        assert_eq!(back.next_cursor, Some(c));
+    }
+
+    #[test]
+    fn garbage_token_is_a_decode_error() {
+        assert!(matches!(
+            Cursor::decode("%%%"),
+            Err(SupabaseError::Decode { .. })
+        ));
+        let not_a_cursor = URL_SAFE_NO_PAD.encode(b"{\"k\":[],\"v\":[]}");
+        assert!(Cursor::decode(&not_a_cursor).is_err());
+    }
+
+    #[test]
+    fn first_page_only_orders() {
+        let p = client()
+            .from_row::<Posts>()
+            .after(Posts::id, None)
+            .limit(10)
+            .build_path()
+            .unwrap();
+        assert_eq!(p, "/rest/v1/posts?select=%2A&order=id.asc&limit=10");
+    }
+
+    #[test]
+    fn after_single_column_seeks_with_gt() {
+        let p = client()
+            .from_row::<Posts>()
+            .after(Posts::id, cursor(&["id"], vec![json!(42)]))
+            .build_path()
+            .unwrap();
+        assert_eq!(p, "/rest/v1/posts?select=%2A&order=id.asc&id=gt.42");
+    }
+
+    #[test]
+    fn before_with_tie_breaker_builds_or_tree() {
+        let c = cursor(
+            &["created_at", "id"],
+            vec![json!("2024-01-01T00:00:00Z"), json!(9)],
+        );
+        let p = client()
+            .from_row::<Posts>()
+            .order(Posts::id, true)
+            .before(Posts::created_at, c)
+            .tie_breaker(Posts::id)
+            .build_path()
+            .unwrap();
+        let expected_or = encode(
+            "(created_at.lt.\"2024-01-01T00:00:00Z\",and(created_at.eq.\"2024-01-01T00:00:00Z\",id.lt.\"9\"))",
+        );
+        assert_eq!(
+            p,
+            format!("/rest/v1/posts?select=%2A&order=created_at.desc,id.desc&or={expected_or}")
+        );
+    }
+
+    #[test]
+    fn cursor_for_other_columns_is_rejected() {
+        let err = client()
+            .from_row::<Posts>()
+            .after(Posts::created_at, cursor(&["id"], vec![json!(1)]))
+            .build_path()
+            .unwrap_err();
+        assert!(matches!(err, SupabaseError::Unexpected(_)));
+    }
+
+    fn encode(s: &str) -> String {
+        urlencoding::encode(s).into_owned()
+    }
+}
@@ -0,0 +1,589 @@
+//! Typed resource embedding (PostgREST joins).
+//!
+//! Foreign-key relations are declared as [`Relation<R, O>`] constants next to
+//! the [`Column`](super::Column) constants, plus a [`Related<O>`] impl when
+//! `R` has exactly one relation to `O`. Embedding switches the builder's
+//! output to the composite [`Embeddable::WithRelations`] struct:
+//!
+//! ```no_run
+//! # use rust_supabase_sdk::{SupabaseClient, Row};
+//! # use rust_supabase_sdk::postgrest::{Column, Embeddable, Related, Relation};
+//! # use serde::{Serialize, Deserialize};
+//! # #[derive(Debug, Clone, Serialize, Deserialize)]
+//! # struct Users { id: String, name: String }
+//! # impl Row for Users { const TABLE: &'static str = "users"; }
+//! # #[allow(non_upper_case_globals)]
+//! # impl Users { pub const name: Column<Users, String> = Column::new("name"); }
+//! # #[derive(Debug, Clone, Serialize, Deserialize)]
+//! # struct Posts { id: String, author_id: String }
+//! # impl Row for Posts { const TABLE: &'static str = "posts"; }
+//! #[allow(non_upper_case_globals)]
+//! impl Posts {
+//!     pub const author: Relation<Posts, Users> = Relation::to_one("author", Some("author_id"));
+//! }
+//! impl Related<Users> for Posts {
+//!     const RELATION: Relation<Posts, Users> = Posts::author;
+//! }
+//!
+//! #[derive(Debug, Clone, Serialize, Deserialize)]
+//! pub struct PostsWithRelations {
+//!     #[serde(flatten)]
+//!     pub row: Posts,
+//!     #[serde(default, skip_serializing_if = "Option::is_none")]
+//!     pub author: Option<Users>,
+//! }
+//! impl Embeddable for Posts {
+//!     type WithRelations = PostsWithRelations;
+//! }
+//!
+//! # async fn demo(client: SupabaseClient) -> rust_supabase_sdk::Result<()> {
+//! // select=*,author:users!author_id!inner(*)&author.name=eq.alice
+//! let rows: Vec<PostsWithRelations> = client
+//!     .from_row::<Posts>()
+//!     .embed_inner_with::<Users>(|author| author.eq(Users::name, "alice".to_string()))
+//!     .await?;
+//! # Ok(()) }
+//! ```
+//!
+//! `cargo supabase gen types` emits the relation constants, `Related` impls,
+//! composite struct and `Embeddable` impl from the foreign keys PostgREST
+//! reports in its OpenAPI document.
+
+use std::fmt;
+use std::marker::PhantomData;
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::Value;
+
+use super::builder::{Operation, Order, PostgrestBuilder};
+use super::column::{Column, IntoColumnName};
+use super::logic::Filter;
+use super::row::Row;
+use super::typed_builder::TypedBuilder;
+
+/// Whether a relation yields a single row or a list.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Cardinality {
+    /// Many-to-one (the foreign key lives on `R`): embeds as an object.
+    One,
+    /// One-to-many (the foreign key lives on `O`): embeds as an array.
+    Many,
+}
+
+/// A statically-typed foreign-key relation from row type `R` to row type `O`.
+///
+/// `name` is the JSON key the embed lands under (rendered as a PostgREST
+/// alias); `hint` disambiguates when several foreign keys link the same two
+/// tables — a constraint name or the foreign-key column both work.
+pub struct Relation<R, O> {
+    name: &'static str,
+    hint: Option<&'static str>,
+    cardinality: Cardinality,
+    _phantom: PhantomData<fn(R) -> O>,
+}
+
+impl<R, O> Relation<R, O> {
+    /// A many-to-one relation: each `R` references at most one `O`.
+    pub const fn to_one(name: &'static str, hint: Option<&'static str>) -> Self {
+        Self {
+            name,
+            hint,
+            cardinality: Cardinality::One,
+            _phantom: PhantomData,
+        }
+    }
+
+    /// A one-to-many relation: each `R` is referenced by any number of `O`.
+    pub const fn to_many(name: &'static str, hint: Option<&'static str>) -> Self {
+        Self {
+            name,
+            hint,
+            cardinality: Cardinality::Many,
+            _phantom: PhantomData,
+        }
+    }
+
+    /// JSON key / alias the embedded rows appear under.
+    pub const fn name(&self) -> &'static str {
+        self.name
+    }
+
+    /// Disambiguation hint, if any.
+    pub const fn hint(&self) -> Option<&'static str> {
+        self.hint
+    }
+
+    /// Whether the embed is an object or an array.
+    pub const fn cardinality(&self) -> Cardinality {
+        self.cardinality
+    }
+}
+
+// Manual impls so `R` and `O` don't need bounds.
+impl<R, O> Clone for Relation<R, O> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+impl<R, O> Copy for Relation<R, O> {}
+
+impl<R, O> fmt::Debug for Relation<R, O> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Relation")
+            .field("name", &self.name)
+            .field("hint", &self.hint)
+            .field("cardinality", &self.cardinality)
+            .finish()
+    }
+}
+
+/// Declares the (unique) relation from `Self` to `O`, enabling
+/// [`TypedBuilder::embed::<O>()`](TypedBuilder::embed). When two tables are
+/// linked by more than one foreign key, skip this impl and embed through the
+/// relation constants with [`TypedBuilder::embed_via`].
+pub trait Related<O: Row>: Row {
+    const RELATION: Relation<Self, O>;
+}
+
+/// A row type with a composite "row + embedded relations" struct.
+///
+/// `WithRelations` flattens `Self` and carries one `Option` field per
+/// relation; relations that weren't embedded in a query stay `None`.
+pub trait Embeddable: Row {
+    type WithRelations: DeserializeOwned + Send + 'static;
+}
+
+/// Scoped options for one embedded table, built inside the closure passed to
+/// [`TypedBuilder::embed_with`] / [`TypedBuilder::embed_via`].
+///
+/// Filters, ordering and limits render as `<alias>.<param>` query params, so
+/// they only affect the embedded rows (or, with [`inner`](Self::inner), which
+/// parent rows match).
+#[must_use]
+pub struct EmbedScope<O: Row> {
+    scratch: PostgrestBuilder<Value>,
+    columns: String,
+    inner_join: bool,
+    _row: PhantomData<fn() -> O>,
+}
+
+impl<O: Row> fmt::Debug for EmbedScope<O> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("EmbedScope")
+            .field("table", &O::TABLE)
+            .field("columns", &self.columns)
+            .field("inner_join", &self.inner_join)
+            .field("state", &self.scratch.state)
+            .finish()
+    }
+}
+
+impl<O: Row> EmbedScope<O> {
+    /// Columns to pull from the embedded table (PostgREST syntax). Defaults
+    /// to `*`.
+    pub fn select(mut self, columns: impl Into<String>) -> Self {
+        self.columns = columns.into();
+        self
+    }
+
+    /// Render as an `!inner` join: parent rows without a matching embedded
+    /// row are dropped, so filters in this scope also filter the parents.
+    pub fn inner(mut self) -> Self {
+        self.inner_join = true;
+        self
+    }
+
+    /// `column = value` on the embedded table.
+    pub fn eq<V>(mut self, col: Column<O, V>, val: V) -> Self
+    where
+        V: Serialize + fmt::Display,
+    {
+        self.scratch = self.scratch.eq(col.name(), val.to_string());
+        self
+    }
+
+    /// `column <> value` on the embedded table.
+    pub fn neq<V>(mut self, col: Column<O, V>, val: V) -> Self
+    where
+        V: Serialize + fmt::Display,
+    {
+        self.scratch = self.scratch.neq(col.name(), val.to_string());
+        self
+    }
+
+    /// `column > value` on the embedded table.
+    pub fn gt<V>(mut self, col: Column<O, V>, val: V) -> Self
+    where
+        V: Serialize + fmt::Display + PartialOrd,
+    {
+        self.scratch = self.scratch.gt(col.name(), val.to_string());
+        self
+    }
+
+    /// `column >= value` on the embedded table.
+    pub fn gte<V>(mut self, col: Column<O, V>, val: V) -> Self
+    where
+        V: Serialize + fmt::Display + PartialOrd,
+    {
+        self.scratch = self.scratch.gte(col.name(), val.to_string());
+        self
+    }
+
+    /// `column < value` on the embedded table.
+    pub fn lt<V>(mut self, col: Column<O, V>, val: V) -> Self
+    where
+        V: Serialize + fmt::Display + PartialOrd,
+    {
+        self.scratch = self.scratch.lt(col.name(), val.to_string());
+        self
+    }
+
+    /// `column <= value` on the embedded table.
+    pub fn lte<V>(mut self, col: Column<O, V>, val: V) -> Self
+    where
+        V: Serialize + fmt::Display + PartialOrd,
+    {
+        self.scratch = self.scratch.lte(col.name(), val.to_string());
+        self
+    }
+
+    /// `LIKE` on a string column of the embedded table.
+    pub fn like(mut self, col: Column<O, String>, pattern: impl AsRef<str>) -> Self {
+        self.scratch = self.scratch.like(col.name(), pattern.as_ref());
+        self
+    }
+
+    /// `ILIKE` on a string column of the embedded table.
+    pub fn ilike(mut self, col: Column<O, String>, pattern: impl AsRef<str>) -> Self {
+        self.scratch = self.scratch.ilike(col.name(), pattern.as_ref());
+        self
+    }
+
+    /// `column IS NULL` on a nullable column of the embedded table.
+    pub fn is_null<V>(mut self, col: Column<O, Option<V>>) -> Self {
+        self.scratch = self.scratch.is(col.name(), "null");
+        self
+    }
+
+    /// `column IS NOT NULL` on a nullable column of the embedded table.
+    pub fn is_not_null<V>(mut self, col: Column<O, Option<V>>) -> Self {
+        self.scratch = self.scratch.is(col.name(), "not.null");
+        self
+    }
+
+    /// `column = ANY (vals)` on the embedded table.
+    pub fn in_<V, I>(mut self, col: Column<O, V>, vals: I) -> Self
+    where
+        V: Serialize + fmt::Display,
+        I: IntoIterator<Item = V>,
+    {
+        self.scratch = self
+            .scratch
+            .in_(col.name(), vals.into_iter().map(|v| v.to_string()));
+        self
+    }
+
+    /// Apply a [`Filter<O>`] expression to the embedded table, e.g.
+    /// `author.or=(…)`.
+    pub fn where_(mut self, filter: Filter<O>) -> Self {
+        filter.apply(&mut self.scratch, None);
+        self
+    }
+
+    /// Order the embedded rows.
+    pub fn order(mut self, col: impl IntoColumnName<O>, ascending: bool) -> Self {
+        self.scratch = self.scratch.order(&col.into_column_name(), ascending);
+        self
+    }
+
+    /// Order the embedded rows with full [`Order`] options. `foreign_table`
+    /// is ignored — the scope already is the foreign table.
+    pub fn order_with(mut self, col: impl IntoColumnName<O>, options: Order) -> Self {
+        let options = Order {
+            foreign_table: None,
+            ..options
+        };
+        self.scratch = self.scratch.order_with(&col.into_column_name(), options);
+        self
+    }
+
+    /// Cap the number of embedded rows per parent.
+    pub fn limit(mut self, n: u64) -> Self {
+        self.scratch = self.scratch.limit(n);
+        self
+    }
+
+    /// Skip the first `n` embedded rows per parent.
+    pub fn offset(mut self, n: u64) -> Self {
+        self.scratch = self.scratch.offset(n);
+        self
+    }
+}
+
+impl<R: Row, O> TypedBuilder<R, O> {
+    /// Embed the related table `E` via the `Related<E>` relation, e.g.
+    /// `select=*,author:users!author_id(*)`. Rows come back as
+    /// `R::WithRelations`.
+    ///
+    /// Call [`select`](TypedBuilder::select) *before* embedding — it
+    /// replaces the whole projection, embeds included.
+    pub fn embed<E: Row>(self) -> TypedBuilder<R, R::WithRelations>
+    where
+        R: Related<E> + Embeddable,
+    {
+        self.embed_via(R::RELATION, |scope| scope)
+    }
+
+    /// Like [`embed`](Self::embed) but as an `!inner` join: parent rows
+    /// without a matching `E` row are dropped.
+    pub fn embed_inner<E: Row>(self) -> TypedBuilder<R, R::WithRelations>
+    where
+        R: Related<E> + Embeddable,
+    {
+        self.embed_via(R::RELATION, EmbedScope::inner)
+    }
+
+    /// Embed `E` with scoped filters / ordering / limits.
+    pub fn embed_with<E: Row>(
+        self,
+        scope: impl FnOnce(EmbedScope<E>) -> EmbedScope<E>,
+    ) -> TypedBuilder<R, R::WithRelations>
+    where
+        R: Related<E> + Embeddable,
+    {
+        self.embed_via(R::RELATION, scope)
+    }
+
+    /// [`embed_with`](Self::embed_with) as an `!inner` join, so the scoped
+    /// filters also restrict which parent rows are returned.
+    pub fn embed_inner_with<E: Row>(
+        self,
+        scope: impl FnOnce(EmbedScope<E>) -> EmbedScope<E>,
+    ) -> TypedBuilder<R, R::WithRelations>
+    where
+        R: Related<E> + Embeddable,
+    {
+        self.embed_via(R::RELATION, |s| scope(s.inner()))
+    }
+
+    /// Embed through an explicit relation constant. Needed when `R` has
+    /// several relations to the same table (e.g. `Posts::author` and
+    /// `Posts::editor`, both to `Users`).
+    pub fn embed_via<E: Row>(
+        mut self,
+        relation: Relation<R, E>,
+        scope: impl FnOnce(EmbedScope<E>) -> EmbedScope<E>,
+    ) -> TypedBuilder<R, R::WithRelations>
+    where
+        R: Embeddable,
+    {
+        let blank = EmbedScope {
+            scratch: PostgrestBuilder::new(
+                self.inner.client.clone(),
+                E::TABLE.to_string(),
+                Operation::Select,
+            ),
+            columns: "*".to_string(),
+            inner_join: false,
+            _row: PhantomData,
+        };
+        let EmbedScope {
+            scratch,
+            columns,
+            inner_join,
+            ..
+        } = scope(blank);
+
+        let alias = relation.name();
+        let mut target = E::TABLE.to_string();
+        if let Some(hint) = relation.hint() {
+            target.push('!');
+            target.push_str(hint);
+        }
+        if inner_join {
+            target.push_str("!inner");
+        }
+        let select = self.inner.state.select_cols.get_or_insert_with(|| "*".to_string());
+        select.push_str(&format!(",{alias}:{target}({columns})"));
+
+        for (key, value) in scratch.state.params {
+            self.inner.push_param(format!("{alias}.{key}"), value);
+        }
+        if let Some(limit) = scratch.state.limit {
+            self.inner.push_param(format!("{alias}.limit"), limit.to_string());
+        }
+        if let Some(offset) = scratch.state.offset {
+            self.inner.push_param(format!("{alias}.offset"), offset.to_string());
+        }
+
+        TypedBuilder {
+            inner: self.inner,
+            _row: PhantomData,
+        }
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+    use crate::SupabaseClient;
+    use serde::Deserialize;
+
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    struct Users {
+        id: String,
+        name: String,
+    }
+
+    impl Row for Users {
+        const TABLE: &'static str = "users";
+    }
+
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    struct Posts {
+        id: String,
+        author_id: String,
+        editor_id: Option<String>,
+    }
+
+    impl Row for Posts {
+        const TABLE: &'static str = "posts";
+    }
+
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    struct Comments {
+        id: i64,
+        body: String,
+    }
+
+    impl Row for Comments {
+        const TABLE: &'static str = "comments";
+    }
+
+    #[allow(non_upper_case_globals)]
+    impl Users {
+        pub const name: Column<Users, String> = Column::new("name");
+    }
+
+    #[allow(non_upper_case_globals)]
+    impl Comments {
+        pub const id: Column<Comments, i64> = Column::new("id");
+    }
+
+    #[allow(non_upper_case_globals)]
+    impl Posts {
+        pub const author: Relation<Posts, Users> = Relation::to_one("author", Some("author_id"));
+        pub const editor: Relation<Posts, Users> = Relation::to_one("editor", Some("editor_id"));
+        pub const comments: Relation<Posts, Comments> = Relation::to_many("comments", None);
+    }
+
+    impl Related<Comments> for Posts {
+        const RELATION: Relation<Posts, Comments> = Posts::comments;
+    }
+
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    struct PostsWithRelations {
+        #[serde(flatten)]
+        row: Posts,
+        #[serde(default)]
+        author: Option<Users>,
+        #[serde(default)]
+        editor: Option<Users>,
+        #[serde(default)]
+        comments: Option<Vec<Comments>>,
+    }
+
+    impl Embeddable for Posts {
+        type WithRelations = PostsWithRelations;
+    }
+
+    fn client() -> SupabaseClient {
+        SupabaseClient::new("https://x.supabase.co", "anon", None)
+    }
+
+    fn decode(s: &str) -> String {
+        urlencoding::decode(s).unwrap().into_owned()
+    }
+
+    #[test]
+    fn embed_appends_aliased_resource_to_select() {
+        let p = client().from_row::<Posts>().embed::<Comments>().build_path();
+        assert_eq!(decode(&p), "/rest/v1/posts?select=*,comments:comments(*)");
+    }
+
+    #[test]
+    fn embed_inner_adds_inner_modifier() {
+        let p = client().from_row::<Posts>().embed_inner::<Comments>().build_path();
+        assert_eq!(decode(&p), "/rest/v1/posts?select=*,comments:comments!inner(*)");
+    }
+
+    #[test]
+    fn embed_via_disambiguates_with_hint() {
+        let p = client()
+            .from_row::<Posts>()
+            .embed_via(Posts::author, |a| a.select("id,name"))
+            .embed_via(Posts::editor, |e| e)
+            .build_path();
+        assert_eq!(
+            decode(&p),
+            "/rest/v1/posts?select=*,author:users!author_id(id,name),editor:users!editor_id(*)"
+        );
+    }
+
+    #[test]
+    fn scoped_filters_order_and_limit_are_prefixed() {
+        let p = client()
+            .from_row::<Posts>()
+            .embed_with::<Comments>(|c| c.gt(Comments::id, 10).order(Comments::id, false).limit(3))
+            .build_path();
+        assert_eq!(
+            decode(&p),
+            "/rest/v1/posts?select=*,comments:comments(*)&comments.id=gt.10\
+             &comments.order=id.desc&comments.limit=3"
+        );
+    }
+
+    #[test]
+    fn inner_scope_filters_parents() {
+        let p = client()
+            .from_row::<Posts>()
+            .embed_via(Posts::author, |a| a.inner().eq(Users::name, "alice".to_string()))
+            .build_path();
+        assert_eq!(
+            decode(&p),
+            "/rest/v1/posts?select=*,author:users!author_id!inner(*)&author.name=eq.alice"
+        );
+    }
+
+    #[test]
+    fn scoped_filter_expressions_are_prefixed() {
+        let p = client()
+            .from_row::<Posts>()
+            .embed_via(Posts::author, |a| {
+                a.where_(Filter::eq(Users::name, "ann".to_string()) | !Filter::like(Users::name, "b*"))
+            })
+            .build_path();
+        assert_eq!(
+            decode(&p),
+            "/rest/v1/posts?select=*,author:users!author_id(*)&author.or=(name.eq.ann,name.not.like.b*)"
+        );
+    }
+
+    #[test]
+    fn composite_deserializes_embeds_and_defaults_missing_ones() {
+        let row: PostsWithRelations = serde_json::from_value(serde_json::json!({
+            "id": "p1",
+            "author_id": "u1",
+            "editor_id": null,
+            "author": { "id": "u1", "name": "alice" },
+            "comments": [{ "id": 1, "body": "hi" }]
+        }))
+        .unwrap();
+        assert_eq!(row.row.id, "p1");
+        assert_eq!(row.author.unwrap().name, "alice");
+        assert!(row.editor.is_none());
+        assert_eq!(row.comments.unwrap().len(), 1);
+    }
+}
@@ -0,0 +1,290 @@
+//! `EXPLAIN` plans for builder queries.
+//!
+//! PostgREST returns the query plan instead of rows when asked with
+//! `Accept: application/vnd.pgrst.plan+json` (or `+text`). [`explain`]
+//! sends the builder's exact path, filters and body with that header and
+//! parses the result:
+//!
+//! ```no_run
+//! # use rust_supabase_sdk::SupabaseClient;
+//! # use rust_supabase_sdk::postgrest::ExplainOptions;
+//! # async fn demo(client: SupabaseClient) -> rust_supabase_sdk::Result<()> {
+//! let explain = client
+//!     .from("posts")
+//!     .select("*")
+//!     .eq("author_id", "u1")
+//!     .explain(ExplainOptions { analyze: true, ..Default::default() })
+//!     .await?;
+//! let plan = explain.plan().expect("JSON format");
+//! assert!(plan.uses_index("posts_author_id_idx"));
+//! # Ok(()) }
+//! ```
+//!
+//! Plans must be enabled on the server (`db-plan-enabled = true`). With
+//! `analyze` the statement really runs — including writes.
+//!
+//! [`explain`]: PostgrestBuilder::explain
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use crate::error::{Result, SupabaseError};
+
+use super::builder::PostgrestBuilder;
+use super::row::Row;
+use super::typed_builder::TypedBuilder;
+
+/// Output format of the plan.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ExplainFormat {
+    /// `EXPLAIN (FORMAT JSON)`, parsed into a [`QueryPlan`].
+    #[default]
+    Json,
+    /// `EXPLAIN (FORMAT TEXT)`, returned verbatim.
+    Text,
+}
+
+/// `EXPLAIN` options, mapped to the `options=` parameter of the plan media
+/// type.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ExplainOptions {
+    /// Execute the statement and report actual timings and row counts.
+    pub analyze: bool,
+    /// Include output columns and schema-qualified names.
+    pub verbose: bool,
+    /// Report buffer usage (only meaningful with `analyze`).
+    pub buffers: bool,
+    pub format: ExplainFormat,
+}
+
+impl ExplainOptions {
+    fn accept_header(&self) -> String {
+        let media = match self.format {
+            ExplainFormat::Json => "application/vnd.pgrst.plan+json",
+            ExplainFormat::Text => "application/vnd.pgrst.plan+text",
+        };
+        let options: Vec<&str> = [
+            (self.analyze, "analyze"),
+            (self.verbose, "verbose"),
+            (self.buffers, "buffers"),
+        ]
+        .into_iter()
+        .filter_map(|(on, name)| on.then_some(name))
+        .collect();
+        if options.is_empty() {
+            media.to_string()
+        } else {
+            format!("{media}; options={}", options.join("|"))
+        }
+    }
+}
+
+/// One node of a plan tree. Keys PostgreSQL emits that have no field here
+/// (`Filter`, `Index Cond`, `Shared Hit Blocks`, …) are kept in
+/// [`extra`](Self::extra).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlanNode {
+    #[serde(rename = "Node Type")]
+    pub node_type: String,
+    #[serde(rename = "Relation Name", default, skip_serializing_if = "Option::is_none")]
+    pub relation_name: Option<String>,
+    #[serde(rename = "Index Name", default, skip_serializing_if = "Option::is_none")]
+    pub index_name: Option<String>,
+    #[serde(rename = "Startup Cost", default)]
+    pub startup_cost: f64,
+    #[serde(rename = "Total Cost", default)]
+    pub total_cost: f64,
+    /// Estimated rows.
+    #[serde(rename = "Plan Rows", default)]
+    pub plan_rows: f64,
+    #[serde(rename = "Plan Width", default)]
+    pub plan_width: u64,
+    /// Milliseconds; `analyze` only.
+    #[serde(rename = "Actual Startup Time", default, skip_serializing_if = "Option::is_none")]
+    pub actual_startup_time: Option<f64>,
+    /// Milliseconds; `analyze` only.
+    #[serde(rename = "Actual Total Time", default, skip_serializing_if = "Option::is_none")]
+    pub actual_total_time: Option<f64>,
+    #[serde(rename = "Actual Rows", default, skip_serializing_if = "Option::is_none")]
+    pub actual_rows: Option<f64>,
+    #[serde(rename = "Actual Loops", default, skip_serializing_if = "Option::is_none")]
+    pub actual_loops: Option<f64>,
+    #[serde(rename = "Plans", default, skip_serializing_if = "Vec::is_empty")]
+    pub plans: Vec<PlanNode>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+impl PlanNode {
+    /// This node and all of its descendants, depth-first.
+    pub fn nodes(&self) -> Vec<&PlanNode> {
+        let mut out = Vec::new();
+        let mut stack = vec![self];
+        while let Some(node) = stack.pop() {
+            out.push(node);
+            stack.extend(node.plans.iter().rev());
+        }
+        out
+    }
+}
+
+/// A parsed `EXPLAIN (FORMAT JSON)` result.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QueryPlan {
+    #[serde(rename = "Plan")]
+    pub root: PlanNode,
+    /// Milliseconds; `analyze` only.
+    #[serde(rename = "Planning Time", default, skip_serializing_if = "Option::is_none")]
+    pub planning_time: Option<f64>,
+    /// Milliseconds; `analyze` only.
+    #[serde(rename = "Execution Time", default, skip_serializing_if = "Option::is_none")]
+    pub execution_time: Option<f64>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+impl QueryPlan {
+    /// Every node in the plan, root first.
+    pub fn nodes(&self) -> Vec<&PlanNode> {
+        self.root.nodes()
+    }
+
+    /// `true` if any node scans `index`.
+    pub fn uses_index(&self, index: &str) -> bool {
+        self.nodes()
+            .iter()
+            .any(|n| n.index_name.as_deref() == Some(index))
+    }
+
+    /// `true` if any node is a sequential scan over `table`.
+    pub fn seq_scans(&self, table: &str) -> bool {
+        self.nodes()
+            .iter()
+            .any(|n| n.node_type == "Seq Scan" && n.relation_name.as_deref() == Some(table))
+    }
+}
+
+/// Result of [`PostgrestBuilder::explain`], depending on
+/// [`ExplainOptions::format`].
+#[derive(Debug, Clone)]
+pub enum Explain {
+    Json(Box<QueryPlan>),
+    Text(String),
+}
+
+impl Explain {
+    /// The parsed plan, for [`ExplainFormat::Json`].
+    pub fn plan(&self) -> Option<&QueryPlan> {
+        match self {
+            Self::Json(plan) => Some(plan),
+            Self::Text(_) => None,
+        }
+    }
+
+    /// The plan text, for [`ExplainFormat::Text`].
+    pub fn text(&self) -> Option<&str> {
+        match self {
+            Self::Json(_) => None,
+            Self::Text(text) => Some(text),
+        }
+    }
+}
+
+impl<T> PostgrestBuilder<T> {
+    /// Fetch the query plan for this request instead of its rows. Uses the
+    /// same path, filters, `Prefer` headers and body as awaiting the builder.
+    pub async fn explain(self, options: ExplainOptions) -> Result<Explain> {
+        self.state.check()?;
+        let path = self.build_path();
+        let mut opts = self.build_options();
+        opts.headers.push(("Accept".to_string(), options.accept_header()));
+
+        let (_status, _headers, body) = self
+            .client
+            .request_full(&path, self.op.method(), self.state.body.clone(), &opts)
+            .await?;
+
+        match options.format {
+            ExplainFormat::Text => Ok(Explain::Text(body)),
+            ExplainFormat::Json => parse_plan(&body).map(|plan| Explain::Json(Box::new(plan))),
+        }
+    }
+}
+
+impl<R: Row, O> TypedBuilder<R, O> {
+    /// See [`PostgrestBuilder::explain`].
+    pub async fn explain(self, options: ExplainOptions) -> Result<Explain> {
+        self.inner.explain(options).await
+    }
+}
+
+/// PostgreSQL wraps the JSON plan in a one-element array.
+fn parse_plan(body: &str) -> Result<QueryPlan> {
+    let decode = |e: serde_json::Error| SupabaseError::Decode {
+        message: e.to_string(),
+        body: body.to_string(),
+    };
+    let value: Value = serde_json::from_str(body).map_err(decode)?;
+    let plan = match value {
+        Value::Array(mut items) if !items.is_empty() => items.swap_remove(0),
+        other => other,
+    };
+    serde_json::from_value(plan).map_err(decode)
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn accept_header_lists_enabled_options() {
+        assert_eq!(
+            ExplainOptions::default().accept_header(),
+            "application/vnd.pgrst.plan+json"
+        );
+        let opts = ExplainOptions {
+            analyze: true,
+            buffers: true,
+            format: ExplainFormat::Text,
+            ..Default::default()
+        };
+        assert_eq!(
+            opts.accept_header(),
+            "application/vnd.pgrst.plan+text; options=analyze|buffers"
+        );
+    }
+
+    #[test]
+    fn parse_plan_walks_nested_nodes() {
+        let body = r#"[{
+            "Plan": {
+                "Node Type": "Limit", "Startup Cost": 0.0, "Total Cost": 8.3,
+                "Plan Rows": 1, "Plan Width": 40,
+                "Plans": [{
+                    "Node Type": "Index Scan", "Relation Name": "posts",
+                    "Index Name": "posts_author_id_idx", "Total Cost": 8.3,
+                    "Plan Rows": 1, "Plan Width": 40, "Actual Rows": 1,
+                    "Index Cond": "(author_id = 'u1'::text)"
+                }]
+            },
+            "Planning Time": 0.1,
+            "Execution Time": 0.05
+        }]"#;
+        let plan = parse_plan(body).unwrap();
+        assert_eq!(plan.root.node_type, "Limit");
+        assert_eq!(plan.nodes().len(), 2);
+        assert!(plan.uses_index("posts_author_id_idx"));
+        assert!(!plan.seq_scans("posts"));
+        assert_eq!(plan.execution_time, Some(0.05));
+        let scan = &plan.root.plans[0];
+        assert_eq!(scan.actual_rows, Some(1.0));
+        assert_eq!(scan.extra["Index Cond"], "(author_id = 'u1'::text)");
+    }
+
+    #[test]
+    fn parse_plan_reports_decode_errors() {
+        let err = parse_plan("[]").unwrap_err();
+        assert!(matches!(err, SupabaseError::Decode { .. }), "{err:?}");
+    }
+}
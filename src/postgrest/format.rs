@@ -0,0 +1,262 @@
+//! Non-JSON response formats: CSV exports and GeoJSON.
+//!
+//! [`execute_csv`](PostgrestBuilder::execute_csv) asks PostgREST for
+//! `text/csv` and hands back the body as a [`ByteStream`] that is read from
+//! the socket chunk by chunk, so exports of any size run in constant memory:
+//!
+//! ```no_run
+//! # use rust_supabase_sdk::SupabaseClient;
+//! use futures_util::StreamExt;
+//! # async fn demo(client: SupabaseClient, mut out: impl std::io::Write) -> rust_supabase_sdk::Result<()> {
+//! let mut csv = client.from("orders").select("id,total").execute_csv().await?;
+//! while let Some(chunk) = csv.next().await {
+//!     out.write_all(&chunk?).ok();
+//! }
+//! # Ok(()) }
+//! ```
+//!
+//! [`execute_geojson`](PostgrestBuilder::execute_geojson) requests
+//! `application/geo+json` (PostGIS must be installed and the select must
+//! include a geometry column) and decodes a [`FeatureCollection`] whose
+//! `properties` are the builder's row type.
+
+use std::fmt;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use bytes::Bytes;
+use futures_util::stream::{self, Stream};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::error::{Result, SupabaseError};
+
+use super::builder::{Operation, PostgrestBuilder};
+use super::row::Row;
+use super::typed_builder::TypedBuilder;
+
+/// A response body delivered incrementally. Each item is one chunk as it
+/// arrives from the network.
+pub struct ByteStream {
+    inner: Pin<Box<dyn Stream<Item = Result<Bytes>> + Send>>,
+}
+
+impl ByteStream {
+    pub(crate) fn from_response(resp: reqwest::Response) -> Self {
+        let chunks = stream::unfold(Some(resp), |resp| async move {
+            let mut resp = resp?;
+            match resp.chunk().await {
+                Ok(Some(chunk)) => Some((Ok(chunk), Some(resp))),
+                Ok(None) => None,
+                Err(e) => Some((Err(SupabaseError::Transport(e)), None)),
+            }
+        });
+        Self {
+            inner: Box::pin(chunks),
+        }
+    }
+
+    /// A stream of `body` alone, for results known without a request.
+    pub(crate) fn from_static(body: &'static [u8]) -> Self {
+        let chunks = (!body.is_empty()).then(|| Ok(Bytes::from_static(body)));
+        Self {
+            inner: Box::pin(stream::iter(chunks)),
+        }
+    }
+
+    /// Read the remaining stream into memory.
+    pub async fn collect_bytes(mut self) -> Result<Vec<u8>> {
+        use futures_util::StreamExt;
+        let mut out = Vec::new();
+        while let Some(chunk) = self.next().await {
+            out.extend_from_slice(&chunk?);
+        }
+        Ok(out)
+    }
+}
+
+impl Stream for ByteStream {
+    type Item = Result<Bytes>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.inner.as_mut().poll_next(cx)
+    }
+}
+
+impl fmt::Debug for ByteStream {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ByteStream").finish_non_exhaustive()
+    }
+}
+
+/// A GeoJSON position: `[lon, lat]` or `[lon, lat, elevation]`.
+pub type Position = Vec<f64>;
+
+/// A GeoJSON geometry object (RFC 7946 §3.1).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum Geometry {
+    Point { coordinates: Position },
+    MultiPoint { coordinates: Vec<Position> },
+    LineString { coordinates: Vec<Position> },
+    MultiLineString { coordinates: Vec<Vec<Position>> },
+    Polygon { coordinates: Vec<Vec<Position>> },
+    MultiPolygon { coordinates: Vec<Vec<Vec<Position>>> },
+    GeometryCollection { geometries: Vec<Geometry> },
+}
+
+/// A GeoJSON `Feature`: one row, with its geometry column lifted out and the
+/// remaining columns in `properties`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub struct Feature<P = Value> {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<Value>,
+    pub geometry: Option<Geometry>,
+    pub properties: P,
+}
+
+/// A GeoJSON `FeatureCollection`, as returned by
+/// [`PostgrestBuilder::execute_geojson`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub struct FeatureCollection<P = Value> {
+    pub features: Vec<Feature<P>>,
+}
+
+impl<T> PostgrestBuilder<T> {
+    /// Run a select with `Accept: text/csv` and stream the CSV body (header
+    /// line first). The body is never buffered as a whole. A query that
+    /// provably matches nothing (`is_in` with no values) yields an empty
+    /// stream, without a header line.
+    ///
+    /// Only select queries are supported; write operations return
+    /// [`SupabaseError::Unexpected`].
+    pub async fn execute_csv(self) -> Result<ByteStream> {
+        self.send_with_accept("text/csv", b"").await
+    }
+
+    /// Run a select with `Accept: application/geo+json` and decode the
+    /// `FeatureCollection`, with each feature's `properties` decoded as `T`
+    /// (use [`returns`](Self::returns) to pick the type).
+    pub async fn execute_geojson(self) -> Result<FeatureCollection<T>>
+    where
+        T: DeserializeOwned,
+    {
+        let bytes = self
+            .send_with_accept("application/geo+json", br#"{"type":"FeatureCollection","features":[]}"#)
+            .await?
+            .collect_bytes()
+            .await?;
+        serde_json::from_slice(&bytes).map_err(|e| SupabaseError::Decode {
+            message: e.to_string(),
+            body: String::from_utf8_lossy(&bytes).into_owned(),
+        })
+    }
+
+    /// Send the select with `Accept: accept` and stream the body. Builder
+    /// errors and empty-result short-circuits are handled as in
+    /// [`execute`](Self::execute); the latter yield `empty` without a
+    /// request.
+    pub(crate) async fn send_with_accept(self, accept: &str, empty: &'static [u8]) -> Result<ByteStream> {
+        if self.op != Operation::Select {
+            return Err(SupabaseError::Unexpected(format!(
+                "`{accept}` responses are only supported on select queries"
+            )));
+        }
+        self.state.check()?;
+        if self.state.short_circuit_empty_result {
+            return Ok(ByteStream::from_static(empty));
+        }
+        let path = self.build_path();
+        let mut opts = self.build_options();
+        opts.headers.push(("Accept".to_string(), accept.to_string()));
+        let resp = self
+            .client
+            .request_streaming(&path, self.op.method(), &opts)
+            .await?;
+        Ok(ByteStream::from_response(resp))
+    }
+}
+
+impl<R: Row, O> TypedBuilder<R, O> {
+    /// See [`PostgrestBuilder::execute_csv`].
+    pub async fn execute_csv(self) -> Result<ByteStream> {
+        self.inner.execute_csv().await
+    }
+
+    /// See [`PostgrestBuilder::execute_geojson`]. Properties decode as `O`;
+    /// make sure its geometry field (if any) is an `Option`, since PostgREST
+    /// moves that column into `geometry`.
+    pub async fn execute_geojson(self) -> Result<FeatureCollection<O>>
+    where
+        O: DeserializeOwned,
+    {
+        self.inner.returns::<O>().execute_geojson().await
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn feature_collection_decodes_typed_properties() {
+        #[derive(Debug, Deserialize)]
+        struct Place {
+            name: String,
+        }
+
+        let fc: FeatureCollection<Place> = serde_json::from_value(json!({
+            "type": "FeatureCollection",
+            "features": [{
+                "type": "Feature",
+                "geometry": {"type": "Point", "coordinates": [151.2, -33.8]},
+                "properties": {"name": "Sydney"}
+            }, {
+                "type": "Feature",
+                "geometry": {"type": "Polygon", "coordinates": [[[0, 0], [1, 0], [1, 1], [0, 0]]]},
+                "properties": {"name": "Square"}
+            }]
+        }))
+        .unwrap();
+        assert_eq!(fc.features[0].properties.name, "Sydney");
+        assert_eq!(
+            fc.features[0].geometry,
+            Some(Geometry::Point { coordinates: vec![151.2, -33.8] })
+        );
+        assert!(matches!(fc.features[1].geometry, Some(Geometry::Polygon { .. })));
+    }
+
+    #[test]
+    fn feature_collection_serializes_type_tags() {
+        let fc = FeatureCollection {
+            features: vec![Feature {
+                id: None,
+                geometry: Some(Geometry::Point { coordinates: vec![1.0, 2.0] }),
+                properties: json!({"name": "a"}),
+            }],
+        };
+        assert_eq!(
+            serde_json::to_value(&fc).unwrap(),
+            json!({
+                "type": "FeatureCollection",
+                "features": [{
+                    "type": "Feature",
+                    "geometry": {"type": "Point", "coordinates": [1.0, 2.0]},
+                    "properties": {"name": "a"}
+                }]
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn csv_rejects_write_operations() {
+        let client = crate::SupabaseClient::new("https://x.supabase.co", "anon", None);
+        let err = client.from("t").delete().execute_csv().await.unwrap_err();
+        assert!(matches!(err, SupabaseError::Unexpected(_)), "{err:?}");
+    }
+}
@@ -0,0 +1,254 @@
+//! Typed paths into `json` / `jsonb` columns.
+//!
+//! [`JsonPath`] renders PostgREST's arrow syntax — `->` keeps a JSON value,
+//! `->>` extracts text — from a codegen'd JSON column:
+//!
+//! ```no_run
+//! # use rust_supabase_sdk::{SupabaseClient, Row, postgrest::{Column, Filter}};
+//! # use serde::{Serialize, Deserialize};
+//! # #[derive(Debug, Clone, Serialize, Deserialize)]
+//! # struct Posts { id: String, metadata: Option<serde_json::Value> }
+//! # impl Row for Posts { const TABLE: &'static str = "posts"; }
+//! # #[allow(non_upper_case_globals)]
+//! # impl Posts {
+//! #     pub const metadata: Column<Posts, Option<serde_json::Value>> = Column::new("metadata");
+//! # }
+//! # async fn demo(client: SupabaseClient) -> rust_supabase_sdk::Result<()> {
+//! let theme = Posts::metadata.path("settings").text("theme");
+//! let views = Posts::metadata.path("stats").path("views").typed::<i64>();
+//!
+//! // select=*,metadata->settings->>theme
+//! //   &metadata->settings->>theme=eq.dark&metadata->stats->views=gt.100
+//! //   &order=metadata->stats->views.desc
+//! let rows = client
+//!     .from_row::<Posts>()
+//!     .select_json(theme.clone())
+//!     .where_(Filter::eq(theme, "dark".to_string()) & Filter::gt(views.clone(), 100))
+//!     .order(views, false)
+//!     .returns::<serde_json::Value>()
+//!     .await?;
+//! # Ok(()) }
+//! ```
+//!
+//! A path carries the type of the value at its end — `String` after
+//! `text`, `serde_json::Value` after `path` — so [`Filter`] constructors
+//! still check the compared value.
+//!
+//! [`Filter`]: crate::postgrest::Filter
+
+use std::fmt;
+use std::marker::PhantomData;
+
+use serde_json::Value;
+
+use super::column::{Column, IntoColumnName};
+use super::logic::{FilterColumn, NullableColumn};
+use super::row::Row;
+use super::typed_builder::TypedBuilder;
+
+/// Marker for column value types that hold JSON, enabling
+/// [`Column::path`] / [`Column::text`]. Implemented for
+/// `serde_json::Value` and `Option` of it (what codegen emits for `json` /
+/// `jsonb` columns).
+pub trait Json {}
+
+impl Json for Value {}
+impl<T: Json> Json for Option<T> {}
+
+/// A path into a JSON column of row type `R`, ending in a value of type `V`.
+///
+/// Start one with [`Column::path`] or [`Column::text`]. Usable wherever the
+/// typed builder takes a column name ([`order`](TypedBuilder::order),
+/// [`group_by`](TypedBuilder::group_by), …), in
+/// [`select_json`](TypedBuilder::select_json), and in
+/// [`Filter`](crate::postgrest::Filter) constructors.
+pub struct JsonPath<R, V = Value> {
+    path: String,
+    _phantom: PhantomData<fn(R) -> V>,
+}
+
+impl<R, V> JsonPath<R, V> {
+    fn new(path: String) -> Self {
+        Self {
+            path,
+            _phantom: PhantomData,
+        }
+    }
+
+    /// The rendered path, e.g. `metadata->settings->>theme`.
+    pub fn as_str(&self) -> &str {
+        &self.path
+    }
+}
+
+impl<R> JsonPath<R, Value> {
+    /// Step into object key `key` (`->key`), staying JSON.
+    pub fn path(self, key: &str) -> Self {
+        Self::new(format!("{}->{key}", self.path))
+    }
+
+    /// Step into array element `index` (`->index`), staying JSON.
+    pub fn index(self, index: i64) -> Self {
+        Self::new(format!("{}->{index}", self.path))
+    }
+
+    /// Extract object key `key` as text (`->>key`).
+    pub fn text(self, key: &str) -> JsonPath<R, String> {
+        JsonPath::new(format!("{}->>{key}", self.path))
+    }
+
+    /// Compare the JSON value at this path as a `T` — e.g. a number, so
+    /// `gt` orders numerically rather than as text. Use [`text`](Self::text)
+    /// for string leaves.
+    pub fn typed<T>(self) -> JsonPath<R, T> {
+        JsonPath::new(self.path)
+    }
+}
+
+impl<R, V: Json> Column<R, V> {
+    /// Start a JSON path at object key `key` (`column->key`).
+    pub fn path(self, key: &str) -> JsonPath<R, Value> {
+        JsonPath::new(format!("{}->{key}", self.name()))
+    }
+
+    /// Extract object key `key` of this column as text (`column->>key`).
+    pub fn text(self, key: &str) -> JsonPath<R, String> {
+        JsonPath::new(format!("{}->>{key}", self.name()))
+    }
+}
+
+impl<R, V> Clone for JsonPath<R, V> {
+    fn clone(&self) -> Self {
+        Self::new(self.path.clone())
+    }
+}
+
+impl<R, V> fmt::Debug for JsonPath<R, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("JsonPath").field(&self.path).finish()
+    }
+}
+
+impl<R, V> fmt::Display for JsonPath<R, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.path)
+    }
+}
+
+impl<R, V> IntoColumnName<R> for JsonPath<R, V> {
+    fn into_column_name(self) -> String {
+        self.path
+    }
+}
+
+impl<R, V> FilterColumn<R, V> for JsonPath<R, V> {
+    fn column_name(self) -> String {
+        self.path
+    }
+}
+
+// A missing key reads as null, so every JSON leaf is nullable.
+impl<R, V> NullableColumn<R> for JsonPath<R, V> {
+    fn column_name(self) -> String {
+        self.path
+    }
+}
+
+impl<R: Row, O> TypedBuilder<R, O> {
+    /// Add a JSON path to the select list. PostgREST names the result after
+    /// the last key (`theme` for `metadata->settings->>theme`).
+    pub fn select_json<V>(self, path: JsonPath<R, V>) -> Self {
+        self.push_select(path.path)
+    }
+
+    /// Add a JSON path to the select list under `alias`.
+    pub fn select_json_as<V>(self, alias: &str, path: JsonPath<R, V>) -> Self {
+        self.push_select(format!("{alias}:{}", path.path))
+    }
+
+    fn push_select(mut self, item: String) -> Self {
+        match &mut self.inner.state.select_cols {
+            Some(cols) if !cols.is_empty() => {
+                cols.push(',');
+                cols.push_str(&item);
+            }
+            slot => *slot = Some(item),
+        }
+        self
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+    use crate::postgrest::Filter;
+    use crate::SupabaseClient;
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    struct Posts {
+        id: String,
+        data: Value,
+        metadata: Option<Value>,
+    }
+
+    impl Row for Posts {
+        const TABLE: &'static str = "posts";
+    }
+
+    #[allow(non_upper_case_globals)]
+    impl Posts {
+        const id: Column<Posts, String> = Column::new("id");
+        const data: Column<Posts, Value> = Column::new("data");
+        const metadata: Column<Posts, Option<Value>> = Column::new("metadata");
+    }
+
+    fn client() -> SupabaseClient {
+        SupabaseClient::new("https://x.supabase.co", "anon", None)
+    }
+
+    fn decode(s: &str) -> String {
+        urlencoding::decode(s).unwrap().into_owned()
+    }
+
+    #[test]
+    fn paths_render_arrow_syntax() {
+        assert_eq!(Posts::data.path("a").path("b").as_str(), "data->a->b");
+        assert_eq!(Posts::metadata.path("tags").index(0).as_str(), "metadata->tags->0");
+        assert_eq!(Posts::data.path("settings").text("theme").as_str(), "data->settings->>theme");
+        assert_eq!(Posts::metadata.text("status").to_string(), "metadata->>status");
+    }
+
+    #[test]
+    fn select_json_appends_to_the_projection() {
+        let p = client()
+            .from_row::<Posts>()
+            .select("id")
+            .select_json(Posts::data.path("settings").text("theme"))
+            .select_json_as("views", Posts::data.path("stats").path("views"))
+            .build_path();
+        assert_eq!(
+            decode(&p),
+            "/rest/v1/posts?select=id,data->settings->>theme,views:data->stats->views"
+        );
+    }
+
+    #[test]
+    fn json_paths_filter_and_order() {
+        let status = Posts::data.text("status");
+        let views = Posts::data.path("views").typed::<i64>();
+        let p = client()
+            .from_row::<Posts>()
+            .where_(Filter::eq(status, "active".to_string()))
+            .where_(Filter::gt(views.clone(), 10) | Filter::is_null(Posts::metadata.path("pin")))
+            .order(views, false)
+            .order(Posts::id, true)
+            .build_path();
+        assert_eq!(
+            decode(&p),
+            "/rest/v1/posts?select=*&data->>status=eq.active\
+             &or=(data->views.gt.10,metadata->pin.is.null)&order=data->views.desc,id.asc"
+        );
+    }
+}
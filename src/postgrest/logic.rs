@@ -0,0 +1,553 @@
+//! Composable filter expressions — PostgREST `and` / `or` / `not` trees.
+//!
+//! [`Filter`] builds logic trees with `&`, `|` and `!` instead of
+//! hand-written `or=(...)` strings, and quotes values that contain
+//! PostgREST's reserved characters:
+//!
+//! ```no_run
+//! # use rust_supabase_sdk::{SupabaseClient, postgrest::Filter};
+//! # async fn demo(client: &SupabaseClient) -> rust_supabase_sdk::Result<()> {
+//! // or=(and(status.eq.published,views.gt.100),pinned_at.not.is.null)
+//! let rows: Vec<serde_json::Value> = client
+//!     .from("posts")
+//!     .select("*")
+//!     .where_(
+//!         (Filter::eq("status", "published") & Filter::gt("views", 100))
+//!             | !Filter::is_null("pinned_at"),
+//!     )
+//!     .await?;
+//! # Ok(()) }
+//! ```
+//!
+//! With codegen'd [`Column<R, V>`] handles the same constructors produce a
+//! `Filter<R>`: values are checked against the column type, and a filter
+//! over one table can't be combined with, or applied to, another.
+
+use std::fmt;
+use std::marker::PhantomData;
+use std::ops::{BitAnd, BitOr, Not};
+
+use super::builder::PostgrestBuilder;
+use super::column::Column;
+use super::row::Row;
+use super::typed_builder::TypedBuilder;
+use super::value::{encode_column, encode_value, render_list, PostgrestValue};
+
+/// A column a [`Filter<R>`] can compare against values of type `V`.
+///
+/// Typed [`Column<R, V>`] handles only accept their declared value type;
+/// string column names build an untyped `Filter` (`R = ()`) that accepts
+/// any [`PostgrestValue`].
+pub trait FilterColumn<R, V> {
+    fn column_name(self) -> String;
+}
+
+impl<R, V> FilterColumn<R, V> for Column<R, V> {
+    fn column_name(self) -> String {
+        self.name().to_string()
+    }
+}
+
+impl<V> FilterColumn<(), V> for &str {
+    fn column_name(self) -> String {
+        self.to_string()
+    }
+}
+
+impl<V> FilterColumn<(), V> for String {
+    fn column_name(self) -> String {
+        self
+    }
+}
+
+/// A column [`Filter::is_null`] accepts: a typed `Column<R, Option<_>>`, or
+/// any column name for an untyped filter.
+pub trait NullableColumn<R> {
+    fn column_name(self) -> String;
+}
+
+impl<R, V> NullableColumn<R> for Column<R, Option<V>> {
+    fn column_name(self) -> String {
+        self.name().to_string()
+    }
+}
+
+impl NullableColumn<()> for &str {
+    fn column_name(self) -> String {
+        self.to_string()
+    }
+}
+
+impl NullableColumn<()> for String {
+    fn column_name(self) -> String {
+        self
+    }
+}
+
+/// A filter expression over rows of type `R` (`()` for string column names).
+///
+/// Combine with `&` (and), `|` (or) and `!` (not), then apply with
+/// [`PostgrestBuilder::where_`], [`TypedBuilder::where_`] or
+/// [`EmbedScope::where_`](crate::postgrest::EmbedScope::where_).
+pub struct Filter<R = ()> {
+    node: Node,
+    _row: PhantomData<fn() -> R>,
+}
+
+#[derive(Debug, Clone)]
+enum Node {
+    Leaf {
+        column: String,
+        op: String,
+        operand: Operand,
+        negated: bool,
+    },
+    Group {
+        any: bool,
+        negated: bool,
+        children: Vec<Node>,
+    },
+}
+
+#[derive(Debug, Clone)]
+enum Operand {
+    /// A single value — quoted inside a logic tree when it contains
+    /// reserved characters.
+    Scalar(String),
+    /// Already in PostgREST syntax (`(a,b)`, `null`); never quoted.
+    Verbatim(String),
+}
+
+impl<R> Filter<R> {
+    fn leaf(column: String, op: &str, operand: Operand) -> Self {
+        Self::wrap(Node::Leaf {
+            column,
+            op: op.to_string(),
+            operand,
+            negated: false,
+        })
+    }
+
+    fn wrap(node: Node) -> Self {
+        Self {
+            node,
+            _row: PhantomData,
+        }
+    }
+
+    fn scalar<C, V>(col: C, op: &str, value: V) -> Self
+    where
+        C: FilterColumn<R, V>,
+        V: PostgrestValue,
+    {
+        let rendered = value.render();
+        Self::leaf(col.column_name(), op, Operand::Scalar(rendered))
+    }
+
+    /// `column = value`
+    pub fn eq<C, V>(col: C, value: V) -> Self
+    where
+        C: FilterColumn<R, V>,
+        V: PostgrestValue,
+    {
+        Self::scalar(col, "eq", value)
+    }
+
+    /// `column <> value`
+    pub fn neq<C, V>(col: C, value: V) -> Self
+    where
+        C: FilterColumn<R, V>,
+        V: PostgrestValue,
+    {
+        Self::scalar(col, "neq", value)
+    }
+
+    /// `column > value`
+    pub fn gt<C, V>(col: C, value: V) -> Self
+    where
+        C: FilterColumn<R, V>,
+        V: PostgrestValue,
+    {
+        Self::scalar(col, "gt", value)
+    }
+
+    /// `column >= value`
+    pub fn gte<C, V>(col: C, value: V) -> Self
+    where
+        C: FilterColumn<R, V>,
+        V: PostgrestValue,
+    {
+        Self::scalar(col, "gte", value)
+    }
+
+    /// `column < value`
+    pub fn lt<C, V>(col: C, value: V) -> Self
+    where
+        C: FilterColumn<R, V>,
+        V: PostgrestValue,
+    {
+        Self::scalar(col, "lt", value)
+    }
+
+    /// `column <= value`
+    pub fn lte<C, V>(col: C, value: V) -> Self
+    where
+        C: FilterColumn<R, V>,
+        V: PostgrestValue,
+    {
+        Self::scalar(col, "lte", value)
+    }
+
+    /// SQL `LIKE`. Typed filters only accept string columns.
+    pub fn like<C>(col: C, pattern: impl AsRef<str>) -> Self
+    where
+        C: FilterColumn<R, String>,
+    {
+        Self::scalar(col, "like", pattern.as_ref().to_string())
+    }
+
+    /// SQL `ILIKE`. Typed filters only accept string columns.
+    pub fn ilike<C>(col: C, pattern: impl AsRef<str>) -> Self
+    where
+        C: FilterColumn<R, String>,
+    {
+        Self::scalar(col, "ilike", pattern.as_ref().to_string())
+    }
+
+    /// `column IS NULL`. Negate with `!` for `IS NOT NULL`.
+    pub fn is_null<C: NullableColumn<R>>(col: C) -> Self {
+        Self::leaf(col.column_name(), "is", Operand::Verbatim("null".into()))
+    }
+
+    /// `column = ANY (values)`.
+    pub fn in_<C, V, I>(col: C, values: I) -> Self
+    where
+        C: FilterColumn<R, V>,
+        V: PostgrestValue,
+        I: IntoIterator<Item = V>,
+    {
+        Self::leaf(col.column_name(), "in", Operand::Verbatim(render_list(values)))
+    }
+
+    /// Any other operator (`cs`, `fts`, `match`, …): `column.op.value`.
+    pub fn op<C, V>(col: C, op: &str, value: V) -> Self
+    where
+        C: FilterColumn<R, V>,
+        V: PostgrestValue,
+    {
+        Self::scalar(col, op, value)
+    }
+
+    /// Push this filter onto `builder` as one query param, keyed under
+    /// `prefix.` (an embedded resource) when given.
+    pub(crate) fn apply<T>(self, builder: &mut PostgrestBuilder<T>, prefix: Option<&str>) {
+        let (key, value) = self.node.to_param();
+        let key = match prefix {
+            Some(prefix) => format!("{prefix}.{key}"),
+            None => key,
+        };
+        builder.push_param(encode_column(&key), encode_value(&value));
+    }
+}
+
+impl Node {
+    fn negate(self) -> Self {
+        match self {
+            Node::Leaf {
+                column,
+                op,
+                operand,
+                negated,
+            } => Node::Leaf {
+                column,
+                op,
+                operand,
+                negated: !negated,
+            },
+            Node::Group {
+                any,
+                negated,
+                children,
+            } => Node::Group {
+                any,
+                negated: !negated,
+                children,
+            },
+        }
+    }
+
+    /// Join two nodes under `and` / `or`, flattening un-negated groups of
+    /// the same kind so `a & b & c` renders as `and(a,b,c)`.
+    fn combine(any: bool, lhs: Node, rhs: Node) -> Node {
+        let mut children = Vec::new();
+        for node in [lhs, rhs] {
+            match node {
+                Node::Group {
+                    any: kind,
+                    negated: false,
+                    children: inner,
+                } if kind == any => children.extend(inner),
+                other => children.push(other),
+            }
+        }
+        Node::Group {
+            any,
+            negated: false,
+            children,
+        }
+    }
+
+    /// Top-level form: `(column, [not.]op.value)` or `([not.]or, (…))`.
+    fn to_param(&self) -> (String, String) {
+        match self {
+            Node::Leaf {
+                column,
+                op,
+                operand,
+                negated,
+            } => {
+                let value = match operand {
+                    Operand::Scalar(v) | Operand::Verbatim(v) => v,
+                };
+                (column.clone(), format!("{}{op}.{value}", not_prefix(*negated)))
+            }
+            Node::Group {
+                any,
+                negated,
+                children,
+            } => (
+                format!("{}{}", not_prefix(*negated), group_name(*any)),
+                format!("({})", render_children(children)),
+            ),
+        }
+    }
+
+    /// Form inside a logic tree: `column.[not.]op.value` or `[not.]or(…)`.
+    fn render_nested(&self) -> String {
+        match self {
+            Node::Leaf {
+                column,
+                op,
+                operand,
+                negated,
+            } => {
+                let value = match operand {
+                    Operand::Scalar(v) => quote(v),
+                    Operand::Verbatim(v) => v.clone(),
+                };
+                format!("{column}.{}{op}.{value}", not_prefix(*negated))
+            }
+            Node::Group {
+                any,
+                negated,
+                children,
+            } => format!(
+                "{}{}({})",
+                not_prefix(*negated),
+                group_name(*any),
+                render_children(children)
+            ),
+        }
+    }
+}
+
+fn not_prefix(negated: bool) -> &'static str {
+    if negated {
+        "not."
+    } else {
+        ""
+    }
+}
+
+fn group_name(any: bool) -> &'static str {
+    if any {
+        "or"
+    } else {
+        "and"
+    }
+}
+
+fn render_children(children: &[Node]) -> String {
+    children
+        .iter()
+        .map(Node::render_nested)
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+/// Double-quote a value inside a logic tree if it contains characters
+/// PostgREST would read as syntax, escaping `\` and `"`.
+fn quote(value: &str) -> String {
+    if value.contains([',', '.', ':', '(', ')', '"', '\\']) {
+        format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+    } else {
+        value.to_string()
+    }
+}
+
+impl<R> BitAnd for Filter<R> {
+    type Output = Self;
+
+    fn bitand(self, rhs: Self) -> Self {
+        Self::wrap(Node::combine(false, self.node, rhs.node))
+    }
+}
+
+impl<R> BitOr for Filter<R> {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self {
+        Self::wrap(Node::combine(true, self.node, rhs.node))
+    }
+}
+
+impl<R> Not for Filter<R> {
+    type Output = Self;
+
+    fn not(self) -> Self {
+        Self::wrap(self.node.negate())
+    }
+}
+
+impl<R> Clone for Filter<R> {
+    fn clone(&self) -> Self {
+        Self::wrap(self.node.clone())
+    }
+}
+
+impl<R> fmt::Debug for Filter<R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Filter").field(&self.node.render_nested()).finish()
+    }
+}
+
+impl<T> PostgrestBuilder<T> {
+    /// Apply a [`Filter`] expression. A single comparison renders like the
+    /// plain filter methods; `&` / `|` trees render as `and=(…)` / `or=(…)`.
+    pub fn where_(mut self, filter: Filter) -> Self {
+        filter.apply(&mut self, None);
+        self
+    }
+
+    /// [`where_`](Self::where_) scoped to an embedded resource, e.g.
+    /// `author.or=(…)` for `foreign_table = "author"`.
+    pub fn where_on(mut self, foreign_table: &str, filter: Filter) -> Self {
+        filter.apply(&mut self, Some(foreign_table));
+        self
+    }
+}
+
+impl<R: Row, O> TypedBuilder<R, O> {
+    /// Apply a typed [`Filter<R>`] expression built from `R`'s columns.
+    pub fn where_(mut self, filter: Filter<R>) -> Self {
+        filter.apply(&mut self.inner, None);
+        self
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+    use crate::SupabaseClient;
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    struct Posts {
+        status: String,
+        views: i32,
+        archived_at: Option<String>,
+    }
+
+    impl Row for Posts {
+        const TABLE: &'static str = "posts";
+    }
+
+    #[allow(non_upper_case_globals)]
+    impl Posts {
+        const status: Column<Posts, String> = Column::new("status");
+        const views: Column<Posts, i32> = Column::new("views");
+        const archived_at: Column<Posts, Option<String>> = Column::new("archived_at");
+    }
+
+    fn client() -> SupabaseClient {
+        SupabaseClient::new("https://x.supabase.co", "anon", None)
+    }
+
+    fn query(filter: Filter) -> String {
+        let p = client().from("posts").select("*").where_(filter).build_path();
+        urlencoding::decode(&p).unwrap().into_owned()
+    }
+
+    #[test]
+    fn nested_trees_render_postgrest_logic_syntax() {
+        let f = (Filter::eq("a", 1) & Filter::gt("b", 2)) | Filter::is_null("c");
+        assert_eq!(query(f), "/rest/v1/posts?select=*&or=(and(a.eq.1,b.gt.2),c.is.null)");
+    }
+
+    #[test]
+    fn chains_of_the_same_operator_flatten() {
+        let f = Filter::eq("a", 1) | Filter::eq("b", 2) | Filter::eq("c", 3);
+        assert_eq!(query(f), "/rest/v1/posts?select=*&or=(a.eq.1,b.eq.2,c.eq.3)");
+        let f = Filter::eq("a", 1) & (Filter::eq("b", 2) & Filter::eq("c", 3));
+        assert_eq!(query(f), "/rest/v1/posts?select=*&and=(a.eq.1,b.eq.2,c.eq.3)");
+    }
+
+    #[test]
+    fn negation_prefixes_leaves_and_groups() {
+        assert_eq!(query(!Filter::is_null("c")), "/rest/v1/posts?select=*&c=not.is.null");
+        let f = !(Filter::eq("a", 1) | Filter::lt("b", 0)) & !Filter::eq("d", "x");
+        assert_eq!(query(f), "/rest/v1/posts?select=*&and=(not.or(a.eq.1,b.lt.0),d.not.eq.x)");
+        assert_eq!(query(!!Filter::eq("a", 1)), "/rest/v1/posts?select=*&a=eq.1");
+    }
+
+    #[test]
+    fn reserved_characters_are_quoted_inside_trees() {
+        let f = Filter::eq("name", "Smith, J. (Jr)") | Filter::eq("note", r#"say "hi" \o/"#);
+        assert_eq!(
+            query(f),
+            r#"/rest/v1/posts?select=*&or=(name.eq."Smith, J. (Jr)",note.eq."say \"hi\" \\o/")"#
+        );
+        // A lone comparison is a plain param, so nothing needs quoting.
+        assert_eq!(query(Filter::eq("name", "a,b")), "/rest/v1/posts?select=*&name=eq.a,b");
+    }
+
+    #[test]
+    fn in_lists_keep_their_own_syntax() {
+        let f = Filter::in_("id", ["a", "b,c"]) | Filter::like("title", "*rust*");
+        assert_eq!(
+            query(f),
+            r#"/rest/v1/posts?select=*&or=(id.in.(a,"b,c"),title.like.*rust*)"#
+        );
+    }
+
+    #[test]
+    fn where_on_prefixes_the_embedded_resource() {
+        let p = client()
+            .from("posts")
+            .select("*,author(*)")
+            .where_on("author", Filter::eq("name", "ann") | Filter::eq("name", "bo"))
+            .build_path();
+        assert_eq!(
+            urlencoding::decode(&p).unwrap(),
+            "/rest/v1/posts?select=*,author(*)&author.or=(name.eq.ann,name.eq.bo)"
+        );
+    }
+
+    #[test]
+    fn typed_filters_use_columns() {
+        let f = (Filter::eq(Posts::status, "draft".to_string()) & Filter::gte(Posts::views, 10))
+            | Filter::is_null(Posts::archived_at);
+        let p = client().from_row::<Posts>().where_(f).build_path();
+        assert_eq!(
+            urlencoding::decode(&p).unwrap(),
+            "/rest/v1/posts?select=*&or=(and(status.eq.draft,views.gte.10),archived_at.is.null)"
+        );
+    }
+
+    #[test]
+    fn debug_shows_the_rendered_tree() {
+        let f = Filter::eq("a", 1) | !Filter::eq("b", 2);
+        assert_eq!(format!("{f:?}"), r#"Filter("or(a.eq.1,b.not.eq.2)")"#);
+    }
+}
@@ -0,0 +1,249 @@
+//! Primary-key lookups for [`Keyed`] rows.
+//!
+//! ```no_run
+//! # use rust_supabase_sdk::{SupabaseClient, Row, postgrest::Keyed};
+//! # use serde::{Serialize, Deserialize};
+//! # #[derive(Debug, Clone, Serialize, Deserialize)]
+//! # struct Memberships { org_id: i64, user_id: String, role: String }
+//! # impl Row for Memberships {
+//! #     const TABLE: &'static str = "memberships";
+//! #     const PRIMARY_KEY: &'static [&'static str] = &["org_id", "user_id"];
+//! # }
+//! # impl Keyed for Memberships { type PrimaryKey = (i64, String); }
+//! # async fn demo(client: SupabaseClient) -> rust_supabase_sdk::Result<()> {
+//! // org_id=eq.1&user_id=eq.u1
+//! let one: Option<Memberships> = client.find::<Memberships>((1, "u1".into())).await?;
+//!
+//! // or=(and(org_id.eq.1,user_id.eq.u1),and(org_id.eq.2,user_id.eq.u2))
+//! let many: Vec<Memberships> = client
+//!     .find_many::<Memberships>([(1, "u1".into()), (2, "u2".into())])
+//!     .await?;
+//! # Ok(()) }
+//! ```
+//!
+//! Single-column keys render `find_many` as `column=in.(…)`, and an empty
+//! key list returns no rows without a request, like
+//! [`is_in`](super::PostgrestBuilder::is_in).
+
+use serde_json::Value;
+
+use crate::SupabaseClient;
+
+use super::builder::{MaybeSingleBuilder, PostgrestBuilder};
+use super::logic::Filter;
+use super::row::{Keyed, Writable};
+use super::typed_builder::TypedBuilder;
+
+impl SupabaseClient {
+    /// Fetch the row with primary key `pk`, or `None`.
+    pub fn find<R: Keyed>(&self, pk: R::PrimaryKey) -> MaybeSingleBuilder<R> {
+        let mut q = self.from_row::<R>();
+        q.inner = with_key::<R>(q.inner, &pk);
+        q.maybe_single()
+    }
+
+    /// Fetch the rows whose primary key is one of `pks`. Returns a typed
+    /// builder, so ordering and limits still apply.
+    pub fn find_many<R: Keyed>(
+        &self,
+        pks: impl IntoIterator<Item = R::PrimaryKey>,
+    ) -> TypedBuilder<R> {
+        let mut q = self.from_row::<R>();
+        let keys: Result<Vec<Vec<(&'static str, String)>>, String> =
+            pks.into_iter().map(|pk| key_columns::<R>(&pk)).collect();
+        let keys = match keys {
+            Ok(keys) => keys,
+            Err(e) => {
+                q.inner.state.build_error = Some(e);
+                return q;
+            }
+        };
+        q.inner = match R::PRIMARY_KEY {
+            [column] => q.inner.is_in(column, keys.into_iter().flatten().map(|(_, v)| v)),
+            _ => match keys.into_iter().filter_map(all_of).reduce(|a, b| a | b) {
+                Some(filter) => q.inner.where_(filter),
+                None => {
+                    q.inner.state.short_circuit_empty_result = true;
+                    q.inner
+                }
+            },
+        };
+        q
+    }
+
+    /// Write `patch` to the row with primary key `pk` and return it as
+    /// stored, or `None` when no row has that key.
+    pub fn update_by_pk<R>(&self, pk: R::PrimaryKey, patch: R::Update) -> MaybeSingleBuilder<R>
+    where
+        R: Keyed + Writable,
+    {
+        let mut q = self.from_row::<R>().update(patch);
+        q.inner = with_key::<R>(q.inner, &pk);
+        q.maybe_single()
+    }
+
+    /// Delete the row with primary key `pk` and return it, or `None` when no
+    /// row has that key.
+    pub fn delete_by_pk<R: Keyed>(&self, pk: R::PrimaryKey) -> MaybeSingleBuilder<R> {
+        let mut q = self.from_row::<R>().delete();
+        q.inner = with_key::<R>(q.inner, &pk);
+        q.maybe_single()
+    }
+}
+
+/// `column=eq.value` for every key column, or the reason `pk` can't be
+/// rendered as a build-time error.
+fn with_key<R: Keyed>(mut q: PostgrestBuilder<Value>, pk: &R::PrimaryKey) -> PostgrestBuilder<Value> {
+    match key_columns::<R>(pk) {
+        Ok(columns) => {
+            for (column, value) in columns {
+                q = q.eq(column, value);
+            }
+        }
+        Err(e) => q.state.build_error = Some(e),
+    }
+    q
+}
+
+fn all_of(columns: Vec<(&'static str, String)>) -> Option<Filter> {
+    columns
+        .into_iter()
+        .map(|(column, value)| Filter::eq(column, value))
+        .reduce(|a, b| a & b)
+}
+
+/// Pair each [`PRIMARY_KEY`](super::Row::PRIMARY_KEY) column with its value
+/// in `pk`, rendered as filter text.
+fn key_columns<R: Keyed>(pk: &R::PrimaryKey) -> Result<Vec<(&'static str, String)>, String> {
+    let columns = R::PRIMARY_KEY;
+    if columns.is_empty() {
+        return Err(format!("{} declares no PRIMARY_KEY", R::TABLE));
+    }
+    let value = serde_json::to_value(pk).map_err(|e| format!("{} primary key: {e}", R::TABLE))?;
+    let values = match value {
+        Value::Array(values) if columns.len() > 1 => values,
+        value => vec![value],
+    };
+    if values.len() != columns.len() {
+        return Err(format!(
+            "{} primary key has {} columns, got {} values",
+            R::TABLE,
+            columns.len(),
+            values.len()
+        ));
+    }
+    columns
+        .iter()
+        .zip(values)
+        .map(|(column, value)| match value {
+            Value::String(s) => Ok((*column, s)),
+            Value::Number(_) | Value::Bool(_) => Ok((*column, value.to_string())),
+            other => Err(format!("{} primary key `{column}` can't be {other}", R::TABLE)),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+    use crate::postgrest::Row;
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    struct Posts {
+        id: i64,
+        title: String,
+    }
+
+    impl Row for Posts {
+        const TABLE: &'static str = "posts";
+        const PRIMARY_KEY: &'static [&'static str] = &["id"];
+    }
+
+    impl Keyed for Posts {
+        type PrimaryKey = i64;
+    }
+
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    struct Memberships {
+        org_id: i64,
+        user_id: String,
+    }
+
+    impl Row for Memberships {
+        const TABLE: &'static str = "memberships";
+        const PRIMARY_KEY: &'static [&'static str] = &["org_id", "user_id"];
+    }
+
+    impl Keyed for Memberships {
+        type PrimaryKey = (i64, String);
+    }
+
+    fn client() -> SupabaseClient {
+        SupabaseClient::new("https://x.supabase.co", "anon", None)
+    }
+
+    fn decode(s: &str) -> String {
+        urlencoding::decode(s).unwrap().into_owned()
+    }
+
+    #[test]
+    fn single_column_keys_render_eq_and_in() {
+        let q = with_key::<Posts>(client().from("posts").select("*"), &7);
+        assert_eq!(decode(&q.build_path()), "/rest/v1/posts?select=*&id=eq.7");
+        let q = client().find_many::<Posts>([1, 2, 3]);
+        assert_eq!(decode(&q.build_path()), "/rest/v1/posts?select=*&id=in.(1,2,3)");
+        let q = client().find_many::<Posts>([]);
+        assert!(q.inner.state.short_circuit_empty_result);
+    }
+
+    #[test]
+    fn composite_keys_render_one_group_per_key() {
+        let q = with_key::<Memberships>(
+            client().from("memberships").select("*"),
+            &(1, "a,b".to_string()),
+        );
+        assert_eq!(
+            decode(&q.build_path()),
+            "/rest/v1/memberships?select=*&org_id=eq.1&user_id=eq.a,b"
+        );
+        let q = client().find_many::<Memberships>([(1, "u1".into()), (2, "a,b".into())]);
+        assert_eq!(
+            decode(&q.build_path()),
+            "/rest/v1/memberships?select=*\
+             &or=(and(org_id.eq.1,user_id.eq.u1),and(org_id.eq.2,user_id.eq.\"a,b\"))"
+        );
+        let q = client().find_many::<Memberships>([]);
+        assert!(q.inner.state.short_circuit_empty_result);
+    }
+
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    struct Mislabeled {
+        id: Option<i64>,
+    }
+
+    impl Row for Mislabeled {
+        const TABLE: &'static str = "mislabeled";
+        const PRIMARY_KEY: &'static [&'static str] = &["a", "b"];
+    }
+
+    impl Keyed for Mislabeled {
+        type PrimaryKey = Option<i64>;
+    }
+
+    #[test]
+    fn unrenderable_keys_fail_at_execution() {
+        let q = client().find_many::<Mislabeled>([Some(1)]);
+        assert_eq!(
+            q.inner.state.build_error.as_deref(),
+            Some("mislabeled primary key has 2 columns, got 1 values")
+        );
+        let q = client().find::<Mislabeled>(None);
+        assert!(format!("{:?}", q.inner.state.build_error).contains("2 columns"));
+        assert_eq!(
+            key_columns::<Posts>(&0),
+            Ok(vec![("id", "0".to_string())])
+        );
+    }
+}
@@ -16,20 +16,53 @@
 //! # Ok(()) }
 //! ```
 
+mod aggregate;
+mod batch;
 mod builder;
+mod cache;
 mod column;
+mod cursor;
+mod embed;
+mod explain;
 mod filters;
+mod format;
+mod json_path;
+mod logic;
+mod lookup;
+mod paginate;
+mod projection;
 pub mod row;
+mod stream;
+mod tracked;
+mod transaction;
 mod typed_builder;
+mod types;
 mod value;
 
+pub use aggregate::{Agg, AggFn, Aggregated, Numeric};
+pub use batch::{BatchOptions, BatchReport, ChunkFailure};
+pub use cache::{CacheConfig, QueryCache};
 pub use builder::{
     CountMode, MaybeSingleBuilder, Order, PostgrestBuilder, SingleBuilder, TableBuilder,
     TextSearchType,
 };
 pub use column::{Column, IntoColumnName};
-pub use row::Row;
+pub use cursor::{Cursor, CursorBuilder, Page};
+pub use embed::{Cardinality, EmbedScope, Embeddable, Related, Relation};
+pub use explain::{Explain, ExplainFormat, ExplainOptions, PlanNode, QueryPlan};
+pub use format::{ByteStream, Feature, FeatureCollection, Geometry, Position};
+pub use json_path::{Json, JsonPath};
+pub use logic::{Filter, FilterColumn, NullableColumn};
+pub use paginate::Paginate;
+pub use projection::{ProjectedField, Projection};
+pub use row::{Keyed, Row, Writable};
+pub use stream::{RowStream, DEFAULT_MAX_ROW_BYTES};
+pub use tracked::Tracked;
+pub use transaction::{
+    StatementResult, Transaction, DEFAULT_TRANSACTION_FUNCTION, TRANSACTION_SQL,
+};
 pub use typed_builder::TypedBuilder;
+pub use types::{ArrayType, PgRange, RangeType, TextSearchable, TsVector};
 pub use value::PostgrestValue;
 
 use crate::SupabaseClient;
@@ -0,0 +1,516 @@
+//! Lazy pagination over a PostgREST query.
+//!
+//! [`PostgrestBuilder::paginate`] / [`PostgrestBuilder::paginate_keyset`] turn
+//! a configured query into a [`Paginate`] stream that fetches one page at a
+//! time and yields individual rows:
+//!
+//! ```no_run
+//! # use rust_supabase_sdk::SupabaseClient;
+//! # use rust_supabase_sdk::postgrest::CountMode;
+//! use futures_util::StreamExt;
+//!
+//! # async fn demo(client: &SupabaseClient) -> rust_supabase_sdk::Result<()> {
+//! let mut rows = client
+//!     .from("events")
+//!     .select("*")
+//!     .eq("kind", "signup")
+//!     .count(CountMode::Exact)
+//!     .paginate_keyset(500, "id");
+//!
+//! println!("{:?} rows to go", rows.total().await?);
+//! while let Some(row) = rows.next().await {
+//!     let row = row?;
+//!     # let _ = row;
+//! }
+//! # Ok(()) }
+//! ```
+//!
+//! Two paging modes are supported:
+//!
+//!   * **Offset** — each page is `limit=N&offset=K`. Works with any ordering,
+//!     but rows can be skipped or repeated if the table changes mid-scan.
+//!   * **Keyset** — each page after the first adds `column=gt.<last seen>`
+//!     (or `lt.` for a descending order). Stable under concurrent writes and
+//!     O(page) on the server, but requires a unique, non-null sort column.
+
+use std::collections::VecDeque;
+use std::future::Future;
+use std::marker::PhantomData;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use futures_util::Stream;
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+
+use crate::error::{Result, SupabaseError};
+
+use super::builder::PostgrestBuilder;
+use super::value::{encode_column, encode_value};
+
+type PageFuture = Pin<Box<dyn Future<Output = Result<(Option<u64>, Vec<Value>)>> + Send>>;
+
+/// How successive pages are addressed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Mode {
+    Offset,
+    Keyset { column: String, ascending: bool },
+}
+
+/// Stream of rows returned by [`PostgrestBuilder::paginate`] and
+/// [`PostgrestBuilder::paginate_keyset`].
+///
+/// Pages are fetched lazily — nothing hits the wire until the stream is
+/// polled (or [`total`](Paginate::total) is awaited). The stream ends after
+/// the first short page, once any `limit`/`range` cap set on the original
+/// builder is reached, or after the first error.
+#[must_use = "Paginate is lazy — poll it as a Stream"]
+pub struct Paginate<T> {
+    template: PostgrestBuilder<Value>,
+    mode: Mode,
+    page_size: u64,
+    /// Row offset of the next page (offset mode) or of the first page (keyset mode).
+    next_offset: u64,
+    /// Remaining rows allowed by the original `limit`/`range`, if any.
+    remaining: Option<u64>,
+    /// Last value seen in the keyset column, already rendered for the URL.
+    cursor: Option<String>,
+    total: Option<u64>,
+    started: bool,
+    done: bool,
+    buffer: VecDeque<T>,
+    pending: Option<PageFuture>,
+    _marker: PhantomData<fn() -> T>,
+}
+
+// No field is ever pin-projected; the in-flight future is already boxed.
+impl<T> Unpin for Paginate<T> {}
+
+impl<T> std::fmt::Debug for Paginate<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Paginate")
+            .field("table", &self.template.table)
+            .field("mode", &self.mode)
+            .field("page_size", &self.page_size)
+            .field("next_offset", &self.next_offset)
+            .field("cursor", &self.cursor)
+            .field("total", &self.total)
+            .field("done", &self.done)
+            .finish()
+    }
+}
+
+impl<T> PostgrestBuilder<T> {
+    /// Stream every matching row, fetching `page_size` rows per request
+    /// using `limit`/`offset` paging.
+    ///
+    /// Filters, `order` and `count` set on the builder apply to every page.
+    /// An existing `offset`/`range` start is honoured, and an existing
+    /// `limit`/`range` length caps the total number of rows streamed. A
+    /// `page_size` of zero is treated as one.
+    pub fn paginate(self, page_size: u64) -> Paginate<T> {
+        Paginate::new(self, page_size, Mode::Offset)
+    }
+
+    /// Stream every matching row using keyset paging on `column`.
+    ///
+    /// The direction comes from an existing `.order(column, ..)` on the
+    /// builder, which must be the first `order` term; if the query isn't
+    /// ordered yet, `column.asc` is added. Any other ordering fails on the
+    /// first page, since seeking on a secondary sort column would skip or
+    /// repeat rows. `column` must be unique and non-null, and must be present
+    /// in the `select` list so the last value of each page can be read back.
+    pub fn paginate_keyset(mut self, page_size: u64, column: &str) -> Paginate<T> {
+        let order = self
+            .state
+            .params
+            .iter()
+            .find(|(k, _)| k == "order")
+            .map(|(_, order)| order.clone());
+        let ascending = match order {
+            None => {
+                self = self.order(column, true);
+                true
+            }
+            Some(order) => match leading_order_direction(&order, column) {
+                Some(ascending) => ascending,
+                None => {
+                    self.state.build_error = Some(format!(
+                        "paginate_keyset: `{column}` must be the first order term, got `order={order}`"
+                    ));
+                    true
+                }
+            },
+        };
+        let mode = Mode::Keyset {
+            column: column.to_string(),
+            ascending,
+        };
+        Paginate::new(self, page_size, mode)
+    }
+}
+
+/// Direction of `column` when it is the first term of `order`
+/// (`column.asc`, `column.desc.nullslast`, …). Matches on the rendered
+/// prefix, so column names containing `.` or `,` work too.
+fn leading_order_direction(order: &str, column: &str) -> Option<bool> {
+    let rest = order.strip_prefix(column)?.strip_prefix('.')?;
+    [("asc", true), ("desc", false)]
+        .into_iter()
+        .find_map(|(dir, ascending)| {
+            let after = rest.strip_prefix(dir)?;
+            (after.is_empty() || after.starts_with(['.', ','])).then_some(ascending)
+        })
+}
+
+impl<T> Paginate<T> {
+    fn new(q: PostgrestBuilder<T>, page_size: u64, mode: Mode) -> Self {
+        let mut template = q.returns::<Value>();
+        let (next_offset, remaining) = match template.state.range.take() {
+            Some((from, to)) => (from, Some(to.saturating_sub(from).saturating_add(1))),
+            None => (
+                template.state.offset.take().unwrap_or(0),
+                template.state.limit,
+            ),
+        };
+        template.state.limit = None;
+        template.state.offset = None;
+        Self {
+            template,
+            mode,
+            page_size: page_size.max(1),
+            next_offset,
+            remaining,
+            cursor: None,
+            total: None,
+            started: false,
+            done: remaining == Some(0),
+            buffer: VecDeque::new(),
+            pending: None,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Number of rows the next request asks for.
+    fn next_page_len(&self) -> u64 {
+        match self.remaining {
+            Some(remaining) => remaining.min(self.page_size),
+            None => self.page_size,
+        }
+    }
+
+    /// Build the query for the next page from the template.
+    fn next_query(&self) -> PostgrestBuilder<Value> {
+        let mut q = PostgrestBuilder {
+            client: self.template.client.clone(),
+            table: self.template.table.clone(),
+            op: self.template.op,
+            state: self.template.state.clone(),
+            _marker: PhantomData,
+        };
+        q.state.limit = Some(self.next_page_len());
+        match &self.mode {
+            Mode::Offset => q.state.offset = Some(self.next_offset),
+            Mode::Keyset { column, ascending } => match &self.cursor {
+                Some(cursor) => {
+                    let op = if *ascending { "gt" } else { "lt" };
+                    q.push_param(encode_column(column), format!("{op}.{cursor}"));
+                }
+                None if self.next_offset > 0 => q.state.offset = Some(self.next_offset),
+                None => {}
+            },
+        }
+        // The total only needs computing once; drop the count on later pages.
+        if self.started {
+            q.state.prefer.retain(|p| !p.starts_with("count="));
+        }
+        q
+    }
+
+    fn fetch_next(&self) -> PageFuture {
+        let q = self.next_query();
+        Box::pin(async move { q.execute_inner().await })
+    }
+
+    /// Total row count reported by PostgREST, when `.count(..)` was set on
+    /// the builder. Fetches the first page if it hasn't been fetched yet;
+    /// its rows are buffered and still yielded by the stream.
+    pub async fn total(&mut self) -> Result<Option<u64>>
+    where
+        T: DeserializeOwned,
+    {
+        if !self.started && !self.done {
+            let fut = match self.pending.take() {
+                Some(fut) => fut,
+                None => self.fetch_next(),
+            };
+            let page = fut.await;
+            self.absorb(page)?;
+        }
+        Ok(self.total)
+    }
+
+    /// Fold a fetched page into the stream state.
+    fn absorb(&mut self, page: Result<(Option<u64>, Vec<Value>)>) -> Result<()>
+    where
+        T: DeserializeOwned,
+    {
+        let (count, rows) = match page {
+            Ok(page) => page,
+            Err(e) => {
+                self.done = true;
+                return Err(e);
+            }
+        };
+        if !self.started {
+            self.total = count;
+            self.started = true;
+        }
+
+        let requested = self.next_page_len();
+        let n = rows.len() as u64;
+        self.next_offset = self.next_offset.saturating_add(n);
+        if let Some(remaining) = self.remaining.as_mut() {
+            *remaining = remaining.saturating_sub(n);
+        }
+        if n < requested || self.remaining == Some(0) {
+            self.done = true;
+        }
+
+        if let (Mode::Keyset { column, .. }, Some(last)) = (&self.mode, rows.last()) {
+            let value = match last.get(column) {
+                Some(Value::String(s)) => s.clone(),
+                Some(v) if !v.is_null() => v.to_string(),
+                _ => {
+                    self.done = true;
+                    return Err(SupabaseError::Unexpected(format!(
+                        "keyset column `{column}` missing or null in row from `{}`; \
+                         include it in the select list",
+                        self.template.table
+                    )));
+                }
+            };
+            self.cursor = Some(encode_value(&value));
+        }
+
+        for row in rows {
+            match serde_json::from_value::<T>(row.clone()) {
+                Ok(decoded) => self.buffer.push_back(decoded),
+                Err(e) => {
+                    self.done = true;
+                    return Err(SupabaseError::Decode {
+                        message: e.to_string(),
+                        body: row.to_string(),
+                    });
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<T: DeserializeOwned> Stream for Paginate<T> {
+    type Item = Result<T>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = &mut *self;
+        loop {
+            if let Some(row) = this.buffer.pop_front() {
+                return Poll::Ready(Some(Ok(row)));
+            }
+            if this.done && this.pending.is_none() {
+                return Poll::Ready(None);
+            }
+            let fut = match this.pending.as_mut() {
+                Some(fut) => fut,
+                None => this.pending.insert(this.fetch_next()),
+            };
+            let page = match fut.as_mut().poll(cx) {
+                Poll::Ready(page) => page,
+                Poll::Pending => return Poll::Pending,
+            };
+            this.pending = None;
+            if let Err(e) = this.absorb(page) {
+                this.buffer.clear();
+                return Poll::Ready(Some(Err(e)));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+    use crate::SupabaseClient;
+
+    fn client() -> SupabaseClient {
+        SupabaseClient::new("https://example.supabase.co", "anon", None)
+    }
+
+    #[test]
+    fn offset_mode_first_page_reuses_filters() {
+        let p = client()
+            .from("t")
+            .select("*")
+            .eq("status", "active")
+            .order("id", true)
+            .paginate(50);
+        assert_eq!(
+            p.next_query().build_path(),
+            "/rest/v1/t?select=%2A&status=eq.active&order=id.asc&limit=50&offset=0"
+        );
+    }
+
+    #[test]
+    fn offset_mode_honours_existing_range() {
+        let mut p = client().from("t").select("*").range(10, 24).paginate(10);
+        assert_eq!(
+            p.next_query().build_path(),
+            "/rest/v1/t?select=%2A&limit=10&offset=10"
+        );
+        let rows = (0..10).map(|i| serde_json::json!({ "id": i })).collect();
+        p.absorb(Ok((None, rows))).unwrap();
+        assert!(!p.done);
+        // 15 rows in range, 10 already fetched — only 5 remain.
+        assert_eq!(
+            p.next_query().build_path(),
+            "/rest/v1/t?select=%2A&limit=5&offset=20"
+        );
+    }
+
+    #[test]
+    fn short_page_ends_the_stream() {
+        let mut p: Paginate<Value> = client().from("t").select("*").paginate(10);
+        p.absorb(Ok((None, vec![serde_json::json!({ "id": 1 })])))
+            .unwrap();
+        assert!(p.done);
+        assert_eq!(p.buffer.len(), 1);
+    }
+
+    #[test]
+    fn keyset_appends_ascending_order_when_missing() {
+        let p = client().from("t").select("*").paginate_keyset(100, "id");
+        assert_eq!(
+            p.mode,
+            Mode::Keyset {
+                column: "id".into(),
+                ascending: true
+            }
+        );
+        assert_eq!(
+            p.next_query().build_path(),
+            "/rest/v1/t?select=%2A&order=id.asc&limit=100"
+        );
+    }
+
+    #[test]
+    fn keyset_follows_existing_descending_order() {
+        let mut p: Paginate<Value> = client()
+            .from("t")
+            .select("*")
+            .order("created_at", false)
+            .paginate_keyset(2, "created_at");
+        let rows = vec![
+            serde_json::json!({ "created_at": "2024-02-02" }),
+            serde_json::json!({ "created_at": "2024-02-01" }),
+        ];
+        p.absorb(Ok((Some(7), rows))).unwrap();
+        assert_eq!(
+            p.next_query().build_path(),
+            "/rest/v1/t?select=%2A&order=created_at.desc&created_at=lt.2024-02-01&limit=2"
+        );
+    }
+
+    #[test]
+    fn keyset_requires_the_column_to_lead_the_order() {
+        let p: Paginate<Value> = client()
+            .from("t")
+            .select("*")
+            .order("created_at", false)
+            .order("id", true)
+            .paginate_keyset(10, "id");
+        let err = p.template.state.build_error.as_deref().unwrap();
+        assert!(err.contains("first order term"), "{err}");
+
+        let p: Paginate<Value> = client()
+            .from("t")
+            .select("*")
+            .order_with("id", crate::postgrest::Order::desc())
+            .order("created_at", true)
+            .paginate_keyset(10, "id");
+        assert!(p.template.state.build_error.is_none());
+        assert!(matches!(p.mode, Mode::Keyset { ascending: false, .. }));
+    }
+
+    #[test]
+    fn keyset_columns_with_separators_do_not_recurse() {
+        let p: Paginate<Value> = client().from("t").select("*").paginate_keyset(10, "a.b,c");
+        assert!(p.template.state.build_error.is_none());
+        assert!(matches!(p.mode, Mode::Keyset { ascending: true, .. }));
+        assert_eq!(leading_order_direction("id.descx", "id"), None);
+        assert_eq!(leading_order_direction("idx.asc", "id"), None);
+    }
+
+    #[test]
+    fn keyset_errors_when_column_not_selected() {
+        let mut p: Paginate<Value> = client().from("t").select("name").paginate_keyset(1, "id");
+        let err = p
+            .absorb(Ok((None, vec![serde_json::json!({ "name": "x" })])))
+            .unwrap_err();
+        assert!(matches!(err, SupabaseError::Unexpected(_)));
+        assert!(p.done);
+    }
+
+    #[test]
+    fn typed_keyset_uses_column_constant() {
+        #[derive(Debug, serde::Serialize, serde::Deserialize)]
+        struct Posts {
+            id: i64,
+        }
+        impl crate::postgrest::Row for Posts {
+            const TABLE: &'static str = "posts";
+        }
+        const ID: crate::postgrest::Column<Posts, i64> = crate::postgrest::Column::new("id");
+
+        let p = client()
+            .from_row::<Posts>()
+            .order(ID, false)
+            .paginate_keyset(25, ID);
+        assert_eq!(
+            p.mode,
+            Mode::Keyset {
+                column: "id".into(),
+                ascending: false
+            }
+        );
+        assert_eq!(
+            p.next_query().build_path(),
+            "/rest/v1/posts?select=%2A&order=id.desc&limit=25"
+        );
+    }
+
+    #[test]
+    fn count_is_only_requested_on_first_page() {
+        let mut p: Paginate<Value> = client()
+            .from("t")
+            .select("*")
+            .count(super::super::CountMode::Exact)
+            .paginate(1);
+        assert!(p
+            .next_query()
+            .build_options()
+            .prefer
+            .iter()
+            .any(|h| h == "count=exact"));
+        p.absorb(Ok((Some(3), vec![serde_json::json!({ "id": 1 })])))
+            .unwrap();
+        assert_eq!(p.total, Some(3));
+        assert!(!p
+            .next_query()
+            .build_options()
+            .prefer
+            .iter()
+            .any(|h| h == "count=exact"));
+    }
+}
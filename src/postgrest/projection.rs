@@ -0,0 +1,325 @@
+//! Typed partial selects.
+//!
+//! A [`Projection<R>`] is a struct holding a subset of `R`'s columns —
+//! possibly renamed or cast. [`TypedBuilder::select_as`] renders exactly its
+//! fields into `select=` and decodes rows into it, so leaving out a
+//! non-`Option` column no longer breaks deserialization of the full `R`.
+//!
+//! Declare projections with the [`projection!`](crate::projection) macro. Each
+//! field names a `Column<R, _>` constant, so a typo or a column from another
+//! table is a compile error, and un-cast fields must match the column's
+//! declared Rust type:
+//!
+//! ```no_run
+//! # use rust_supabase_sdk::{SupabaseClient, Row, postgrest::Column};
+//! # use serde::{Serialize, Deserialize};
+//! # #[derive(Debug, Clone, Serialize, Deserialize)]
+//! # struct Posts { id: String, title: String, view_count: i32 }
+//! # impl Row for Posts { const TABLE: &'static str = "posts"; }
+//! # #[allow(non_upper_case_globals)]
+//! # impl Posts {
+//! #     pub const id: Column<Posts, String> = Column::new("id");
+//! #     pub const title: Column<Posts, String> = Column::new("title");
+//! #     pub const view_count: Column<Posts, i32> = Column::new("view_count");
+//! # }
+//! rust_supabase_sdk::projection! {
+//!     #[derive(Debug, Deserialize)]
+//!     pub struct PostSummary: Posts {
+//!         pub id: String,
+//!         pub headline: String = title,           // headline:title
+//!         pub views: String = view_count as "text", // views:view_count::text
+//!     }
+//! }
+//!
+//! # async fn demo(client: SupabaseClient) -> rust_supabase_sdk::Result<()> {
+//! // select=id,headline:title,views:view_count::text
+//! let rows: Vec<PostSummary> = client
+//!     .from_row::<Posts>()
+//!     .select_as::<PostSummary>()
+//!     .await?;
+//! # Ok(()) }
+//! ```
+
+use std::marker::PhantomData;
+
+use serde::de::DeserializeOwned;
+
+use super::row::Row;
+use super::typed_builder::TypedBuilder;
+
+/// One entry of a projection's select list: `alias:column::cast`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ProjectedField {
+    alias: &'static str,
+    column: &'static str,
+    cast: Option<&'static str>,
+}
+
+impl ProjectedField {
+    /// `alias` is the struct field (and so the JSON key), `column` the
+    /// database column it reads from.
+    pub const fn new(alias: &'static str, column: &'static str, cast: Option<&'static str>) -> Self {
+        Self { alias, column, cast }
+    }
+
+    /// The JSON key the field comes back under. A raw-identifier prefix
+    /// (`r#type`) is stripped, matching serde's field naming.
+    pub fn alias(&self) -> &'static str {
+        self.alias.strip_prefix("r#").unwrap_or(self.alias)
+    }
+
+    /// The source column name.
+    pub const fn column(&self) -> &'static str {
+        self.column
+    }
+
+    /// The PostgreSQL cast applied to the column, if any.
+    pub const fn cast(&self) -> Option<&'static str> {
+        self.cast
+    }
+
+    fn render(&self) -> String {
+        let alias = self.alias();
+        let mut out = if alias == self.column {
+            self.column.to_string()
+        } else {
+            format!("{alias}:{}", self.column)
+        };
+        if let Some(cast) = self.cast {
+            out.push_str("::");
+            out.push_str(cast);
+        }
+        out
+    }
+}
+
+/// A partial view of row type `R`, decoded from a `select=` list of exactly
+/// [`FIELDS`](Self::FIELDS).
+///
+/// Usually implemented by [`projection!`](crate::projection), which checks
+/// every field against `R`'s column constants at compile time.
+pub trait Projection<R: Row>: DeserializeOwned + Send + 'static {
+    /// The projected columns, in select-list order.
+    const FIELDS: &'static [ProjectedField];
+
+    /// The rendered select list, e.g. `id,headline:title,views:view_count::text`.
+    fn select_list() -> String {
+        Self::FIELDS
+            .iter()
+            .map(ProjectedField::render)
+            .collect::<Vec<_>>()
+            .join(",")
+    }
+}
+
+impl<R: Row, O> TypedBuilder<R, O> {
+    /// Select exactly `P`'s fields and decode rows as `P` instead of `R`.
+    ///
+    /// Like [`select`](Self::select) this replaces the whole projection, so
+    /// call it before any `embed*` method.
+    pub fn select_as<P: Projection<R>>(mut self) -> TypedBuilder<R, P> {
+        self.inner = self.inner.select_returning(P::select_list());
+        TypedBuilder {
+            inner: self.inner,
+            _row: PhantomData,
+        }
+    }
+}
+
+/// Declare a [`Projection`](crate::postgrest::Projection) struct over a
+/// [`Row`](crate::Row) type.
+///
+/// ```text
+/// projection! {
+///     #[derive(Debug, Deserialize)]
+///     pub struct Name: RowType {
+///         pub field: Type,                       // column `field`
+///         pub field: Type = column,              // renamed: field:column
+///         pub field: Type = column as "text",    // cast: field:column::text
+///         pub column: Type as "text",            // cast without rename
+///     }
+/// }
+/// ```
+///
+/// Every column must exist as a `RowType::column` constant. Fields without a
+/// cast must have the column's declared type; cast fields may use any type
+/// the cast result deserializes into. The struct must derive (or implement)
+/// `Deserialize`; don't `#[serde(rename)]` its fields — the alias already
+/// matches the field name.
+#[macro_export]
+macro_rules! projection {
+    (
+        $(#[$meta:meta])*
+        $vis:vis struct $name:ident : $row:ty {
+            $(
+                $(#[$fmeta:meta])*
+                $fvis:vis $field:ident : $fty:ty $(= $col:ident)? $(as $cast:literal)?
+            ),* $(,)?
+        }
+    ) => {
+        $(#[$meta])*
+        $vis struct $name {
+            $(
+                $(#[$fmeta])*
+                $fvis $field: $fty,
+            )*
+        }
+
+        impl $crate::postgrest::Projection<$row> for $name {
+            const FIELDS: &'static [$crate::postgrest::ProjectedField] = &[
+                $(
+                    $crate::postgrest::ProjectedField::new(
+                        ::core::stringify!($field),
+                        $crate::__projection_column!($row, $field $(, $col)?).name(),
+                        $crate::__projection_cast!($($cast)?),
+                    ),
+                )*
+            ];
+        }
+
+        $(
+            $crate::__projection_check!(
+                $row,
+                $fty,
+                $crate::__projection_column!($row, $field $(, $col)?)
+                $(, $cast)?
+            );
+        )*
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __projection_column {
+    ($row:ty, $field:ident) => {
+        <$row>::$field
+    };
+    ($row:ty, $field:ident, $col:ident) => {
+        <$row>::$col
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __projection_cast {
+    () => {
+        ::core::option::Option::None
+    };
+    ($cast:literal) => {
+        ::core::option::Option::Some($cast)
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __projection_check {
+    ($row:ty, $fty:ty, $col:expr) => {
+        const _: $crate::postgrest::Column<$row, $fty> = $col;
+    };
+    // A cast changes the value type; the column's existence is already
+    // checked by `FIELDS`.
+    ($row:ty, $fty:ty, $col:expr, $cast:literal) => {};
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+    use crate::postgrest::Column;
+    use crate::SupabaseClient;
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    struct Posts {
+        id: String,
+        title: String,
+        view_count: i32,
+        r#type: String,
+    }
+
+    impl Row for Posts {
+        const TABLE: &'static str = "posts";
+    }
+
+    #[allow(non_upper_case_globals)]
+    impl Posts {
+        const id: Column<Posts, String> = Column::new("id");
+        const title: Column<Posts, String> = Column::new("title");
+        const view_count: Column<Posts, i32> = Column::new("view_count");
+        const r#type: Column<Posts, String> = Column::new("type");
+    }
+
+    crate::projection! {
+        #[derive(Debug, Deserialize)]
+        struct PostSummary: Posts {
+            id: String,
+            headline: String = title,
+            views: String = view_count as "text",
+            r#type: String,
+        }
+    }
+
+    crate::projection! {
+        #[derive(Debug, Deserialize)]
+        struct Counted: Posts {
+            view_count: i64 as "int8",
+        }
+    }
+
+    fn client() -> SupabaseClient {
+        SupabaseClient::new("https://x.supabase.co", "anon", None)
+    }
+
+    #[test]
+    fn fields_carry_aliases_columns_and_casts() {
+        assert_eq!(
+            PostSummary::FIELDS,
+            &[
+                ProjectedField::new("id", "id", None),
+                ProjectedField::new("headline", "title", None),
+                ProjectedField::new("views", "view_count", Some("text")),
+                ProjectedField::new("r#type", "type", None),
+            ]
+        );
+    }
+
+    #[test]
+    fn select_list_renders_renames_and_casts() {
+        assert_eq!(
+            <PostSummary as Projection<Posts>>::select_list(),
+            "id,headline:title,views:view_count::text,type"
+        );
+        assert_eq!(
+            <Counted as Projection<Posts>>::select_list(),
+            "view_count::int8"
+        );
+    }
+
+    #[test]
+    fn select_as_replaces_select_param() {
+        let p = client()
+            .from_row::<Posts>()
+            .select("*")
+            .select_as::<PostSummary>()
+            .build_path();
+        assert_eq!(
+            p,
+            "/rest/v1/posts?select=id%2Cheadline%3Atitle%2Cviews%3Aview_count%3A%3Atext%2Ctype"
+        );
+    }
+
+    #[test]
+    fn projected_rows_decode_under_aliases() {
+        let row: PostSummary = serde_json::from_value(serde_json::json!({
+            "id": "p1", "headline": "Hello", "views": "42", "type": "note"
+        }))
+        .unwrap();
+        assert_eq!(row.id, "p1");
+        assert_eq!(row.headline, "Hello");
+        assert_eq!(row.views, "42");
+        assert_eq!(row.r#type, "note");
+
+        let counted: Counted = serde_json::from_value(serde_json::json!({"view_count": 7})).unwrap();
+        assert_eq!(counted.view_count, 7);
+    }
+}
@@ -20,6 +20,14 @@
 //!
 //! `cargo supabase gen types` emits this `impl` automatically for every
 //! discovered table.
+//!
+//! Typed write payloads are declared separately, through [`Writable`], so
+//! hand-written read-only impls don't have to name them.
+//!
+//! Foreign-key relations are declared separately, through
+//! [`Related<O>`](crate::postgrest::Related) and
+//! [`Embeddable`](crate::postgrest::Embeddable), because they are
+//! parameterized by the other row type.
 
 use serde::de::DeserializeOwned;
 use serde::Serialize;
@@ -27,9 +35,9 @@ use serde::Serialize;
 /// A Rust type bound to a PostgREST table. Implementors are usable as the
 /// `R` parameter in [`SupabaseClient::from_row`](crate::SupabaseClient::from_row).
 ///
-/// The trait is intentionally minimal — it only carries the table name today.
-/// Future minor versions may add associated columns / primary-key metadata
-/// behind default-defaulted associated items so existing impls keep compiling.
+/// Only [`TABLE`](Self::TABLE) is required. Column names, the schema and the
+/// primary key are associated constants with defaults, so hand-written impls
+/// that only name the table keep compiling; codegen fills them all in.
 pub trait Row: DeserializeOwned + Serialize + Send + Sync + 'static {
     /// The PostgREST/PostgreSQL table name. Forwarded to
     /// [`SupabaseClient::from`](crate::SupabaseClient::from) when this row
@@ -44,6 +52,46 @@ pub trait Row: DeserializeOwned + Serialize + Send + Sync + 'static {
     /// Optional schema. Defaults to `None` meaning the client's configured
     /// schema (typically `public`).
     const SCHEMA: Option<&'static str> = None;
+
+    /// Primary-key column names. Defaults to `&[]`; codegen fills it in.
+    /// Required by [`SupabaseClient::save`](crate::SupabaseClient::save),
+    /// which targets the row through these columns.
+    const PRIMARY_KEY: &'static [&'static str] = &[];
+}
+
+/// The Rust type of a [`Row`]'s primary key: the column's type for a
+/// single-column key, a tuple in [`PRIMARY_KEY`](Row::PRIMARY_KEY) order for
+/// a composite one. Enables [`find`](crate::SupabaseClient::find),
+/// [`find_many`](crate::SupabaseClient::find_many),
+/// [`update_by_pk`](crate::SupabaseClient::update_by_pk) and
+/// [`delete_by_pk`](crate::SupabaseClient::delete_by_pk).
+///
+/// A separate trait rather than an item on [`Row`] because associated types
+/// can't have defaults, and hand-written `Row` impls must keep compiling.
+/// `cargo supabase gen types` implements it for every table with a primary
+/// key.
+pub trait Keyed: Row {
+    /// `i64`, `String`, `(i64, String)`, … Must serialize to a scalar, or to
+    /// an array with one element per key column.
+    type PrimaryKey: Serialize + Send + Sync + 'static;
+}
+
+/// The write payloads of a [`Row`]. Implementing it unlocks the typed
+/// [`insert`](crate::postgrest::TypedBuilder::insert),
+/// [`upsert`](crate::postgrest::TypedBuilder::upsert) and
+/// [`update`](crate::postgrest::TypedBuilder::update), which accept only
+/// these types.
+///
+/// `cargo supabase gen types` emits a `<Name>Insert` / `<Name>Update` pair
+/// and this impl for every discovered table.
+pub trait Writable: Row {
+    /// `INSERT` / upsert payload. Columns the database fills in (defaults,
+    /// nullable columns) should be `Option` and skipped when `None`.
+    type Insert: Serialize + Send + Sync + 'static;
+
+    /// `UPDATE` payload. Every column optional, skipped when `None`, so only
+    /// the columns that are set are written.
+    type Update: Serialize + Send + Sync + 'static;
 }
 
 #[cfg(test)]
@@ -67,5 +115,6 @@ mod tests {
         assert_eq!(Country::TABLE, "countries");
         assert_eq!(Country::COLUMNS, &["id", "name"]);
         assert_eq!(Country::SCHEMA, None);
+        assert!(Country::PRIMARY_KEY.is_empty());
     }
 }
@@ -0,0 +1,323 @@
+//! Incremental row decoding for large result sets.
+//!
+//! [`execute_stream`](PostgrestBuilder::execute_stream) reads the response
+//! body chunk by chunk, splits the top-level JSON array into elements as
+//! their bytes arrive, and decodes each one into `T` on its own — memory use
+//! is bounded by the largest row, not by the result set:
+//!
+//! ```no_run
+//! # use rust_supabase_sdk::SupabaseClient;
+//! use futures_util::StreamExt;
+//! # async fn demo(client: SupabaseClient) -> rust_supabase_sdk::Result<()> {
+//! let mut rows = client
+//!     .from("events")
+//!     .select("*")
+//!     .execute_stream()
+//!     .await?
+//!     .max_row_bytes(64 * 1024);
+//! while let Some(row) = rows.next().await {
+//!     let row: serde_json::Value = row?;
+//!     // ...
+//! }
+//! # Ok(()) }
+//! ```
+
+use std::collections::VecDeque;
+use std::marker::PhantomData;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use futures_util::Stream;
+use serde::de::DeserializeOwned;
+
+use crate::error::{Result, SupabaseError};
+
+use super::builder::PostgrestBuilder;
+use super::format::ByteStream;
+use super::row::Row;
+use super::typed_builder::TypedBuilder;
+
+/// Default cap on the encoded size of a single row: 16 MiB.
+pub const DEFAULT_MAX_ROW_BYTES: usize = 16 * 1024 * 1024;
+
+/// Stream of rows returned by [`PostgrestBuilder::execute_stream`].
+///
+/// Yields each array element as soon as its closing byte arrives. The stream
+/// ends after the closing `]`, or after the first error (transport, malformed
+/// JSON, a row that fails to decode, or a row over
+/// [`max_row_bytes`](Self::max_row_bytes)).
+#[must_use = "RowStream is lazy — poll it as a Stream"]
+pub struct RowStream<T> {
+    body: ByteStream,
+    scanner: ArrayScanner,
+    ready: VecDeque<Vec<u8>>,
+    /// Scan error held back until the rows completed before it are yielded.
+    error: Option<SupabaseError>,
+    done: bool,
+    _marker: PhantomData<fn() -> T>,
+}
+
+// The byte stream is already boxed and nothing else is pin-projected.
+impl<T> Unpin for RowStream<T> {}
+
+impl<T> std::fmt::Debug for RowStream<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RowStream")
+            .field("max_row_bytes", &self.scanner.max_row_bytes)
+            .field("buffered_rows", &self.ready.len())
+            .field("done", &self.done)
+            .finish()
+    }
+}
+
+impl<T> RowStream<T> {
+    fn new(body: ByteStream) -> Self {
+        Self {
+            body,
+            scanner: ArrayScanner::new(DEFAULT_MAX_ROW_BYTES),
+            ready: VecDeque::new(),
+            error: None,
+            done: false,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Fail with [`SupabaseError::Decode`] instead of buffering a row whose
+    /// JSON encoding exceeds `bytes`. Defaults to
+    /// [`DEFAULT_MAX_ROW_BYTES`].
+    pub fn max_row_bytes(mut self, bytes: usize) -> Self {
+        self.scanner.max_row_bytes = bytes;
+        self
+    }
+
+    /// End the stream after the rows already split off.
+    fn fail(&mut self, message: String, body: String) {
+        self.done = true;
+        self.error = Some(SupabaseError::Decode { message, body });
+    }
+}
+
+impl<T: DeserializeOwned> Stream for RowStream<T> {
+    type Item = Result<T>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        loop {
+            if let Some(raw) = self.ready.pop_front() {
+                match serde_json::from_slice(&raw) {
+                    Ok(row) => return Poll::Ready(Some(Ok(row))),
+                    Err(e) => {
+                        self.ready.clear();
+                        let body = String::from_utf8_lossy(&raw).into_owned();
+                        self.fail(e.to_string(), body);
+                    }
+                }
+            }
+            if let Some(err) = self.error.take() {
+                return Poll::Ready(Some(Err(err)));
+            }
+            if self.done {
+                return Poll::Ready(None);
+            }
+            match Pin::new(&mut self.body).poll_next(cx) {
+                Poll::Pending => return Poll::Pending,
+                Poll::Ready(Some(Ok(chunk))) => {
+                    let this = &mut *self;
+                    if let Err(message) = this.scanner.feed(&chunk, &mut this.ready) {
+                        let body = String::from_utf8_lossy(&this.scanner.buf).into_owned();
+                        self.fail(message, body);
+                    }
+                }
+                Poll::Ready(Some(Err(e))) => {
+                    self.done = true;
+                    self.error = Some(e);
+                }
+                Poll::Ready(None) => {
+                    if self.scanner.state != ScanState::End {
+                        let body = String::from_utf8_lossy(&self.scanner.buf).into_owned();
+                        self.fail("response ended inside the JSON array".to_string(), body);
+                    }
+                    self.done = true;
+                }
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ScanState {
+    /// Before the opening `[`.
+    Start,
+    /// Expecting an element (or `]` when `first`).
+    BeforeValue { first: bool },
+    InValue,
+    /// After the closing `]`.
+    End,
+}
+
+/// Splits a top-level JSON array into its raw elements. Tracks only nesting
+/// depth and string/escape state; each element is validated by serde when
+/// it's decoded.
+#[derive(Debug)]
+struct ArrayScanner {
+    state: ScanState,
+    depth: usize,
+    in_string: bool,
+    escaped: bool,
+    buf: Vec<u8>,
+    max_row_bytes: usize,
+}
+
+impl ArrayScanner {
+    fn new(max_row_bytes: usize) -> Self {
+        Self {
+            state: ScanState::Start,
+            depth: 0,
+            in_string: false,
+            escaped: false,
+            buf: Vec::new(),
+            max_row_bytes,
+        }
+    }
+
+    fn feed(&mut self, chunk: &[u8], out: &mut VecDeque<Vec<u8>>) -> std::result::Result<(), String> {
+        for &b in chunk {
+            match self.state {
+                ScanState::Start => match b {
+                    b'[' => self.state = ScanState::BeforeValue { first: true },
+                    b if b.is_ascii_whitespace() => {}
+                    other => return Err(format!("expected a JSON array, found `{}`", other as char)),
+                },
+                ScanState::BeforeValue { first } => match b {
+                    b if b.is_ascii_whitespace() => {}
+                    b']' if first => self.state = ScanState::End,
+                    b']' | b',' => return Err(format!("unexpected `{}` in JSON array", b as char)),
+                    _ => {
+                        self.state = ScanState::InValue;
+                        self.value_byte(b, out)?;
+                    }
+                },
+                ScanState::InValue => self.value_byte(b, out)?,
+                ScanState::End => {
+                    if !b.is_ascii_whitespace() {
+                        return Err("trailing characters after JSON array".to_string());
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn value_byte(&mut self, b: u8, out: &mut VecDeque<Vec<u8>>) -> std::result::Result<(), String> {
+        if self.in_string {
+            if self.escaped {
+                self.escaped = false;
+            } else if b == b'\\' {
+                self.escaped = true;
+            } else if b == b'"' {
+                self.in_string = false;
+            }
+        } else {
+            match b {
+                b'"' => self.in_string = true,
+                b'{' | b'[' => self.depth += 1,
+                b'}' | b']' if self.depth > 0 => self.depth -= 1,
+                b']' => {
+                    out.push_back(std::mem::take(&mut self.buf));
+                    self.state = ScanState::End;
+                    return Ok(());
+                }
+                b',' if self.depth == 0 => {
+                    out.push_back(std::mem::take(&mut self.buf));
+                    self.state = ScanState::BeforeValue { first: false };
+                    return Ok(());
+                }
+                _ => {}
+            }
+        }
+        self.buf.push(b);
+        if self.buf.len() > self.max_row_bytes {
+            return Err(format!("row exceeds max_row_bytes ({})", self.max_row_bytes));
+        }
+        Ok(())
+    }
+}
+
+impl<T: DeserializeOwned + Send + 'static> PostgrestBuilder<T> {
+    /// Run a select and decode rows one at a time as the response body
+    /// arrives, instead of buffering it. Non-2xx responses are decoded into
+    /// the usual [`SupabaseError`] before the stream is returned. A query
+    /// that provably matches nothing (`is_in` with no values) yields an
+    /// empty stream without a request.
+    ///
+    /// Only select queries are supported; write operations return
+    /// [`SupabaseError::Unexpected`].
+    pub async fn execute_stream(self) -> Result<RowStream<T>> {
+        Ok(RowStream::new(self.send_with_accept("application/json", b"[]").await?))
+    }
+}
+
+impl<R: Row, O: DeserializeOwned + Send + 'static> TypedBuilder<R, O> {
+    /// See [`PostgrestBuilder::execute_stream`].
+    pub async fn execute_stream(self) -> Result<RowStream<O>> {
+        self.inner.returns::<O>().execute_stream().await
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    fn scan(chunks: &[&str], max: usize) -> std::result::Result<(Vec<String>, ScanState), String> {
+        let mut scanner = ArrayScanner::new(max);
+        let mut out = VecDeque::new();
+        for chunk in chunks {
+            scanner.feed(chunk.as_bytes(), &mut out)?;
+        }
+        let rows = out
+            .into_iter()
+            .map(|raw| String::from_utf8(raw).unwrap())
+            .collect();
+        Ok((rows, scanner.state))
+    }
+
+    #[test]
+    fn splits_elements_across_chunk_boundaries() {
+        let (rows, state) = scan(
+            &[" [{\"a\":1,\"b\":[1,", "2]}, {\"s\":\"x,]}\\\"", "y\"} ,3, \"z\"", "]\n"],
+            1024,
+        )
+        .unwrap();
+        assert_eq!(
+            rows,
+            vec![
+                "{\"a\":1,\"b\":[1,2]}",
+                "{\"s\":\"x,]}\\\"y\"} ",
+                "3",
+                "\"z\""
+            ]
+        );
+        assert_eq!(state, ScanState::End);
+    }
+
+    #[test]
+    fn empty_array_yields_nothing() {
+        let (rows, state) = scan(&["[", " ]"], 16).unwrap();
+        assert!(rows.is_empty());
+        assert_eq!(state, ScanState::End);
+    }
+
+    #[test]
+    fn rejects_non_arrays_and_trailing_garbage() {
+        assert!(scan(&["{\"a\":1}"], 16).unwrap_err().contains("expected a JSON array"));
+        assert!(scan(&["[1] x"], 16).unwrap_err().contains("trailing"));
+        assert!(scan(&["[1,,2]"], 16).unwrap_err().contains("unexpected"));
+    }
+
+    #[test]
+    fn enforces_row_size_bound() {
+        let err = scan(&["[{\"a\":\"0123456789\"}]"], 8).unwrap_err();
+        assert!(err.contains("max_row_bytes (8)"), "{err}");
+        assert!(scan(&["[1,2]"], 1).is_ok());
+    }
+}
@@ -0,0 +1,257 @@
+//! Change tracking for partial updates.
+//!
+//! Sending a whole row back as an update overwrites whatever another writer
+//! changed in the meantime. [`Tracked<R>`] keeps a snapshot of the row as it
+//! was read; [`SupabaseClient::save`] diffs against it and `PATCH`es only
+//! the columns that changed, targeting the row by [`Row::PRIMARY_KEY`]:
+//!
+//! ```no_run
+//! # use rust_supabase_sdk::{SupabaseClient, Row, postgrest::Column};
+//! # use serde::{Serialize, Deserialize};
+//! # #[derive(Debug, Clone, Serialize, Deserialize)]
+//! # struct Posts { id: i64, title: String, updated_at: String }
+//! # impl Row for Posts {
+//! #     const TABLE: &'static str = "posts";
+//! #     const PRIMARY_KEY: &'static [&'static str] = &["id"];
+//! # }
+//! # #[allow(non_upper_case_globals)]
+//! # impl Posts {
+//! #     pub const id: Column<Posts, i64> = Column::new("id");
+//! #     pub const updated_at: Column<Posts, String> = Column::new("updated_at");
+//! # }
+//! # async fn demo(client: SupabaseClient) -> rust_supabase_sdk::Result<()> {
+//! let mut post = client
+//!     .from_row::<Posts>()
+//!     .eq(Posts::id, 7)
+//!     .execute_tracked()
+//!     .await?
+//!     .remove(0);
+//! post.title = "Renamed".into();
+//! post.guard(Posts::updated_at);
+//!
+//! // PATCH /posts?id=eq.7&updated_at=eq.<as read>  {"title":"Renamed"}
+//! let post = client.save(&post).await?;
+//! # Ok(()) }
+//! ```
+//!
+//! A [guarded](Tracked::guard) save whose row changed (or vanished) since
+//! it was read matches nothing and fails with [`SupabaseError::Conflict`].
+
+use std::ops::{Deref, DerefMut};
+
+use serde_json::{Map, Value};
+
+use crate::error::{Result, SupabaseError};
+use crate::SupabaseClient;
+
+use super::column::Column;
+use super::row::Row;
+use super::typed_builder::TypedBuilder;
+
+/// A row plus a snapshot of its columns as they were read. Derefs to `R`,
+/// so fields are read and assigned as usual; [`dirty_columns`] reports the
+/// ones that now differ from the snapshot.
+///
+/// [`dirty_columns`]: Tracked::dirty_columns
+#[derive(Debug, Clone)]
+pub struct Tracked<R> {
+    row: R,
+    original: Map<String, Value>,
+    guards: Vec<&'static str>,
+}
+
+impl<R: Row> Tracked<R> {
+    /// Start tracking `row`, taking its current values as the snapshot.
+    pub fn new(row: R) -> Self {
+        let original = match serde_json::to_value(&row) {
+            Ok(Value::Object(map)) => map,
+            _ => Map::new(),
+        };
+        Self {
+            row,
+            original,
+            guards: Vec::new(),
+        }
+    }
+
+    /// Make [`save`](SupabaseClient::save) also require `col` to still hold
+    /// the value it had when read (`col=eq.<snapshot>`) — typically an
+    /// `updated_at` or version column. Can be called for several columns.
+    pub fn guard<V>(&mut self, col: Column<R, V>) -> &mut Self {
+        if !self.guards.contains(&col.name()) {
+            self.guards.push(col.name());
+        }
+        self
+    }
+
+    /// Columns whose current value differs from the snapshot, in column
+    /// name order.
+    pub fn dirty_columns(&self) -> Vec<String> {
+        self.changes()
+            .map(|changes| changes.into_iter().map(|(k, _)| k).collect())
+            .unwrap_or_default()
+    }
+
+    /// `true` when at least one column changed since the snapshot.
+    pub fn is_dirty(&self) -> bool {
+        !self.dirty_columns().is_empty()
+    }
+
+    /// Stop tracking and return the row.
+    pub fn into_inner(self) -> R {
+        self.row
+    }
+
+    /// The changed columns with their new values.
+    fn changes(&self) -> Result<Map<String, Value>> {
+        let current = match serde_json::to_value(&self.row)? {
+            Value::Object(map) => map,
+            other => {
+                return Err(SupabaseError::Unexpected(format!(
+                    "{} row must serialize to a JSON object, got {other}",
+                    R::TABLE
+                )))
+            }
+        };
+        Ok(current
+            .into_iter()
+            .filter(|(k, v)| self.original.get(k) != Some(v))
+            .collect())
+    }
+}
+
+impl<R> Deref for Tracked<R> {
+    type Target = R;
+
+    fn deref(&self) -> &R {
+        &self.row
+    }
+}
+
+impl<R> DerefMut for Tracked<R> {
+    fn deref_mut(&mut self) -> &mut R {
+        &mut self.row
+    }
+}
+
+impl SupabaseClient {
+    /// Write the dirty columns of `tracked` back with a `PATCH` filtered by
+    /// the row's primary key (and any [guard](Tracked::guard) columns), all
+    /// matched against the snapshot values. Returns the row as stored,
+    /// freshly tracked.
+    ///
+    /// Nothing dirty means no request: the current row is returned as is.
+    /// Fails with [`SupabaseError::Conflict`] when no row matched, and with
+    /// [`SupabaseError::Unexpected`] when `R` declares no
+    /// [`PRIMARY_KEY`](Row::PRIMARY_KEY).
+    pub async fn save<R: Row>(&self, tracked: &Tracked<R>) -> Result<Tracked<R>> {
+        if R::PRIMARY_KEY.is_empty() {
+            return Err(SupabaseError::Unexpected(format!(
+                "{} declares no PRIMARY_KEY, so save cannot target a row",
+                R::TABLE
+            )));
+        }
+        let changes = tracked.changes()?;
+        if changes.is_empty() {
+            let row = serde_json::from_value(serde_json::to_value(&tracked.row)?)?;
+            return Ok(Tracked::new(row));
+        }
+
+        let mut query = self.from(R::TABLE).update(Value::Object(changes));
+        let mut matched = Vec::new();
+        for column in R::PRIMARY_KEY.iter().chain(&tracked.guards) {
+            let Some(value) = tracked.original.get(*column) else {
+                return Err(SupabaseError::Unexpected(format!(
+                    "{} row has no `{column}` value to filter on",
+                    R::TABLE
+                )));
+            };
+            query = match value {
+                Value::Null => query.is(column, "null"),
+                Value::String(s) => query.eq(column, s.as_str()),
+                other => query.eq(column, other.to_string()),
+            };
+            matched.push(format!("{column}={value}"));
+        }
+
+        let rows = query.select_returning("*").returns::<R>().execute().await?;
+        match rows.into_iter().next() {
+            Some(row) => Ok(Tracked::new(row)),
+            None => Err(SupabaseError::Conflict {
+                resource: format!("{} ({})", R::TABLE, matched.join(", ")),
+            }),
+        }
+    }
+}
+
+impl<R: Row> TypedBuilder<R> {
+    /// Execute and wrap each row in a [`Tracked`], ready for
+    /// [`SupabaseClient::save`].
+    pub async fn execute_tracked(self) -> Result<Vec<Tracked<R>>> {
+        let rows = self.execute().await?;
+        Ok(rows.into_iter().map(Tracked::new).collect())
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    struct Posts {
+        id: i64,
+        title: String,
+        body: Option<String>,
+    }
+
+    impl Row for Posts {
+        const TABLE: &'static str = "posts";
+    }
+
+    #[allow(non_upper_case_globals)]
+    impl Posts {
+        const title: Column<Posts, String> = Column::new("title");
+    }
+
+    fn post() -> Posts {
+        Posts {
+            id: 1,
+            title: "Hello".into(),
+            body: Some("text".into()),
+        }
+    }
+
+    #[test]
+    fn mutations_show_up_as_dirty_columns() {
+        let mut tracked = Tracked::new(post());
+        assert!(!tracked.is_dirty());
+        tracked.title = "Hello".into();
+        assert!(!tracked.is_dirty(), "same value is not a change");
+        tracked.body = None;
+        tracked.title.push('!');
+        assert_eq!(tracked.dirty_columns(), ["body", "title"]);
+        assert_eq!(
+            Value::Object(tracked.changes().unwrap()),
+            serde_json::json!({ "body": null, "title": "Hello!" })
+        );
+        assert_eq!(tracked.into_inner().title, "Hello!");
+    }
+
+    #[test]
+    fn guards_are_recorded_once() {
+        let mut tracked = Tracked::new(post());
+        tracked.guard(Posts::title).guard(Posts::title);
+        assert_eq!(tracked.guards, ["title"]);
+    }
+
+    #[tokio::test]
+    async fn save_without_primary_key_is_rejected() {
+        let client = SupabaseClient::new("https://x.supabase.co", "anon", None);
+        let mut tracked = Tracked::new(post());
+        tracked.title = "Changed".into();
+        let err = client.save(&tracked).await.unwrap_err();
+        assert!(err.to_string().contains("PRIMARY_KEY"), "{err}");
+    }
+}
@@ -0,0 +1,529 @@
+//! Atomic multi-statement writes through an RPC helper function.
+//!
+//! PostgREST runs every request in its own transaction, so two awaited
+//! builders can never commit or roll back together. [`Transaction`] instead
+//! compiles several builders into a JSON description and ships them in one
+//! call to a SQL function ([`TRANSACTION_SQL`], installed once as a
+//! migration) that executes them in order inside a single transaction:
+//!
+//! ```no_run
+//! # use rust_supabase_sdk::SupabaseClient;
+//! # use serde_json::json;
+//! # async fn demo(client: SupabaseClient) -> rust_supabase_sdk::Result<()> {
+//! let results = client
+//!     .transaction()
+//!     .push(client.from("accounts").update(json!({"balance": 90})).eq("id", 1))
+//!     .push(client.from("accounts").update(json!({"balance": 110})).eq("id", 2))
+//!     .push(
+//!         client
+//!             .from("transfers")
+//!             .insert(json!({"from": 1, "to": 2, "amount": 10}))
+//!             .select_returning("*"),
+//!     )
+//!     .execute()
+//!     .await?;
+//! assert_eq!(results[0].count, 1);
+//! let transfers: Vec<serde_json::Value> = results[2].decode()?;
+//! # Ok(()) }
+//! ```
+//!
+//! If any statement fails, nothing is committed and the error comes back as
+//! a [`SupabaseError::Postgrest`] whose `details` start with
+//! `statement <n>` (zero-based).
+//!
+//! Only what maps directly onto SQL is supported: plain column lists,
+//! `eq`/`neq`/`gt`/`gte`/`lt`/`lte`/`like`/`ilike`/`is`/`in` filters (and
+//! their `not.` forms), `order`/`limit`/`offset` on selects, and
+//! `on_conflict`/`ignore_duplicates` on upserts. Anything else — `or`,
+//! embedded resources, CSV bodies, bulk rows that don't all name the same
+//! columns, updates with an empty body — is rejected with
+//! [`SupabaseError::Unexpected`] before a request is sent.
+
+use std::collections::BTreeSet;
+
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use serde_json::{json, Map, Value};
+
+use crate::error::{Result, SupabaseError};
+use crate::SupabaseClient;
+
+use super::builder::{Operation, PostgrestBuilder};
+
+/// SQL for the helper function [`Transaction`] calls, `public.sdk_transaction`.
+/// Apply it once, e.g. as a migration.
+pub const TRANSACTION_SQL: &str = include_str!("transaction.sql");
+
+/// Name of the function defined by [`TRANSACTION_SQL`].
+pub const DEFAULT_TRANSACTION_FUNCTION: &str = "sdk_transaction";
+
+/// Builder returned by [`SupabaseClient::transaction`]. Collects statements
+/// with [`push`](Self::push) and runs them atomically with
+/// [`execute`](Self::execute).
+#[must_use = "Transaction does nothing until `.execute()` is awaited"]
+#[derive(Debug)]
+pub struct Transaction {
+    client: SupabaseClient,
+    function: String,
+    statements: Vec<Result<Value>>,
+}
+
+/// Outcome of one statement in a [`Transaction`].
+#[derive(Debug, Clone, Deserialize)]
+pub struct StatementResult {
+    /// Rows selected, inserted, updated or deleted.
+    pub count: u64,
+    /// The rows themselves, for selects and for writes that called
+    /// [`select_returning`](PostgrestBuilder::select_returning). Empty
+    /// otherwise.
+    #[serde(default)]
+    pub rows: Vec<Value>,
+}
+
+impl StatementResult {
+    /// Deserialize [`rows`](Self::rows) into `T`.
+    pub fn decode<T: DeserializeOwned>(&self) -> Result<Vec<T>> {
+        let rows = Value::Array(self.rows.clone());
+        serde_json::from_value(rows.clone()).map_err(|e| SupabaseError::Decode {
+            message: e.to_string(),
+            body: rows.to_string(),
+        })
+    }
+}
+
+impl SupabaseClient {
+    /// Start a [`Transaction`]: several builders executed atomically through
+    /// the [`TRANSACTION_SQL`] helper function.
+    pub fn transaction(&self) -> Transaction {
+        Transaction {
+            client: self.clone(),
+            function: DEFAULT_TRANSACTION_FUNCTION.to_string(),
+            statements: Vec::new(),
+        }
+    }
+}
+
+impl Transaction {
+    /// Append a statement. The builder is compiled immediately; unsupported
+    /// features are reported by [`execute`](Self::execute). Typed builders
+    /// go through [`into_untyped`](super::TypedBuilder::into_untyped).
+    pub fn push<T>(mut self, query: PostgrestBuilder<T>) -> Self {
+        self.statements.push(compile(&query));
+        self
+    }
+
+    /// Call a differently named helper function (same signature as the one
+    /// in [`TRANSACTION_SQL`]), e.g. one installed in another schema.
+    pub fn function(mut self, name: impl Into<String>) -> Self {
+        self.function = name.into();
+        self
+    }
+
+    /// Number of statements added so far.
+    pub fn len(&self) -> usize {
+        self.statements.len()
+    }
+
+    /// `true` if no statements have been added.
+    pub fn is_empty(&self) -> bool {
+        self.statements.is_empty()
+    }
+
+    /// Run every statement in one database transaction. Returns one
+    /// [`StatementResult`] per statement, in the order they were added. An
+    /// empty transaction returns `Ok(vec![])` without a request.
+    pub async fn execute(self) -> Result<Vec<StatementResult>> {
+        if self.statements.is_empty() {
+            return Ok(Vec::new());
+        }
+        let statements = self
+            .statements
+            .into_iter()
+            .enumerate()
+            .map(|(i, stmt)| {
+                stmt.map_err(|e| match e {
+                    SupabaseError::Unexpected(msg) => {
+                        SupabaseError::Unexpected(format!("transaction statement {i}: {msg}"))
+                    }
+                    other => other,
+                })
+            })
+            .collect::<Result<Vec<Value>>>()?;
+        let expected = statements.len();
+        let written: Vec<String> = statements
+            .iter()
+            .filter(|stmt| stmt["op"] != "select")
+            .filter_map(|stmt| stmt["table"].as_str().map(str::to_string))
+            .collect();
+
+        let results = self
+            .client
+            .rpc_call(&self.function, json!({ "statements": statements }))
+            .await;
+        if let Some(cache) = self.client.query_cache() {
+            for table in &written {
+                cache.invalidate_table(table);
+            }
+        }
+        let results = results?;
+        if results.len() != expected {
+            return Err(SupabaseError::Unexpected(format!(
+                "`{}` returned {} results for {expected} statements",
+                self.function,
+                results.len()
+            )));
+        }
+        results
+            .into_iter()
+            .map(|value| {
+                serde_json::from_value(value.clone()).map_err(|e| SupabaseError::Decode {
+                    message: e.to_string(),
+                    body: value.to_string(),
+                })
+            })
+            .collect()
+    }
+}
+
+/// Translate a builder into the statement object `sdk_transaction` reads.
+fn compile<T>(query: &PostgrestBuilder<T>) -> Result<Value> {
+    let state = &query.state;
+    state.check()?;
+    if state.raw_body.is_some() {
+        return Err(unsupported("raw (CSV) request bodies"));
+    }
+
+    let op = match query.op {
+        Operation::Select => "select",
+        Operation::Insert => "insert",
+        Operation::Upsert => "upsert",
+        Operation::Update => "update",
+        Operation::Delete => "delete",
+        Operation::Rpc(_) => return Err(unsupported("function calls")),
+    };
+    let is_select = query.op == Operation::Select;
+    // `update … set  from …` would be a syntax error inside the function.
+    let sets_columns = matches!(&state.body, Some(Value::Object(cols)) if !cols.is_empty());
+    if query.op == Operation::Update && !sets_columns {
+        return Err(SupabaseError::Unexpected("update sets no columns".to_string()));
+    }
+
+    let mut stmt = Map::new();
+    stmt.insert("op".into(), op.into());
+    let schema = query.client.schema.as_deref().unwrap_or("public");
+    stmt.insert("schema".into(), schema.into());
+    stmt.insert("table".into(), query.table.clone().into());
+    if let Some(body) = &state.body {
+        stmt.insert("body".into(), body.clone());
+    }
+    stmt.insert("columns".into(), compile_columns(state.select_cols.as_deref())?);
+    stmt.insert("returning".into(), state.return_representation.into());
+    if state.short_circuit_empty_result {
+        stmt.insert("match_nothing".into(), true.into());
+    }
+    if state.prefer.iter().any(|p| p == "resolution=ignore-duplicates") {
+        stmt.insert("ignore_duplicates".into(), true.into());
+    }
+
+    let mut filters = Vec::new();
+    let mut order = Vec::new();
+    for (key, value) in &state.params {
+        let key = decode(key)?;
+        match key.as_str() {
+            "on_conflict" => {
+                let columns: Vec<String> = decode(value)?.split(',').map(|c| c.trim().to_string()).collect();
+                stmt.insert("on_conflict".into(), columns.into());
+            }
+            // The function takes the insert columns from the body itself,
+            // but fills a row's missing ones with NULL rather than the
+            // default `columns` + `missing=default` would give them.
+            "columns" if rows_share_keys(state.body.as_ref()) => {}
+            "columns" => return Err(unsupported("bulk rows with differing columns")),
+            "order" => order.extend(compile_order(&decode(value)?)?),
+            "or" | "and" | "not.or" | "not.and" => return Err(unsupported("`or` / `and` filter groups")),
+            k if k.contains('.') => return Err(unsupported(&format!("embedded resource parameter `{k}`"))),
+            column => filters.push(compile_filter(column, value)?),
+        }
+    }
+    stmt.insert("filters".into(), filters.into());
+
+    let (limit, offset) = match state.range {
+        Some((from, to)) => (Some(to.saturating_sub(from).saturating_add(1)), Some(from)),
+        None => (state.limit, state.offset),
+    };
+    if !is_select && (!order.is_empty() || limit.is_some() || offset.is_some()) {
+        return Err(unsupported("order / limit / offset on write statements"));
+    }
+    if !order.is_empty() {
+        stmt.insert("order".into(), order.into());
+    }
+    if let Some(limit) = limit {
+        stmt.insert("limit".into(), limit.into());
+    }
+    if let Some(offset) = offset {
+        stmt.insert("offset".into(), offset.into());
+    }
+    Ok(Value::Object(stmt))
+}
+
+/// Do all the rows of a bulk body have the same keys?
+fn rows_share_keys(body: Option<&Value>) -> bool {
+    let Some(Value::Array(rows)) = body else {
+        return true;
+    };
+    let mut keys = rows
+        .iter()
+        .map(|row| row.as_object().map(|o| o.keys().collect::<BTreeSet<_>>()));
+    match keys.next() {
+        Some(first) => keys.all(|k| k == first),
+        None => true,
+    }
+}
+
+/// `null` for `*` (whole row), otherwise the plain column names.
+fn compile_columns(select: Option<&str>) -> Result<Value> {
+    let select = match select.map(str::trim) {
+        None | Some("") | Some("*") => return Ok(Value::Null),
+        Some(cols) => cols,
+    };
+    select
+        .split(',')
+        .map(|col| {
+            let col = col.trim();
+            if !col.is_empty() && col.chars().all(|c| c.is_alphanumeric() || c == '_') {
+                Ok(Value::from(col))
+            } else {
+                Err(unsupported(&format!("select item `{col}` (only plain columns)")))
+            }
+        })
+        .collect::<Result<Vec<_>>>()
+        .map(Value::Array)
+}
+
+fn compile_order(value: &str) -> Result<Vec<Value>> {
+    value
+        .split(',')
+        .map(|term| {
+            let mut parts = term.split('.');
+            let column = parts.next().unwrap_or_default();
+            let mut ascending = true;
+            let mut nulls_first = None;
+            for part in parts {
+                match part {
+                    "asc" => ascending = true,
+                    "desc" => ascending = false,
+                    "nullsfirst" => nulls_first = Some(true),
+                    "nullslast" => nulls_first = Some(false),
+                    other => return Err(unsupported(&format!("order modifier `{other}`"))),
+                }
+            }
+            // PostgreSQL's default: NULLS LAST for ASC, NULLS FIRST for DESC.
+            let nulls_first = nulls_first.unwrap_or(!ascending);
+            Ok(json!({ "column": column, "ascending": ascending, "nulls_first": nulls_first }))
+        })
+        .collect()
+}
+
+fn compile_filter(column: &str, raw: &str) -> Result<Value> {
+    let (negate, rest) = match raw.strip_prefix("not.") {
+        Some(rest) => (true, rest),
+        None => (false, raw),
+    };
+    let (op, encoded) = rest.split_once('.').unwrap_or((rest, ""));
+    let value = decode(encoded)?;
+    let value = match op {
+        "eq" | "neq" | "gt" | "gte" | "lt" | "lte" | "like" | "ilike" | "is" => Value::from(value),
+        "in" => parse_list(&value)?.into(),
+        other => return Err(unsupported(&format!("filter operator `{other}`"))),
+    };
+    Ok(json!({ "column": column, "op": op, "value": value, "negate": negate }))
+}
+
+/// Parse PostgREST's `(a,b,"c,d")` list syntax back into its elements.
+fn parse_list(list: &str) -> Result<Vec<String>> {
+    let inner = list
+        .strip_prefix('(')
+        .and_then(|l| l.strip_suffix(')'))
+        .ok_or_else(|| SupabaseError::Unexpected(format!("malformed `in` list: {list}")))?;
+    let mut items = Vec::new();
+    if inner.is_empty() {
+        return Ok(items);
+    }
+    let mut current = String::new();
+    let (mut quoted, mut escaped) = (false, false);
+    for c in inner.chars() {
+        match c {
+            _ if escaped => {
+                current.push(c);
+                escaped = false;
+            }
+            '\\' if quoted => escaped = true,
+            '"' => quoted = !quoted,
+            ',' if !quoted => items.push(std::mem::take(&mut current)),
+            _ => current.push(c),
+        }
+    }
+    items.push(current);
+    Ok(items)
+}
+
+fn decode(s: &str) -> Result<String> {
+    urlencoding::decode(s)
+        .map(|s| s.into_owned())
+        .map_err(|e| SupabaseError::Unexpected(format!("invalid query parameter `{s}`: {e}")))
+}
+
+fn unsupported(what: &str) -> SupabaseError {
+    SupabaseError::Unexpected(format!("{what} not supported inside a transaction"))
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+    use crate::postgrest::{Row, Writable};
+    use serde::Serialize;
+
+    fn client() -> SupabaseClient {
+        SupabaseClient::new("https://x.supabase.co", "anon", None)
+    }
+
+    #[test]
+    fn compiles_writes_with_filters() {
+        let c = client();
+        let stmt = compile(
+            &c.from("accounts")
+                .update(json!({"balance": 90}))
+                .eq("owner", "a b&c")
+                .not("status", "in", "(x,\"y,z\")")
+                .is("deleted_at", "null")
+                .select_returning("id,balance"),
+        )
+        .unwrap();
+        assert_eq!(
+            stmt,
+            json!({
+                "op": "update",
+                "schema": "public",
+                "table": "accounts",
+                "body": {"balance": 90},
+                "columns": ["id", "balance"],
+                "returning": true,
+                "filters": [
+                    {"column": "owner", "op": "eq", "value": "a b&c", "negate": false},
+                    {"column": "status", "op": "in", "value": ["x", "y,z"], "negate": true},
+                    {"column": "deleted_at", "op": "is", "value": "null", "negate": false}
+                ]
+            })
+        );
+    }
+
+    #[test]
+    fn compiles_select_paging_and_upsert_options() {
+        let c = client().schema("billing");
+        let stmt = compile(&c.from("invoices").select("*").order("created_at", false).range(10, 19)).unwrap();
+        assert_eq!(stmt["schema"], "billing");
+        assert_eq!(stmt["columns"], Value::Null);
+        assert_eq!(
+            stmt["order"],
+            json!([{"column": "created_at", "ascending": false, "nulls_first": true}])
+        );
+        assert_eq!((stmt["limit"].clone(), stmt["offset"].clone()), (json!(10), json!(10)));
+
+        let stmt = compile(
+            &c.from("t")
+                .upsert(json!([{"a": 1}]))
+                .on_conflict("a,b")
+                .ignore_duplicates(),
+        )
+        .unwrap();
+        assert_eq!(stmt["on_conflict"], json!(["a", "b"]));
+        assert_eq!(stmt["ignore_duplicates"], true);
+
+        let stmt = compile(&c.from("t").delete().is_in("id", Vec::<i32>::new())).unwrap();
+        assert_eq!(stmt["match_nothing"], true);
+    }
+
+    #[test]
+    fn rejects_what_sql_cannot_express() {
+        let c = client();
+        for query in [
+            c.from("t").select("*").or("a.eq.1,b.eq.2"),
+            c.from("t").select("id,author(name)"),
+            c.from("t").select("*").contains("tags", "{a}"),
+            c.from("t").insert_csv("a\n1"),
+            c.from("t").delete().limit(1),
+        ] {
+            let err = compile(&query).unwrap_err();
+            assert!(
+                matches!(&err, SupabaseError::Unexpected(m) if m.contains("not supported")),
+                "{err:?}"
+            );
+        }
+    }
+
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    struct Posts {
+        status: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        views: Option<i32>,
+    }
+
+    impl Row for Posts {
+        const TABLE: &'static str = "posts";
+    }
+
+    impl Writable for Posts {
+        type Insert = Posts;
+        type Update = Posts;
+    }
+
+    #[test]
+    fn compiles_typed_bulk_inserts() {
+        let post = |views| Posts { status: "draft".into(), views };
+        let c = client();
+        let same = c.from_row::<Posts>().insert_many([post(Some(1)), post(Some(2))]);
+        let stmt = compile(&same.into_untyped()).unwrap();
+        assert_eq!(
+            stmt["body"],
+            json!([{"status": "draft", "views": 1}, {"status": "draft", "views": 2}])
+        );
+        assert_eq!(stmt["filters"], json!([]));
+
+        let differing = c.from_row::<Posts>().upsert_many([post(None), post(Some(2))]);
+        let err = compile(&differing.into_untyped()).unwrap_err();
+        assert!(
+            matches!(&err, SupabaseError::Unexpected(m) if m.contains("differing columns")),
+            "{err:?}"
+        );
+    }
+
+    #[test]
+    fn rejects_updates_that_set_nothing() {
+        let c = client();
+        for body in [json!({}), json!([{"a": 1}])] {
+            let err = compile(&c.from("t").update(body).eq("id", 1)).unwrap_err();
+            assert!(
+                matches!(&err, SupabaseError::Unexpected(m) if m == "update sets no columns"),
+                "{err:?}"
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn surfaces_compile_errors_with_statement_index() {
+        let c = client();
+        let err = c
+            .transaction()
+            .push(c.from("t").delete().eq("id", 1))
+            .push(c.from("t").select("*").or("a.eq.1"))
+            .execute()
+            .await
+            .unwrap_err();
+        assert!(
+            matches!(&err, SupabaseError::Unexpected(m) if m.starts_with("transaction statement 1:")),
+            "{err:?}"
+        );
+        assert!(c.transaction().execute().await.unwrap().is_empty());
+    }
+}
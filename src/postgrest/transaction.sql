@@ -0,0 +1,174 @@
+-- Helper function for `SupabaseClient::transaction()`.
+--
+-- Apply once per database (e.g. as a migration). It runs every statement in
+-- the request's transaction, in order, and returns one
+-- `{"count": n, "rows": [...]}` object per statement. If any statement
+-- fails, the error is re-raised with `statement <n>` in its detail and
+-- PostgREST rolls the whole call back.
+--
+-- SECURITY INVOKER: row-level security and grants apply exactly as they do
+-- for the equivalent individual requests.
+
+create or replace function public.sdk_transaction(statements jsonb)
+returns jsonb
+language plpgsql
+security invoker
+set search_path = ''
+as $$
+declare
+  stmt jsonb;
+  f jsonb;
+  idx int := 0;
+  op text;
+  target text;
+  projection text;
+  cond text;
+  clause text;
+  body jsonb;
+  cols text[];
+  conflict text[];
+  query text;
+  result jsonb;
+  results jsonb := '[]'::jsonb;
+  err_state text;
+  err_message text;
+  err_detail text;
+  err_hint text;
+begin
+  for stmt in select value from jsonb_array_elements(statements) loop
+    begin
+      op := stmt->>'op';
+      target := format('%I.%I', stmt->>'schema', stmt->>'table');
+
+      if jsonb_typeof(stmt->'columns') = 'array' then
+        select 'jsonb_build_object(' || string_agg(format('%L, t.%I', c, c), ', ') || ')'
+          into projection
+          from jsonb_array_elements_text(stmt->'columns') c;
+      else
+        projection := 'to_jsonb(t.*)';
+      end if;
+
+      cond := 'true';
+      if coalesce((stmt->>'match_nothing')::boolean, false) then
+        cond := 'false';
+      end if;
+      for f in select value from jsonb_array_elements(coalesce(stmt->'filters', '[]'::jsonb)) loop
+        clause := case f->>'op'
+          when 'eq' then format('t.%I = %L', f->>'column', f->>'value')
+          when 'neq' then format('t.%I <> %L', f->>'column', f->>'value')
+          when 'gt' then format('t.%I > %L', f->>'column', f->>'value')
+          when 'gte' then format('t.%I >= %L', f->>'column', f->>'value')
+          when 'lt' then format('t.%I < %L', f->>'column', f->>'value')
+          when 'lte' then format('t.%I <= %L', f->>'column', f->>'value')
+          when 'like' then format('t.%I like %L', f->>'column', replace(f->>'value', '*', '%'))
+          when 'ilike' then format('t.%I ilike %L', f->>'column', replace(f->>'value', '*', '%'))
+          when 'is' then format('t.%I is %s', f->>'column',
+            case lower(f->>'value')
+              when 'null' then 'null' when 'true' then 'true'
+              when 'false' then 'false' when 'unknown' then 'unknown'
+            end)
+          when 'in' then (
+            select case when count(*) = 0 then 'false'
+                   else format('t.%I in (%s)', f->>'column', string_agg(format('%L', v), ', ')) end
+              from jsonb_array_elements_text(f->'value') v)
+        end;
+        if clause is null then
+          raise exception 'unsupported filter: %', f;
+        end if;
+        if coalesce((f->>'negate')::boolean, false) then
+          clause := format('not (%s)', clause);
+        end if;
+        cond := cond || ' and ' || clause;
+      end loop;
+
+      body := stmt->'body';
+      if op in ('insert', 'upsert') then
+        if jsonb_typeof(body) <> 'array' then
+          body := jsonb_build_array(body);
+        end if;
+        select array_agg(distinct k) into cols
+          from jsonb_array_elements(body) e, jsonb_object_keys(e) k;
+      elsif op = 'update' then
+        select array_agg(k) into cols from jsonb_object_keys(body) k;
+      end if;
+
+      if op = 'select' then
+        query := format('select %s as r from %s as t where %s', projection, target, cond);
+        if jsonb_typeof(stmt->'order') = 'array' then
+          query := query || ' order by ' || (
+            select string_agg(format('t.%I %s nulls %s', o->>'column',
+                     case when (o->>'ascending')::boolean then 'asc' else 'desc' end,
+                     case when (o->>'nulls_first')::boolean then 'first' else 'last' end), ', ')
+              from jsonb_array_elements(stmt->'order') o);
+        end if;
+        if stmt->>'limit' is not null then
+          query := query || format(' limit %s', (stmt->>'limit')::bigint);
+        end if;
+        if stmt->>'offset' is not null then
+          query := query || format(' offset %s', (stmt->>'offset')::bigint);
+        end if;
+      elsif op in ('insert', 'upsert') then
+        query := format(
+          'insert into %s as t (%s) select %s from jsonb_populate_recordset(null::%s, $1)',
+          target,
+          (select string_agg(format('%I', c), ', ') from unnest(cols) c),
+          (select string_agg(format('%I', c), ', ') from unnest(cols) c),
+          target);
+        if op = 'upsert' then
+          if jsonb_typeof(stmt->'on_conflict') = 'array' then
+            select array_agg(c) into conflict from jsonb_array_elements_text(stmt->'on_conflict') c;
+          else
+            select array_agg(a.attname::text) into conflict
+              from pg_catalog.pg_index i
+              join pg_catalog.pg_attribute a on a.attrelid = i.indrelid and a.attnum = any(i.indkey)
+             where i.indrelid = target::regclass and i.indisprimary;
+          end if;
+          query := query || format(' on conflict (%s) ',
+            (select string_agg(format('%I', c), ', ') from unnest(conflict) c));
+          if coalesce((stmt->>'ignore_duplicates')::boolean, false) then
+            query := query || 'do nothing';
+          else
+            query := query || 'do update set ' || (
+              select string_agg(format('%I = excluded.%I', c, c), ', ') from unnest(cols) c);
+          end if;
+        end if;
+        query := query || format(' returning %s as r', projection);
+      elsif op = 'update' then
+        query := format(
+          'update %s as t set %s from jsonb_populate_record(null::%s, $1) as src where %s returning %s as r',
+          target,
+          (select string_agg(format('%I = src.%I', c, c), ', ') from unnest(cols) c),
+          target, cond, projection);
+      elsif op = 'delete' then
+        query := format('delete from %s as t where %s returning %s as r', target, cond, projection);
+      else
+        raise exception 'unsupported statement: %', op;
+      end if;
+
+      execute format(
+        'with w as (%s) select jsonb_build_object(''count'', count(*), ''rows'', %s) from w',
+        query,
+        case when op = 'select' or coalesce((stmt->>'returning')::boolean, false)
+          then 'coalesce(jsonb_agg(w.r), ''[]''::jsonb)'
+          else '''[]''::jsonb'
+        end)
+        into result
+        using body;
+    exception when others then
+      get stacked diagnostics
+        err_state = returned_sqlstate,
+        err_message = message_text,
+        err_detail = pg_exception_detail,
+        err_hint = pg_exception_hint;
+      err_detail := format('statement %s: %s', idx, coalesce(nullif(err_detail, ''), op));
+      if coalesce(err_hint, '') = '' then
+        raise exception using errcode = err_state, message = err_message, detail = err_detail;
+      end if;
+      raise exception using errcode = err_state, message = err_message, detail = err_detail, hint = err_hint;
+    end;
+    results := results || jsonb_build_array(result);
+    idx := idx + 1;
+  end loop;
+  return results;
+end;
+$$;
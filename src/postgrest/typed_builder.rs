@@ -10,7 +10,12 @@
 //!   * value-type match — `eq(Posts::view_count, "abc")` is a compile error
 //!     because `view_count` is `Column<Posts, i32>`,
 //!   * nullability — `is_null` only accepts `Column<R, Option<V>>`,
-//!   * `like`/`ilike` only accept string-typed columns.
+//!   * `like`/`ilike` only accept string-typed columns,
+//!   * range, array and full-text operators only accept columns whose value
+//!     type is a [`PgRange`], a `Vec` or [`TextSearchable`] respectively,
+//!   * writes — `insert` / `upsert` only take `R`'s
+//!     [`Writable::Insert`] payload and `update` only its
+//!     [`Writable::Update`] one.
 //!
 //! Internally this just funnels through [`PostgrestBuilder<Value>`] — the
 //! type-safety layer is purely at the front door. Anything you can't express
@@ -27,19 +32,27 @@ use serde_json::Value;
 use crate::error::Result;
 
 use super::builder::{
-    CountMode, MaybeSingleBuilder, Order, PostgrestBuilder, SingleBuilder, TableBuilder,
-    TextSearchType,
+    serialize_body, CountMode, MaybeSingleBuilder, Operation, Order, PostgrestBuilder,
+    SingleBuilder, TableBuilder, TextSearchType,
 };
 use super::column::{Column, IntoColumnName};
-use super::row::Row;
+use super::cursor::{Cursor, CursorBuilder};
+use super::paginate::Paginate;
+use super::projection::Projection;
+use super::row::{Row, Writable};
+use super::types::{render_array, ArrayType, PgRange, RangeType, TextSearchable};
+use super::value::encode_value;
 
 /// Type-safe PostgREST query builder bound to row type `R`.
 ///
 /// Construct via [`SupabaseClient::from_row::<R>()`](crate::SupabaseClient::from_row).
+/// `O` is the type each result row deserializes into; it stays `R` until an
+/// [`embed`](TypedBuilder::embed) switches it to the composite
+/// [`Embeddable::WithRelations`](crate::postgrest::Embeddable::WithRelations).
 #[must_use = "TypedBuilder is lazy — `.await` it or call `.execute()`"]
-pub struct TypedBuilder<R: Row> {
-    inner: PostgrestBuilder<Value>,
-    _row: std::marker::PhantomData<fn() -> R>,
+pub struct TypedBuilder<R: Row, O = R> {
+    pub(crate) inner: PostgrestBuilder<Value>,
+    pub(crate) _row: std::marker::PhantomData<fn() -> (R, O)>,
 }
 
 impl<R: Row> TypedBuilder<R> {
@@ -49,7 +62,9 @@ impl<R: Row> TypedBuilder<R> {
             _row: std::marker::PhantomData,
         }
     }
+}
 
+impl<R: Row, O> TypedBuilder<R, O> {
     /// Replace the column projection. `columns` follows PostgREST syntax
     /// (`"id,name"`, `"*"`, or `"id,name,profile(*)"`). Punted from the
     /// initial typed-columns work — wrong column names here just produce
@@ -246,6 +261,166 @@ impl<R: Row> TypedBuilder<R> {
         self
     }
 
+    // ---- Range operators — range-typed columns only ----
+
+    /// `column << range` — strictly left of `range`.
+    pub fn range_lt<V>(mut self, col: Column<R, V>, range: PgRange<V::Element>) -> Self
+    where
+        V: RangeType,
+        V::Element: std::fmt::Display,
+    {
+        self.inner = self.inner.range_lt(col.name(), range);
+        self
+    }
+
+    /// `column >> range` — strictly right of `range`.
+    pub fn range_gt<V>(mut self, col: Column<R, V>, range: PgRange<V::Element>) -> Self
+    where
+        V: RangeType,
+        V::Element: std::fmt::Display,
+    {
+        self.inner = self.inner.range_gt(col.name(), range);
+        self
+    }
+
+    /// `column &< range` — does not extend to the right of `range`.
+    pub fn range_lte<V>(mut self, col: Column<R, V>, range: PgRange<V::Element>) -> Self
+    where
+        V: RangeType,
+        V::Element: std::fmt::Display,
+    {
+        self.inner = self.inner.range_lte(col.name(), range);
+        self
+    }
+
+    /// `column &> range` — does not extend to the left of `range`.
+    pub fn range_gte<V>(mut self, col: Column<R, V>, range: PgRange<V::Element>) -> Self
+    where
+        V: RangeType,
+        V::Element: std::fmt::Display,
+    {
+        self.inner = self.inner.range_gte(col.name(), range);
+        self
+    }
+
+    /// `column -|- range` — adjacent to `range`.
+    pub fn range_adjacent<V>(mut self, col: Column<R, V>, range: PgRange<V::Element>) -> Self
+    where
+        V: RangeType,
+        V::Element: std::fmt::Display,
+    {
+        self.inner = self.inner.range_adjacent(col.name(), range);
+        self
+    }
+
+    /// `column && range` — shares at least one point with `range`.
+    pub fn range_overlaps<V>(mut self, col: Column<R, V>, range: PgRange<V::Element>) -> Self
+    where
+        V: RangeType,
+        V::Element: std::fmt::Display,
+    {
+        self.inner = self.inner.overlaps(col.name(), range);
+        self
+    }
+
+    // ---- Array operators — array-typed columns only ----
+
+    /// `column @> '{values}'` — the array holds every one of `values`.
+    pub fn array_contains<V, I>(mut self, col: Column<R, V>, values: I) -> Self
+    where
+        V: ArrayType,
+        V::Element: std::fmt::Display,
+        I: IntoIterator<Item = V::Element>,
+    {
+        self.inner = self.inner.contains(col.name(), render_array(values));
+        self
+    }
+
+    /// `column <@ '{values}'` — every element of the array is in `values`.
+    pub fn array_contained_by<V, I>(mut self, col: Column<R, V>, values: I) -> Self
+    where
+        V: ArrayType,
+        V::Element: std::fmt::Display,
+        I: IntoIterator<Item = V::Element>,
+    {
+        self.inner = self.inner.contained_by(col.name(), render_array(values));
+        self
+    }
+
+    /// `column && '{values}'` — the array shares an element with `values`.
+    pub fn array_overlaps<V, I>(mut self, col: Column<R, V>, values: I) -> Self
+    where
+        V: ArrayType,
+        V::Element: std::fmt::Display,
+        I: IntoIterator<Item = V::Element>,
+    {
+        self.inner = self.inner.overlaps(col.name(), render_array(values));
+        self
+    }
+
+    // ---- Regex / distinctness ----
+
+    /// `column ~ pattern` — POSIX regex match. String columns only.
+    pub fn regex_match(mut self, col: Column<R, String>, pattern: impl AsRef<str>) -> Self {
+        self.inner = self.inner.filter(col.name(), "match", pattern.as_ref());
+        self
+    }
+
+    /// `column ~* pattern` — case-insensitive POSIX regex match.
+    pub fn regex_imatch(mut self, col: Column<R, String>, pattern: impl AsRef<str>) -> Self {
+        self.inner = self.inner.filter(col.name(), "imatch", pattern.as_ref());
+        self
+    }
+
+    /// `column IS DISTINCT FROM value` — like `neq`, but a `NULL` column
+    /// counts as different from a value (and `None` matches non-null rows).
+    /// Only allowed on nullable columns; elsewhere it is just [`neq`].
+    ///
+    /// [`neq`]: TypedBuilder::neq
+    pub fn is_distinct<V>(mut self, col: Column<R, Option<V>>, val: Option<V>) -> Self
+    where
+        V: Serialize + std::fmt::Display,
+    {
+        let rendered = val.map_or_else(|| "null".to_string(), |v| v.to_string());
+        self.inner = self.inner.filter(col.name(), "isdistinct", rendered);
+        self
+    }
+
+    /// Equality on every field of `criteria`, a [`Projection`] of `R`
+    /// that also implements `Serialize`. Each field filters the column it
+    /// projects (`headline: String = title` filters `title`); `None` fields
+    /// become `IS NULL`.
+    pub fn match_<P: Projection<R> + Serialize>(mut self, criteria: &P) -> Self {
+        let map = match serde_json::to_value(criteria) {
+            Ok(Value::Object(map)) => map,
+            Ok(_) => {
+                self.inner.state.build_error =
+                    Some("match_ criteria must serialize to a JSON object".to_string());
+                return self;
+            }
+            Err(e) => {
+                self.inner.state.build_error = Some(format!("match_ criteria: {e}"));
+                return self;
+            }
+        };
+        for (key, value) in map {
+            // Only reachable through serde attributes that rename or
+            // flatten fields behind the projection's back.
+            let Some(field) = P::FIELDS.iter().find(|f| f.alias() == key) else {
+                self.inner.state.build_error =
+                    Some(format!("match_ criteria field `{key}` is not a projected column"));
+                return self;
+            };
+            let column = field.column();
+            self.inner = match value {
+                Value::Null => self.inner.is(column, "null"),
+                Value::String(s) => self.inner.eq(column, s),
+                other => self.inner.eq(column, other.to_string()),
+            };
+        }
+        self
+    }
+
     // ---- Negation variants of the common filters ----
 
     /// `column <> value` (alias of [`neq`]).
@@ -328,14 +503,20 @@ impl<R: Row> TypedBuilder<R> {
         self
     }
 
+    /// Skip the client's [query cache](super::QueryCache) for this request.
+    pub fn no_cache(mut self) -> Self {
+        self.inner = self.inner.no_cache();
+        self
+    }
+
     // -----------------------------------------------------------------
-    // Text search — string columns only.
+    // Text search — tsvector columns only.
     // -----------------------------------------------------------------
 
-    /// `to_tsquery`-style full-text search on a string column.
-    pub fn text_search(
+    /// `to_tsquery`-style full-text search on a `tsvector` column.
+    pub fn text_search<V: TextSearchable>(
         mut self,
-        col: Column<R, String>,
+        col: Column<R, V>,
         query: &str,
         kind: TextSearchType,
         config: Option<&str>,
@@ -343,36 +524,197 @@ impl<R: Row> TypedBuilder<R> {
         self.inner = self.inner.text_search(col.name(), query, kind, config);
         self
     }
+}
+
+impl<R: Row> TypedBuilder<R> {
+    /// Keyset-paginate ascending on `col`, starting after `cursor` (or from
+    /// the beginning when `None`). Replaces any earlier `order`. Add
+    /// [`CursorBuilder::tie_breaker`] when `col` isn't unique.
+    pub fn after<V>(
+        self,
+        col: Column<R, V>,
+        cursor: impl Into<Option<Cursor>>,
+    ) -> CursorBuilder<R> {
+        CursorBuilder::new(self.inner, col, true, cursor.into())
+    }
+
+    /// Keyset-paginate descending on `col`, starting before `cursor` (or
+    /// from the end when `None`). See [`after`](TypedBuilder::after).
+    pub fn before<V>(
+        self,
+        col: Column<R, V>,
+        cursor: impl Into<Option<Cursor>>,
+    ) -> CursorBuilder<R> {
+        CursorBuilder::new(self.inner, col, false, cursor.into())
+    }
+}
+
+// ---------------------------------------------------------------------
+// Writes — payload types come from `Writable`, rows come back as `R`.
+// ---------------------------------------------------------------------
+
+impl<R: Writable> TypedBuilder<R> {
+    /// `INSERT` one row. The inserted row comes back
+    /// (`Prefer: return=representation`) with its defaults filled in.
+    pub fn insert(self, row: R::Insert) -> Self {
+        self.write(Operation::Insert, row)
+    }
+
+    /// `INSERT` several rows in one request. Rows may leave out different
+    /// optional columns; those take the column default
+    /// (`Prefer: missing=default`) rather than `NULL`.
+    pub fn insert_many<I>(self, rows: I) -> Self
+    where
+        I: IntoIterator<Item = R::Insert>,
+    {
+        self.write(Operation::Insert, rows.into_iter().collect::<Vec<_>>())
+    }
+
+    /// `UPSERT` one row, merging into an existing row on conflict. Set the
+    /// conflict target with [`on_conflict`](TypedBuilder::on_conflict) when
+    /// it isn't the primary key.
+    pub fn upsert(self, row: R::Insert) -> Self {
+        self.write(Operation::Upsert, row)
+    }
+
+    /// `UPSERT` several rows in one request. See
+    /// [`insert_many`](TypedBuilder::insert_many).
+    pub fn upsert_many<I>(self, rows: I) -> Self
+    where
+        I: IntoIterator<Item = R::Insert>,
+    {
+        self.write(Operation::Upsert, rows.into_iter().collect::<Vec<_>>())
+    }
+
+    /// `UPDATE` the matching rows, writing only the columns set in `patch`.
+    /// Filters apply as usual — add them before or after, but without any the
+    /// update hits every row.
+    pub fn update(self, patch: R::Update) -> Self {
+        self.write(Operation::Update, patch)
+    }
+
+    /// Upsert conflict target. Call once per column for a composite target.
+    pub fn on_conflict(mut self, col: impl IntoColumnName<R>) -> Self {
+        let col = encode_value(&col.into_column_name());
+        match self.inner.state.params.iter_mut().find(|(k, _)| k == "on_conflict") {
+            Some((_, existing)) => {
+                existing.push_str("%2C");
+                existing.push_str(&col);
+            }
+            None => self.inner.push_param("on_conflict", col),
+        }
+        self
+    }
+
+    /// Upsert: skip rows that conflict instead of merging into them.
+    pub fn ignore_duplicates(mut self) -> Self {
+        self.inner = self.inner.ignore_duplicates();
+        self
+    }
 
+    fn write<B: Serialize>(mut self, op: Operation, body: B) -> Self {
+        let (body, err) = serialize_body(body);
+        let q = &mut self.inner;
+        q.op = op;
+        q.state.body_error = err;
+        if op == Operation::Upsert {
+            q.state.prefer.push("resolution=merge-duplicates".to_string());
+        }
+        // PostgREST requires every object of a bulk body to have the same
+        // keys unless `columns` names them up front.
+        if let Some(Value::Array(rows)) = &body {
+            let mut columns: Vec<&str> = Vec::new();
+            for key in rows.iter().filter_map(Value::as_object).flat_map(|row| row.keys()) {
+                if !columns.contains(&key.as_str()) {
+                    columns.push(key);
+                }
+            }
+            let columns = encode_value(&columns.join(","));
+            q.push_param("columns", columns);
+            q.state.prefer.push("missing=default".to_string());
+        }
+        q.state.body = body;
+        self.returning()
+    }
+}
+
+impl<R: Row> TypedBuilder<R> {
+    /// `DELETE` the matching rows and return them. Filters apply as usual —
+    /// without any the delete hits every row.
+    pub fn delete(mut self) -> Self {
+        self.inner.op = Operation::Delete;
+        self.returning()
+    }
+
+    /// Ask for the written rows back, keeping any narrower `select`.
+    fn returning(mut self) -> Self {
+        let cols = self.inner.state.select_cols.take().unwrap_or_else(|| "*".to_string());
+        self.inner = self.inner.select_returning(cols);
+        self
+    }
+}
+
+impl<R: Row, O: DeserializeOwned + Send + 'static> TypedBuilder<R, O> {
     // -----------------------------------------------------------------
     // Execution — same shapes as the untyped builder, but the default
     // typed-row hint is preserved.
     // -----------------------------------------------------------------
 
-    /// Execute and deserialize each row into `R`. This is the typed path —
+    /// Stream every matching row as `O`, `page_size` rows per request,
+    /// using offset paging. See [`PostgrestBuilder::paginate`].
+    pub fn paginate(self, page_size: u64) -> Paginate<O> {
+        self.inner.returns::<O>().paginate(page_size)
+    }
+
+    /// Stream every matching row as `O` using keyset paging on `col`. See
+    /// [`PostgrestBuilder::paginate_keyset`].
+    pub fn paginate_keyset(self, page_size: u64, col: impl IntoColumnName<R>) -> Paginate<O> {
+        self.inner
+            .returns::<O>()
+            .paginate_keyset(page_size, &col.into_column_name())
+    }
+
+    /// Execute and deserialize each row into `O` (`R` unless an embed
+    /// switched the output to `R::WithRelations`). This is the typed path —
     /// no `.returns::<T>()` needed.
-    pub async fn execute(self) -> Result<Vec<R>> {
-        self.inner.returns::<R>().execute().await
+    pub async fn execute(self) -> Result<Vec<O>> {
+        self.inner.returns::<O>().execute().await
     }
 
     /// Execute and return both `(rows, count)` — useful with [`count`].
     ///
     /// [`count`]: TypedBuilder::count
-    pub async fn execute_with_count(self) -> Result<(Vec<R>, Option<u64>)> {
+    pub async fn execute_with_count(self) -> Result<(Vec<O>, Option<u64>)> {
         self.inner
-            .returns::<R>()
+            .returns::<O>()
             .execute_with_count()
             .await
     }
 
+    /// Count the matching rows with a `HEAD` request, downloading none.
+    /// See [`PostgrestBuilder::head`].
+    pub async fn head(self) -> Result<u64> {
+        self.inner.head().await
+    }
+
+    /// Count the matching rows with `mode` and a `HEAD` request.
+    pub async fn count_only(self, mode: CountMode) -> Result<u64> {
+        self.inner.count_only(mode).await
+    }
+
+    /// `true` when at least one row matches. Fetches with `limit=1`.
+    pub async fn exists(self) -> Result<bool> {
+        self.inner.exists().await
+    }
+
     /// Expect exactly one row; error otherwise.
-    pub fn single(self) -> SingleBuilder<R> {
-        self.inner.returns::<R>().single()
+    pub fn single(self) -> SingleBuilder<O> {
+        self.inner.returns::<O>().single()
     }
 
     /// Expect zero or one row.
-    pub fn maybe_single(self) -> MaybeSingleBuilder<R> {
-        self.inner.returns::<R>().maybe_single()
+    pub fn maybe_single(self) -> MaybeSingleBuilder<O> {
+        self.inner.returns::<O>().maybe_single()
     }
 
     /// Override the return type — useful for `select("count")`-style
@@ -394,8 +736,8 @@ impl<R: Row> TypedBuilder<R> {
     }
 }
 
-impl<R: Row + DeserializeOwned + Send + 'static> IntoFuture for TypedBuilder<R> {
-    type Output = Result<Vec<R>>;
+impl<R: Row, O: DeserializeOwned + Send + 'static> IntoFuture for TypedBuilder<R, O> {
+    type Output = Result<Vec<O>>;
     type IntoFuture = Pin<Box<dyn Future<Output = Self::Output> + Send>>;
 
     fn into_future(self) -> Self::IntoFuture {
@@ -410,6 +752,7 @@ impl<R: Row + DeserializeOwned + Send + 'static> IntoFuture for TypedBuilder<R>
 #[allow(clippy::unwrap_used)]
 mod tests {
     use super::*;
+    use crate::postgrest::TsVector;
     use crate::SupabaseClient;
     use serde::{Deserialize, Serialize};
 
@@ -421,6 +764,9 @@ mod tests {
         view_count: i32,
         archived: Option<bool>,
         published_at: Option<String>,
+        during: Option<PgRange<i32>>,
+        tags: Vec<String>,
+        search: TsVector,
     }
 
     impl Row for Posts {
@@ -434,6 +780,9 @@ mod tests {
         pub const view_count: Column<Posts, i32> = Column::new("view_count");
         pub const archived: Column<Posts, Option<bool>> = Column::new("archived");
         pub const published_at: Column<Posts, Option<String>> = Column::new("published_at");
+        pub const during: Column<Posts, Option<PgRange<i32>>> = Column::new("during");
+        pub const tags: Column<Posts, Vec<String>> = Column::new("tags");
+        pub const search: Column<Posts, TsVector> = Column::new("search");
     }
 
     fn client() -> SupabaseClient {
@@ -674,6 +1023,180 @@ mod tests {
         assert!(p.contains("status=ov."), "p={p}");
     }
 
+    #[test]
+    fn range_ops_render_range_literals() {
+        let p = client()
+            .from_row::<Posts>()
+            .range_lt(Posts::during, PgRange::from(1..10))
+            .range_gt(Posts::during, PgRange::from(..0))
+            .range_lte(Posts::during, PgRange::from(5..))
+            .range_gte(Posts::during, PgRange::from(1..=2))
+            .range_adjacent(Posts::during, PgRange::from(10..20))
+            .range_overlaps(Posts::during, PgRange::empty())
+            .build_path();
+        assert_eq!(
+            urlencoding::decode(&p).unwrap(),
+            "/rest/v1/posts?select=*&during=sl.[1,10)&during=sr.(,0)&during=nxr.[5,)\
+             &during=nxl.[1,2]&during=adj.[10,20)&during=ov.empty"
+        );
+    }
+
+    #[test]
+    fn array_ops_render_array_literals() {
+        let p = client()
+            .from_row::<Posts>()
+            .array_contains(Posts::tags, ["rust".to_string(), "sdk tools".to_string()])
+            .array_contained_by(Posts::tags, vec!["a".to_string()])
+            .array_overlaps(Posts::tags, ["x,y".to_string()])
+            .build_path();
+        assert_eq!(
+            urlencoding::decode(&p).unwrap(),
+            r#"/rest/v1/posts?select=*&tags=cs.{rust,"sdk tools"}&tags=cd.{a}&tags=ov.{"x,y"}"#
+        );
+    }
+
+    #[test]
+    fn regex_and_distinct_ops() {
+        let p = client()
+            .from_row::<Posts>()
+            .regex_match(Posts::status, "^pub")
+            .regex_imatch(Posts::id, "a.c")
+            .is_distinct(Posts::archived, Some(true))
+            .is_distinct(Posts::published_at, None)
+            .build_path();
+        assert_eq!(
+            urlencoding::decode(&p).unwrap(),
+            "/rest/v1/posts?select=*&status=match.^pub&id=imatch.a.c\
+             &archived=isdistinct.true&published_at=isdistinct.null"
+        );
+    }
+
+    crate::projection! {
+        #[derive(Serialize, Deserialize)]
+        struct StatusFilter: Posts {
+            status: String,
+            views: i32 = view_count,
+            archived: Option<bool>,
+        }
+    }
+
+    crate::projection! {
+        #[derive(Serialize, Deserialize)]
+        struct RenamedFilter: Posts {
+            #[serde(rename = "state")]
+            status: String,
+        }
+    }
+
+    #[test]
+    fn match_filters_each_projected_column() {
+        let p = client()
+            .from_row::<Posts>()
+            .match_(&StatusFilter {
+                status: "draft".to_string(),
+                views: 3,
+                archived: None,
+            })
+            .build_path();
+        assert_eq!(
+            urlencoding::decode(&p).unwrap(),
+            "/rest/v1/posts?select=*&archived=is.null&status=eq.draft&view_count=eq.3"
+        );
+
+        let q = client().from_row::<Posts>().match_(&RenamedFilter {
+            status: "draft".to_string(),
+        });
+        assert_eq!(
+            q.inner.state.build_error.as_deref(),
+            Some("match_ criteria field `state` is not a projected column")
+        );
+    }
+
+    // ---- writes ----
+
+    #[derive(Debug, Clone, Serialize)]
+    struct PostsInsert {
+        status: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        view_count: Option<i32>,
+    }
+
+    #[derive(Debug, Clone, Default, Serialize)]
+    struct PostsUpdate {
+        #[serde(skip_serializing_if = "Option::is_none")]
+        status: Option<String>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        archived: Option<Option<bool>>,
+    }
+
+    impl Writable for Posts {
+        type Insert = PostsInsert;
+        type Update = PostsUpdate;
+    }
+
+    fn draft(view_count: Option<i32>) -> PostsInsert {
+        PostsInsert {
+            status: "draft".to_string(),
+            view_count,
+        }
+    }
+
+    #[test]
+    fn insert_returns_the_written_rows() {
+        let q = client().from_row::<Posts>().insert(draft(None));
+        assert_eq!(q.inner.op, Operation::Insert);
+        assert_eq!(q.build_path(), "/rest/v1/posts?select=%2A");
+        assert_eq!(q.inner.state.body, Some(serde_json::json!({ "status": "draft" })));
+        assert!(q.inner.build_options().prefer.contains(&"return=representation".to_string()));
+    }
+
+    #[test]
+    fn insert_many_names_the_union_of_columns() {
+        let q = client()
+            .from_row::<Posts>()
+            .select("id")
+            .insert_many([draft(None), draft(Some(3))]);
+        assert_eq!(q.build_path(), "/rest/v1/posts?select=id&columns=status%2Cview_count");
+        let prefer = q.inner.build_options().prefer;
+        assert!(prefer.contains(&"missing=default".to_string()), "{prefer:?}");
+    }
+
+    #[test]
+    fn update_keeps_filters_and_writes_only_set_columns() {
+        let q = client()
+            .from_row::<Posts>()
+            .eq(Posts::status, "draft".to_string())
+            .update(PostsUpdate {
+                archived: Some(None),
+                ..Default::default()
+            })
+            .gt(Posts::view_count, 10);
+        assert_eq!(q.inner.op, Operation::Update);
+        assert_eq!(
+            q.build_path(),
+            "/rest/v1/posts?select=%2A&status=eq.draft&view_count=gt.10"
+        );
+        assert_eq!(q.inner.state.body, Some(serde_json::json!({ "archived": null })));
+    }
+
+    #[test]
+    fn upsert_and_delete_return_rows() {
+        let q = client()
+            .from_row::<Posts>()
+            .upsert(draft(Some(1)))
+            .on_conflict(Posts::status)
+            .on_conflict(Posts::view_count);
+        assert_eq!(q.inner.op, Operation::Upsert);
+        assert!(q.build_path().ends_with("on_conflict=status%2Cview_count"));
+        let prefer = q.inner.build_options().prefer;
+        assert!(prefer.contains(&"resolution=merge-duplicates".to_string()));
+
+        let q = client().from_row::<Posts>().eq(Posts::id, "p1".to_string()).delete();
+        assert_eq!(q.inner.op, Operation::Delete);
+        assert_eq!(q.build_path(), "/rest/v1/posts?select=%2A&id=eq.p1");
+        assert!(q.inner.build_options().prefer.contains(&"return=representation".to_string()));
+    }
+
     // ---- ordering / pagination ----
 
     #[test]
@@ -750,9 +1273,18 @@ mod tests {
     fn text_search_emits_fts_op() {
         let p = client()
             .from_row::<Posts>()
-            .text_search(Posts::status, "rust|sdk", TextSearchType::Plain, None)
+            .text_search(Posts::search, "rust|sdk", TextSearchType::Plain, None)
+            .build_path();
+        assert!(p.contains("search=plfts."), "p={p}");
+    }
+
+    #[test]
+    fn text_search_accepts_tsvector_columns() {
+        let p = client()
+            .from_row::<Posts>()
+            .text_search(Posts::search, "rust", TextSearchType::WebSearch, Some("english"))
             .build_path();
-        assert!(p.contains("status=plfts."), "p={p}");
+        assert!(p.contains("search=wfts(english).rust"), "p={p}");
     }
 
     // ---- conversion / escape hatch ----
@@ -0,0 +1,301 @@
+//! Rust types for Postgres column types without a std equivalent, and the
+//! marker traits the typed builder uses to gate type-specific operators.
+//!
+//! * [`PgRange<T>`] — `int4range`, `numrange`, `daterange`, … Range
+//!   operators (`range_lt`, `range_adjacent`, …) only compile on columns
+//!   whose value type implements [`RangeType`].
+//! * [`TsVector`] — `tsvector`. `text_search` only compiles on
+//!   [`TextSearchable`] columns.
+//! * [`ArrayType`] — `Vec<T>` columns, for `array_contains` and friends.
+
+use std::fmt;
+use std::ops::{Bound, Range, RangeFrom, RangeInclusive, RangeTo};
+use std::str::FromStr;
+
+use serde::de::{self, Deserializer};
+use serde::{Deserialize, Serialize, Serializer};
+
+const RANGE_DELIMITERS: &str = ",()[]";
+const ARRAY_DELIMITERS: &str = ",{}";
+
+/// A Postgres range value, e.g. `[1,10)` or `["2024-01-01","2024-02-01")`.
+///
+/// Serializes to and from Postgres' text form, which is what PostgREST sends
+/// and accepts. Build one from a Rust range (`PgRange::from(1..10)` is
+/// `[1,10)`, `PgRange::from(1..=10)` is `[1,10]`) or with [`PgRange::new`]
+/// for arbitrary bounds.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PgRange<T> {
+    lower: Bound<T>,
+    upper: Bound<T>,
+    empty: bool,
+}
+
+impl<T> PgRange<T> {
+    /// A range with explicit bounds.
+    pub fn new(lower: Bound<T>, upper: Bound<T>) -> Self {
+        Self {
+            lower,
+            upper,
+            empty: false,
+        }
+    }
+
+    /// The empty range (`empty`).
+    pub fn empty() -> Self {
+        Self {
+            lower: Bound::Unbounded,
+            upper: Bound::Unbounded,
+            empty: true,
+        }
+    }
+
+    pub fn lower(&self) -> Bound<&T> {
+        self.lower.as_ref()
+    }
+
+    pub fn upper(&self) -> Bound<&T> {
+        self.upper.as_ref()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.empty
+    }
+}
+
+impl<T> From<Range<T>> for PgRange<T> {
+    fn from(r: Range<T>) -> Self {
+        Self::new(Bound::Included(r.start), Bound::Excluded(r.end))
+    }
+}
+
+impl<T> From<RangeInclusive<T>> for PgRange<T> {
+    fn from(r: RangeInclusive<T>) -> Self {
+        let (start, end) = r.into_inner();
+        Self::new(Bound::Included(start), Bound::Included(end))
+    }
+}
+
+impl<T> From<RangeFrom<T>> for PgRange<T> {
+    fn from(r: RangeFrom<T>) -> Self {
+        Self::new(Bound::Included(r.start), Bound::Unbounded)
+    }
+}
+
+impl<T> From<RangeTo<T>> for PgRange<T> {
+    fn from(r: RangeTo<T>) -> Self {
+        Self::new(Bound::Unbounded, Bound::Excluded(r.end))
+    }
+}
+
+impl<T: fmt::Display> fmt::Display for PgRange<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.empty {
+            return f.write_str("empty");
+        }
+        let (open, lower) = match &self.lower {
+            Bound::Included(v) => ('[', quote_element(&v.to_string(), RANGE_DELIMITERS)),
+            Bound::Excluded(v) => ('(', quote_element(&v.to_string(), RANGE_DELIMITERS)),
+            Bound::Unbounded => ('(', String::new()),
+        };
+        let (close, upper) = match &self.upper {
+            Bound::Included(v) => (']', quote_element(&v.to_string(), RANGE_DELIMITERS)),
+            Bound::Excluded(v) => (')', quote_element(&v.to_string(), RANGE_DELIMITERS)),
+            Bound::Unbounded => (')', String::new()),
+        };
+        write!(f, "{open}{lower},{upper}{close}")
+    }
+}
+
+impl<T: FromStr> FromStr for PgRange<T>
+where
+    T::Err: fmt::Display,
+{
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        if s.eq_ignore_ascii_case("empty") {
+            return Ok(Self::empty());
+        }
+        let inclusive_lower = match s.chars().next() {
+            Some('[') => true,
+            Some('(') => false,
+            _ => return Err(format!("invalid range literal: {s}")),
+        };
+        let inclusive_upper = match s.chars().last() {
+            Some(']') if s.len() > 1 => true,
+            Some(')') if s.len() > 1 => false,
+            _ => return Err(format!("invalid range literal: {s}")),
+        };
+        let parts = split_elements(&s[1..s.len() - 1]);
+        let [lower, upper] = parts.as_slice() else {
+            return Err(format!("invalid range literal: {s}"));
+        };
+        let bound = |part: &Option<String>, inclusive: bool| -> Result<Bound<T>, String> {
+            match part {
+                None => Ok(Bound::Unbounded),
+                Some(raw) => {
+                    let v = raw.parse::<T>().map_err(|e| format!("invalid range bound {raw:?}: {e}"))?;
+                    Ok(if inclusive { Bound::Included(v) } else { Bound::Excluded(v) })
+                }
+            }
+        };
+        Ok(Self::new(bound(lower, inclusive_lower)?, bound(upper, inclusive_upper)?))
+    }
+}
+
+impl<T: fmt::Display> Serialize for PgRange<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de, T: FromStr> Deserialize<'de> for PgRange<T>
+where
+    T::Err: fmt::Display,
+{
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let raw = String::deserialize(deserializer)?;
+        raw.parse().map_err(de::Error::custom)
+    }
+}
+
+/// A `tsvector` value, kept as Postgres' text form (`'fat':2 'rat':3`).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct TsVector(pub String);
+
+impl fmt::Display for TsVector {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/// Column value types that are ranges. `Element` is the bound type.
+pub trait RangeType {
+    type Element;
+}
+
+impl<T> RangeType for PgRange<T> {
+    type Element = T;
+}
+
+impl<T: RangeType> RangeType for Option<T> {
+    type Element = T::Element;
+}
+
+/// Column value types that are Postgres arrays. `Element` is the item type.
+pub trait ArrayType {
+    type Element;
+}
+
+impl<T> ArrayType for Vec<T> {
+    type Element = T;
+}
+
+impl<T: ArrayType> ArrayType for Option<T> {
+    type Element = T::Element;
+}
+
+/// Column value types full-text search applies to: `tsvector`.
+pub trait TextSearchable {}
+
+impl TextSearchable for TsVector {}
+impl<T: TextSearchable> TextSearchable for Option<T> {}
+
+/// Render values as a Postgres array literal, `{a,"b c"}`.
+pub(crate) fn render_array<I, V>(values: I) -> String
+where
+    I: IntoIterator<Item = V>,
+    V: fmt::Display,
+{
+    let parts: Vec<String> = values
+        .into_iter()
+        .map(|v| quote_element(&v.to_string(), ARRAY_DELIMITERS))
+        .collect();
+    format!("{{{}}}", parts.join(","))
+}
+
+/// Double-quote a range / array element when it contains a delimiter,
+/// whitespace, a quote or a backslash, or is empty.
+fn quote_element(raw: &str, delimiters: &str) -> String {
+    let needs_quotes = raw.is_empty()
+        || raw.eq_ignore_ascii_case("null")
+        || raw
+            .chars()
+            .any(|c| c.is_whitespace() || c == '"' || c == '\\' || delimiters.contains(c));
+    if needs_quotes {
+        format!("\"{}\"", raw.replace('\\', "\\\\").replace('"', "\\\""))
+    } else {
+        raw.to_string()
+    }
+}
+
+/// Split the inside of a range literal on top-level commas, unquoting each
+/// part. An empty, unquoted part is an unbounded side (`None`).
+fn split_elements(inner: &str) -> Vec<Option<String>> {
+    let mut parts = Vec::new();
+    let mut current = String::new();
+    let mut quoted = false;
+    let mut in_quotes = false;
+    let mut chars = inner.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => current.extend(chars.next()),
+            '"' => {
+                in_quotes = !in_quotes;
+                quoted = true;
+            }
+            ',' if !in_quotes => {
+                parts.push((quoted || !current.is_empty()).then(|| std::mem::take(&mut current)));
+                quoted = false;
+            }
+            c => current.push(c),
+        }
+    }
+    parts.push((quoted || !current.is_empty()).then_some(current));
+    parts
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ranges_render_postgres_literals() {
+        assert_eq!(PgRange::from(1..10).to_string(), "[1,10)");
+        assert_eq!(PgRange::from(1..=10).to_string(), "[1,10]");
+        assert_eq!(PgRange::from(5..).to_string(), "[5,)");
+        assert_eq!(PgRange::from(..5).to_string(), "(,5)");
+        assert_eq!(PgRange::<i32>::empty().to_string(), "empty");
+        let ts = PgRange::from("2024-01-01 00:00".to_string().."2024-02-01 00:00".to_string());
+        assert_eq!(ts.to_string(), r#"["2024-01-01 00:00","2024-02-01 00:00")"#);
+    }
+
+    #[test]
+    fn ranges_parse_postgrest_output() {
+        let r: PgRange<i32> = "[1,10)".parse().unwrap();
+        assert_eq!(r, PgRange::from(1..10));
+        let r: PgRange<i64> = "(,5]".parse().unwrap();
+        assert_eq!((r.lower(), r.upper()), (Bound::Unbounded, Bound::Included(&5)));
+        let r: PgRange<String> = r#"["2024-01-01 10:00:00+00","2024-01-02 10:00:00+00")"#.parse().unwrap();
+        assert_eq!(r.lower(), Bound::Included(&"2024-01-01 10:00:00+00".to_string()));
+        assert!("empty".parse::<PgRange<i32>>().unwrap().is_empty());
+        assert!("[1,x)".parse::<PgRange<i32>>().is_err());
+        assert!("1,2".parse::<PgRange<i32>>().is_err());
+    }
+
+    #[test]
+    fn ranges_round_trip_through_serde() {
+        let r: PgRange<f64> = serde_json::from_str(r#""[1.5,2.5]""#).unwrap();
+        assert_eq!(serde_json::to_string(&r).unwrap(), r#""[1.5,2.5]""#);
+    }
+
+    #[test]
+    fn arrays_quote_elements_with_delimiters() {
+        assert_eq!(render_array([1, 2, 3]), "{1,2,3}");
+        assert_eq!(render_array(["a", "b c", "d,e", "", "NULL"]), r#"{a,"b c","d,e","","NULL"}"#);
+    }
+}
@@ -1,4 +1,73 @@
+//! Calling Postgres functions through `/rest/v1/rpc/<name>`.
+//!
+//! [`SupabaseClient::rpc_call`] is the untyped escape hatch, and
+//! [`SupabaseClient::rpc_builder`] returns a [`PostgrestBuilder`] so a
+//! set-returning function's rows can be filtered, ordered, paged and counted
+//! like a table's:
+//!
+//! ```no_run
+//! # use rust_supabase_sdk::{SupabaseClient, postgrest::CountMode, rpc::RpcMode};
+//! # use serde_json::json;
+//! # async fn demo(client: SupabaseClient) -> rust_supabase_sdk::Result<()> {
+//! let (rows, total) = client
+//!     .rpc_builder("posts_by_tag", json!({"tag": "rust"}))
+//!     .gte("score", 10)
+//!     .order("created_at", false)
+//!     .range(0, 9)
+//!     .count(CountMode::Exact)
+//!     .execute_with_count()
+//!     .await?;
+//!
+//! // Count only, for a STABLE function: HEAD with the args in the query string.
+//! let (_, total) = client
+//!     .rpc_builder_with("posts_by_tag", json!({"tag": "rust"}), RpcMode::Head)
+//!     .count(CountMode::Exact)
+//!     .execute_with_count()
+//!     .await?;
+//! # Ok(()) }
+//! ```
+//!
+//! For typed calls,
+//! describe the function with [`RpcFunction`] (`cargo supabase gen types`
+//! emits one impl per exposed function) and use [`SupabaseClient::rpc`]:
+//!
+//! ```no_run
+//! # use rust_supabase_sdk::{SupabaseClient, rpc::RpcFunction};
+//! # use serde::{Deserialize, Serialize};
+//! # #[derive(Debug, Deserialize)] struct Posts { id: i64 }
+//! #[derive(Serialize)]
+//! struct SearchPostsArgs { query: String }
+//!
+//! struct SearchPosts;
+//! impl RpcFunction for SearchPosts {
+//!     const NAME: &'static str = "search_posts";
+//!     const READ_ONLY: bool = true;
+//!     type Args = SearchPostsArgs;
+//!     type Returns = Vec<Posts>;
+//! }
+//!
+//! # async fn demo(client: SupabaseClient) -> rust_supabase_sdk::Result<()> {
+//! // GET /rest/v1/rpc/search_posts?query=rust&id=gt.100&order=id.desc&limit=10
+//! let posts: Vec<Posts> = client
+//!     .rpc::<SearchPosts>(SearchPostsArgs { query: "rust".into() })
+//!     .get()
+//!     .gt("id", 100)
+//!     .order("id", false)
+//!     .limit(10)
+//!     .await?;
+//! # Ok(()) }
+//! ```
+
+use std::future::{Future, IntoFuture};
+use std::marker::PhantomData;
+use std::pin::Pin;
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::Value;
+
 use crate::error::{Result, SupabaseError};
+use crate::postgrest::{CountMode, Order, PostgrestBuilder, PostgrestValue};
 use crate::{universals::HttpMethod, SupabaseClient};
 
 impl SupabaseClient {
@@ -21,4 +90,279 @@ impl SupabaseClient {
             ))),
         }
     }
+
+    /// Call a Postgres function through the regular query builder (`POST`,
+    /// args as the JSON body). Every filter, `order` / `limit` / `range`,
+    /// `count`, `single` / `maybe_single` and `returns::<U>()` applies to
+    /// the function's result rows.
+    pub fn rpc_builder(&self, name: &str, args: impl Serialize) -> PostgrestBuilder<Value> {
+        self.rpc_builder_with(name, args, RpcMode::Post)
+    }
+
+    /// [`rpc_builder`](Self::rpc_builder) with an explicit [`RpcMode`].
+    pub fn rpc_builder_with(
+        &self,
+        name: &str,
+        args: impl Serialize,
+        mode: RpcMode,
+    ) -> PostgrestBuilder<Value> {
+        PostgrestBuilder::rpc(self.clone(), name, args).with_rpc_mode(mode)
+    }
+
+    /// Call the Postgres function described by `F`. The result decodes as
+    /// `F::Returns`; set-returning functions can be filtered, ordered and
+    /// paged before awaiting.
+    pub fn rpc<F: RpcFunction>(&self, args: F::Args) -> RpcBuilder<F> {
+        RpcBuilder {
+            inner: PostgrestBuilder::rpc(self.clone(), F::NAME, args),
+            get: false,
+            _fn: PhantomData,
+        }
+    }
+}
+
+/// How an RPC call is sent.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum RpcMode {
+    /// `POST` with the arguments as a JSON body. Works for every function.
+    #[default]
+    Post,
+    /// `GET` with the arguments in the query string. Only for `STABLE` /
+    /// `IMMUTABLE` functions; cacheable by proxies.
+    Get,
+    /// Like `Get`, but PostgREST sends no body — pair with
+    /// [`count`](PostgrestBuilder::count) to get just the row count.
+    Head,
+}
+
+/// Signature of a Postgres function exposed through PostgREST.
+///
+/// `Returns` is whatever the response body decodes to:
+///
+/// | Function returns     | `Returns`                 |
+/// |----------------------|---------------------------|
+/// | `setof t` / `table`  | `Vec<Row>`                |
+/// | a scalar             | `i64`, `String`, …        |
+/// | a single composite   | the row struct            |
+/// | `void`               | `()`                      |
+pub trait RpcFunction {
+    /// Function name, as in `/rest/v1/rpc/<NAME>`.
+    const NAME: &'static str;
+    /// `true` for `STABLE` / `IMMUTABLE` functions, which PostgREST also
+    /// serves over `GET` (see [`RpcBuilder::get`]).
+    const READ_ONLY: bool = false;
+    /// Named arguments, serialized as a JSON object.
+    type Args: Serialize;
+    type Returns: DeserializeOwned;
+}
+
+/// Builder returned by [`SupabaseClient::rpc`]. Awaits to `F::Returns`.
+#[must_use = "RpcBuilder is lazy — `.await` it or call `.execute()`"]
+pub struct RpcBuilder<F: RpcFunction> {
+    inner: PostgrestBuilder<Value>,
+    get: bool,
+    _fn: PhantomData<fn() -> F>,
+}
+
+impl<F: RpcFunction> std::fmt::Debug for RpcBuilder<F> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RpcBuilder")
+            .field("function", &F::NAME)
+            .field("get", &self.get)
+            .field("inner", &self.inner)
+            .finish()
+    }
+}
+
+/// Forward result-shaping methods to the inner [`PostgrestBuilder`].
+macro_rules! forward {
+    ($($(#[$meta:meta])* fn $name:ident $(<$($g:ident: $b:path),*>)? ($($arg:ident: $ty:ty),*);)*) => {$(
+        $(#[$meta])*
+        pub fn $name $(<$($g: $b),*>)? (mut self, $($arg: $ty),*) -> Self {
+            self.inner = self.inner.$name($($arg),*);
+            self
+        }
+    )*};
+}
+
+impl<F: RpcFunction> RpcBuilder<F> {
+    /// Send the call as `GET` with the arguments in the query string.
+    /// Requires [`RpcFunction::READ_ONLY`]; otherwise awaiting fails with
+    /// [`SupabaseError::Unexpected`] without sending a request.
+    pub fn get(mut self) -> Self {
+        self.get = true;
+        self.inner = self.inner.with_rpc_mode(RpcMode::Get);
+        self
+    }
+
+    forward! {
+        /// `column = value` on the returned rows.
+        fn eq<V: PostgrestValue>(column: &str, value: V);
+        /// `column <> value` on the returned rows.
+        fn neq<V: PostgrestValue>(column: &str, value: V);
+        /// `column > value` on the returned rows.
+        fn gt<V: PostgrestValue>(column: &str, value: V);
+        /// `column >= value` on the returned rows.
+        fn gte<V: PostgrestValue>(column: &str, value: V);
+        /// `column < value` on the returned rows.
+        fn lt<V: PostgrestValue>(column: &str, value: V);
+        /// `column <= value` on the returned rows.
+        fn lte<V: PostgrestValue>(column: &str, value: V);
+        /// `column LIKE pattern` (`*` is the wildcard).
+        fn like<V: PostgrestValue>(column: &str, pattern: V);
+        /// `column ILIKE pattern`.
+        fn ilike<V: PostgrestValue>(column: &str, pattern: V);
+        /// `column IS value` (`null`, `true`, `false`).
+        fn is(column: &str, value: &str);
+        /// Any PostgREST operator: `column=<op>.<value>`.
+        fn filter<V: PostgrestValue>(column: &str, op: &str, value: V);
+        /// `column=not.<op>.<value>`.
+        fn not<V: PostgrestValue>(column: &str, op: &str, value: V);
+        /// Raw PostgREST `or=(...)` group.
+        fn or(filters: &str);
+        /// Order the returned rows.
+        fn order(column: &str, ascending: bool);
+        /// Order with nulls placement.
+        fn order_with(column: &str, options: Order);
+        /// Cap the number of returned rows.
+        fn limit(n: u64);
+        /// Skip the first `n` returned rows.
+        fn offset(n: u64);
+        /// Return rows in the inclusive `from..=to` range.
+        fn range(from: u64, to: u64);
+        /// Ask for the total row count; read it with
+        /// [`execute_with_count`](Self::execute_with_count).
+        fn count(mode: CountMode);
+    }
+
+    /// Call the function and decode the result.
+    pub async fn execute(self) -> Result<F::Returns> {
+        let (returns, _count) = self.execute_with_count().await?;
+        Ok(returns)
+    }
+
+    /// Call the function and return the decoded result with the total count
+    /// from `Content-Range` (when [`count`](Self::count) was set).
+    pub async fn execute_with_count(self) -> Result<(F::Returns, Option<u64>)> {
+        if self.get && !F::READ_ONLY {
+            return Err(SupabaseError::Unexpected(format!(
+                "RPC `{}` is not read-only and cannot be called with GET",
+                F::NAME
+            )));
+        }
+        let (count, value) = self.inner.execute_value().await?;
+        let returns = serde_json::from_value(value.clone()).map_err(|e| SupabaseError::Decode {
+            message: e.to_string(),
+            body: value.to_string(),
+        })?;
+        Ok((returns, count))
+    }
+
+    /// The path-and-query the call will hit, for debugging.
+    pub fn build_path(&self) -> String {
+        self.inner.build_path()
+    }
+}
+
+impl<F> IntoFuture for RpcBuilder<F>
+where
+    F: RpcFunction + 'static,
+    F::Returns: Send,
+{
+    type Output = Result<F::Returns>;
+    type IntoFuture = Pin<Box<dyn Future<Output = Self::Output> + Send>>;
+
+    fn into_future(self) -> Self::IntoFuture {
+        Box::pin(self.execute())
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    #[derive(Serialize)]
+    struct Args {
+        query: String,
+        tags: Vec<String>,
+        max: Option<i32>,
+    }
+
+    struct Search;
+    impl RpcFunction for Search {
+        const NAME: &'static str = "search";
+        const READ_ONLY: bool = true;
+        type Args = Args;
+        type Returns = Vec<Value>;
+    }
+
+    struct Touch;
+    impl RpcFunction for Touch {
+        const NAME: &'static str = "touch";
+        type Args = ();
+        type Returns = ();
+    }
+
+    fn client() -> SupabaseClient {
+        SupabaseClient::new("https://x.supabase.co", "anon", None)
+    }
+
+    fn args() -> Args {
+        Args {
+            query: "a b".into(),
+            tags: vec!["x".into(), "y,z".into()],
+            max: None,
+        }
+    }
+
+    #[test]
+    fn post_keeps_args_in_body_and_filters_in_query() {
+        let q = client().rpc::<Search>(args()).gt("id", 10).limit(5);
+        assert_eq!(q.build_path(), "/rest/v1/rpc/search?id=gt.10&limit=5");
+        assert_eq!(
+            q.inner.state.body,
+            Some(json!({"query": "a b", "tags": ["x", "y,z"], "max": null}))
+        );
+    }
+
+    #[test]
+    fn get_moves_args_into_query_string() {
+        let q = client().rpc::<Search>(args()).order("id", false).get();
+        assert_eq!(
+            q.build_path(),
+            "/rest/v1/rpc/search?query=a%20b&tags=%7B%22x%22%2C%22y%2Cz%22%7D&order=id.desc"
+        );
+        assert!(q.inner.state.body.is_none());
+    }
+
+    #[test]
+    fn unit_args_become_empty_object() {
+        let q = client().rpc::<Touch>(());
+        assert_eq!(q.inner.state.body, Some(json!({})));
+    }
+
+    #[test]
+    fn rpc_builder_modes_place_args() {
+        let c = client();
+        let post = c.rpc_builder("search", json!({"q": "x"})).eq("id", 1);
+        assert_eq!(post.build_path(), "/rest/v1/rpc/search?id=eq.1");
+        assert_eq!(post.state.body, Some(json!({"q": "x"})));
+
+        for mode in [RpcMode::Get, RpcMode::Head] {
+            let q = c.rpc_builder_with("search", json!({"q": "x", "n": null}), mode).eq("id", 1);
+            assert_eq!(q.build_path(), "/rest/v1/rpc/search?q=x&id=eq.1");
+            assert!(q.state.body.is_none());
+        }
+        assert_eq!(
+            c.rpc_builder_with("f", (), RpcMode::Head).op.method(),
+            HttpMethod::Head
+        );
+    }
+
+    #[tokio::test]
+    async fn get_on_volatile_function_fails_before_sending() {
+        let err = client().rpc::<Touch>(()).get().execute().await.unwrap_err();
+        assert!(matches!(err, SupabaseError::Unexpected(m) if m.contains("not read-only")));
+    }
 }
@@ -21,6 +21,7 @@ pub enum HttpMethod {
     Put,
     Patch,
     Delete,
+    Head,
 }
 
 impl HttpMethod {
@@ -31,6 +32,7 @@ impl HttpMethod {
             Self::Put => Method::PUT,
             Self::Patch => Method::PATCH,
             Self::Delete => Method::DELETE,
+            Self::Head => Method::HEAD,
         }
     }
 }
@@ -360,6 +362,7 @@ mod tests {
         assert_eq!(HttpMethod::Put.as_reqwest(), reqwest::Method::PUT);
         assert_eq!(HttpMethod::Patch.as_reqwest(), reqwest::Method::PATCH);
         assert_eq!(HttpMethod::Delete.as_reqwest(), reqwest::Method::DELETE);
+        assert_eq!(HttpMethod::Head.as_reqwest(), reqwest::Method::HEAD);
     }
 
     #[test]
@@ -1,17 +1,40 @@
 //! Mock-server tests filling coverage gaps in modules that are otherwise
 //! exercised only via live integration:
 //!
-//!   * `src/rpc.rs` — `rpc_call`
+//!   * `src/rpc.rs` — `rpc_call`, typed `rpc::<F>` (scalar, set-returning, void, GET),
+//!     `rpc_builder` filters / single / HEAD counts
 //!   * `src/functions/mod.rs` — `invoke`, `invoke_with`, `invoke_stream`, all
 //!     body variants, region header, JSON/text/bytes/form
 //!   * `src/auth/mod.rs` — sign_up, verify_otp, resend, sign_in_with_id_token,
 //!     exchange_code_for_session, reset_password_for_email, refresh_session,
-//!     sign_in_with_otp paths
+//!     sign_in_with_otp paths, PKCE challenges and verifiers
+//!   * `src/auth/mfa.rs` — enroll, challenge / verify, unenroll, list_factors
+//!   * `src/auth/auto_refresh.rs` — single refresh across clients sharing a store
+//!   * `src/auth/events.rs` — state change events from sign-in, refresh, update, sign-out
 //!   * `src/universals/mod.rs` — `request_bytes` 429 retry, empty / malformed
 //!     body, error decoding
-//!   * `src/auth/admin.rs` — invite_user_by_email, generate_link, list_users
+//!   * `src/auth/admin.rs` — invite_user_by_email, generate_link, list_users,
+//!     MFA factors
 //!   * `src/postgrest/builder.rs` — execute-path variants (bare object, null,
-//!     decode errors, IntoFuture await, maybe_single multi-row)
+//!     decode errors, IntoFuture await, maybe_single multi-row), HEAD counts
+//!   * `src/postgrest/paginate.rs` — offset / keyset page streams, total count
+//!   * `src/postgrest/cursor.rs` — typed `after` / `before` pages and cursor tokens
+//!   * `src/postgrest/embed.rs` — typed embeds decoding into `WithRelations`
+//!   * `src/postgrest/batch.rs` — chunked inserts, partial failures, stop-on-failure
+//!   * `src/postgrest/aggregate.rs` — grouped aggregates decoded into a struct
+//!   * `src/postgrest/logic.rs` — `Filter` trees rendered into `or` / `and` params
+//!   * `src/postgrest/json_path.rs` — JSON paths in select, filters and order
+//!   * `src/postgrest/types.rs` — range / array filters and `PgRange` decoding
+//!   * `src/postgrest/typed_builder.rs` — typed insert / update payloads returning rows
+//!   * `src/postgrest/tracked.rs` — dirty-column saves, optimistic guards, conflicts
+//!   * `src/postgrest/lookup.rs` — `find`, `find_many`, `delete_by_pk` by primary key
+//!   * `src/postgrest/cache.rs` — cached selects, per-bearer keys, write invalidation
+//!   * `src/postgrest/explain.rs` — plan media types, JSON / text plans
+//!   * `src/postgrest/format.rs` — CSV export streams, GeoJSON, CSV insert bodies
+//!   * `src/postgrest/stream.rs` — incremental row decoding, error mapping, row cap
+//!   * `src/postgrest/transaction.rs` — statements compiled into one helper RPC call
+//!   * `src/jwt/verify.rs` — ES256 tokens checked against a cached, rotating JWKS
+//!     (feature `jwt`)
 //!
 //! Mocks let us drive every branch deterministically without needing a live
 //! project pre-configured with edge functions, OTP, OAuth, etc.
@@ -21,16 +44,26 @@
 use std::time::Duration;
 
 use rust_supabase_sdk::auth::{
-    OtpRecipient, OtpType, OAuthOptions, OtpOptions, SignOutScope,
+    AuthFlow, OtpRecipient, OtpType, OAuthOptions, OtpOptions, SignOutScope,
     UpdateUserAttributes, VerifyOtpParams, ResetPasswordOptions,
 };
+use rust_supabase_sdk::auth::mfa::EnrollParams;
+use rust_supabase_sdk::auth::{AuthEvent, AutoRefreshConfig};
 use rust_supabase_sdk::functions::{
     FunctionRegion, InvokeMethod, InvokeOptions,
 };
+use rust_supabase_sdk::postgrest::{
+    Agg, BatchOptions, CacheConfig, Column, CountMode, Cursor, Embeddable, ExplainFormat,
+    ExplainOptions, Filter, Geometry, Keyed, PgRange, Related, Relation, Tracked, Writable,
+};
+use rust_supabase_sdk::rpc::{RpcFunction, RpcMode};
 use rust_supabase_sdk::storage::UploadOptions;
-use rust_supabase_sdk::{RetryConfig, SupabaseClient, SupabaseError};
+use rust_supabase_sdk::{
+    InMemorySessionStore, RetryConfig, Row, Session, SessionStore, SupabaseClient, SupabaseError,
+};
+use futures_util::StreamExt;
 use serde_json::{json, Value};
-use wiremock::matchers::{body_json, header, method, path, query_param};
+use wiremock::matchers::{body_json, body_string, header, headers, method, path, query_param};
 use wiremock::{Mock, MockServer, ResponseTemplate};
 
 fn client(server: &MockServer) -> SupabaseClient {
@@ -88,6 +121,136 @@ async fn rpc_call_propagates_server_error() {
     assert!(matches!(err, SupabaseError::Postgrest(_)));
 }
 
+struct AddThem;
+impl RpcFunction for AddThem {
+    const NAME: &'static str = "add_them";
+    const READ_ONLY: bool = true;
+    type Args = Value;
+    type Returns = i64;
+}
+
+struct RecentPosts;
+impl RpcFunction for RecentPosts {
+    const NAME: &'static str = "recent_posts";
+    type Args = Value;
+    type Returns = Vec<Post>;
+}
+
+struct Touch;
+impl RpcFunction for Touch {
+    const NAME: &'static str = "touch";
+    type Args = ();
+    type Returns = ();
+}
+
+#[tokio::test]
+async fn typed_rpc_decodes_scalar_and_void_results() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/rest/v1/rpc/add_them"))
+        .and(query_param("a", "1"))
+        .and(query_param("b", "2"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!(3)))
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/rest/v1/rpc/touch"))
+        .and(body_json(json!({})))
+        .respond_with(ResponseTemplate::new(204))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let c = client(&server);
+    let sum = c.rpc::<AddThem>(json!({"a": 1, "b": 2})).get().await.unwrap();
+    assert_eq!(sum, 3);
+    c.rpc::<Touch>(()).await.unwrap();
+    server.verify().await;
+}
+
+#[tokio::test]
+async fn typed_rpc_filters_and_counts_set_returning_results() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/rest/v1/rpc/recent_posts"))
+        .and(body_json(json!({"days": 7})))
+        .and(query_param("id", "gt.10"))
+        .and(query_param("order", "created_at.desc"))
+        .and(query_param("limit", "2"))
+        .and(header("prefer", "count=exact"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .insert_header("content-range", "0-1/40")
+                .set_body_json(json!([
+                    {"id": 12, "created_at": "b"},
+                    {"id": 11, "created_at": "a"}
+                ])),
+        )
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let (posts, count) = client(&server)
+        .rpc::<RecentPosts>(json!({"days": 7}))
+        .gt("id", 10)
+        .order("created_at", false)
+        .limit(2)
+        .count(CountMode::Exact)
+        .execute_with_count()
+        .await
+        .unwrap();
+    assert_eq!(posts.iter().map(|p| p.id).collect::<Vec<_>>(), vec![12, 11]);
+    assert_eq!(count, Some(40));
+    server.verify().await;
+}
+
+#[tokio::test]
+async fn rpc_builder_reuses_filters_and_single() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/rest/v1/rpc/posts_by_tag"))
+        .and(body_json(json!({"tag": "rust"})))
+        .and(query_param("id", "eq.12"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!([{"id": 12, "created_at": "b"}])))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let post: Post = client(&server)
+        .rpc_builder("posts_by_tag", json!({"tag": "rust"}))
+        .eq("id", 12)
+        .returns::<Post>()
+        .single()
+        .await
+        .unwrap();
+    assert_eq!(post.created_at, "b");
+    server.verify().await;
+}
+
+#[tokio::test]
+async fn rpc_builder_head_mode_returns_only_the_count() {
+    let server = MockServer::start().await;
+    Mock::given(method("HEAD"))
+        .and(path("/rest/v1/rpc/posts_by_tag"))
+        .and(query_param("tag", "rust"))
+        .and(header("prefer", "count=exact"))
+        .respond_with(ResponseTemplate::new(200).insert_header("content-range", "*/17"))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let (rows, count) = client(&server)
+        .rpc_builder_with("posts_by_tag", json!({"tag": "rust"}), RpcMode::Head)
+        .count(CountMode::Exact)
+        .execute_with_count()
+        .await
+        .unwrap();
+    assert!(rows.is_empty());
+    assert_eq!(count, Some(17));
+    server.verify().await;
+}
+
 
 // ===========================================================================
 // functions/mod.rs — invoke pathways
@@ -562,156 +725,599 @@ async fn auth_reset_password_for_email() {
 }
 
 #[tokio::test]
-async fn auth_refresh_session_uses_stored_refresh_token() {
+async fn auth_pkce_flows_send_challenge_then_verifier() {
+    use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+    use base64::Engine;
+    use sha2::{Digest, Sha256};
+
     let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/auth/v1/otp"))
+        .respond_with(ResponseTemplate::new(200).set_body_string(""))
+        .expect(1)
+        .mount(&server)
+        .await;
     Mock::given(method("POST"))
         .and(path("/auth/v1/token"))
-        .and(query_param("grant_type", "refresh_token"))
-        .and(body_json(json!({"refresh_token": "rtok"})))
+        .and(query_param("grant_type", "pkce"))
         .respond_with(ResponseTemplate::new(200).set_body_json(make_session_body()))
+        .expect(3)
         .mount(&server)
         .await;
-    let c = client(&server);
-    // Pre-seed the session store so refresh_session(None) uses the stored rtok.
-    let initial: rust_supabase_sdk::auth::Session =
-        serde_json::from_value(make_session_body()).unwrap();
-    c.auth().set_session(initial);
-    let s = c.auth().refresh_session(None).await.unwrap();
-    assert_eq!(s.access_token, "tok");
-}
+    let client = SupabaseClient::builder(server.uri(), "test-key")
+        .auth_flow(AuthFlow::Pkce)
+        .build();
+    let s256 = |verifier: &Value| URL_SAFE_NO_PAD.encode(Sha256::digest(verifier.as_str().unwrap()));
 
-#[tokio::test]
-async fn auth_refresh_session_with_no_session_or_token_returns_auth_error() {
-    let server = MockServer::start().await;
-    let c = client(&server);
-    let err = c.auth().refresh_session(None).await.unwrap_err();
-    assert!(matches!(err, SupabaseError::Auth(_)));
+    let flow = client
+        .auth()
+        .sign_in_with_oauth_pkce("github", OAuthOptions::default())
+        .unwrap();
+    let url = url::Url::parse(&flow.url).unwrap();
+    let query: std::collections::HashMap<_, _> = url.query_pairs().into_owned().collect();
+    assert_eq!(query["code_challenge_method"], "s256");
+    client.auth().exchange_code_for_session("code-1").await.unwrap();
+    client
+        .auth()
+        .sign_in_with_otp(OtpRecipient::Email("a@b.co".into()), OtpOptions::default())
+        .await
+        .unwrap();
+    client.auth().exchange_code_for_session("code-2").await.unwrap();
+    // The verifier was used up by the previous exchange.
+    client.auth().exchange_code_for_session("code-3").await.unwrap();
+
+    let bodies: Vec<Value> = server
+        .received_requests()
+        .await
+        .unwrap()
+        .iter()
+        .map(|r| serde_json::from_slice(&r.body).unwrap())
+        .collect();
+    assert_eq!(bodies[0]["auth_code"], "code-1");
+    assert_eq!(s256(&bodies[0]["code_verifier"]), query["code_challenge"]);
+    assert_eq!(bodies[1]["code_challenge_method"], "s256");
+    assert_eq!(s256(&bodies[2]["code_verifier"]), bodies[1]["code_challenge"]);
+    assert!(bodies[3].get("code_verifier").is_none());
+    server.verify().await;
 }
 
 #[tokio::test]
-async fn auth_refresh_session_if_needed_returns_current_when_not_expiring() {
+async fn auth_pkce_verifier_survives_a_failed_exchange_until_the_server_rejects_it() {
     let server = MockServer::start().await;
-    let c = client(&server);
-    let mut session: rust_supabase_sdk::auth::Session =
-        serde_json::from_value(make_session_body()).unwrap();
-    session.expires_at = chrono::Utc::now().timestamp() + 7200; // 2h away
-    c.auth().set_session(session);
+    let token = || Mock::given(method("POST")).and(path("/auth/v1/token"));
+    token()
+        .respond_with(ResponseTemplate::new(503))
+        .up_to_n_times(1)
+        .mount(&server)
+        .await;
+    token()
+        .respond_with(ResponseTemplate::new(200).set_body_json(make_session_body()))
+        .up_to_n_times(1)
+        .mount(&server)
+        .await;
+    token()
+        .respond_with(
+            ResponseTemplate::new(400).set_body_json(json!({ "msg": "invalid flow state" })),
+        )
+        .up_to_n_times(1)
+        .mount(&server)
+        .await;
+    token()
+        .respond_with(ResponseTemplate::new(200).set_body_json(make_session_body()))
+        .mount(&server)
+        .await;
+    let client = SupabaseClient::builder(server.uri(), "test-key")
+        .auth_flow(AuthFlow::Pkce)
+        .build();
 
-    // No /token mock — proves refresh_session was NOT called.
-    let result = c.auth().refresh_session_if_needed(60).await.unwrap();
-    assert!(result.is_some(), "should return the existing session");
-}
+    client.auth().sign_in_with_oauth_pkce("github", OAuthOptions::default()).unwrap();
+    assert!(client.auth().exchange_code_for_session("code-1").await.is_err());
+    client.auth().exchange_code_for_session("code-1").await.unwrap();
+    client.auth().sign_in_with_oauth_pkce("github", OAuthOptions::default()).unwrap();
+    assert!(client.auth().exchange_code_for_session("expired").await.is_err());
+    client.auth().exchange_code_for_session("code-2").await.unwrap();
 
-#[tokio::test]
-async fn auth_refresh_session_if_needed_returns_none_with_no_session() {
-    let server = MockServer::start().await;
-    let c = client(&server);
-    let result = c.auth().refresh_session_if_needed(60).await.unwrap();
-    assert!(result.is_none(), "should return None when no session stored");
+    let bodies: Vec<Value> = server
+        .received_requests()
+        .await
+        .unwrap()
+        .iter()
+        .map(|r| serde_json::from_slice(&r.body).unwrap())
+        .collect();
+    assert_eq!(bodies.len(), 4);
+    // The 503 kept the verifier for the retry; the 400 spent the second one.
+    assert!(bodies[0]["code_verifier"].is_string());
+    assert_eq!(bodies[1]["code_verifier"], bodies[0]["code_verifier"]);
+    assert!(bodies[2]["code_verifier"].is_string());
+    assert_ne!(bodies[2]["code_verifier"], bodies[0]["code_verifier"]);
+    assert!(bodies[3].get("code_verifier").is_none());
 }
 
 #[tokio::test]
-async fn auth_refresh_session_if_needed_triggers_refresh_when_expiring() {
+async fn auth_reset_password_on_pkce_client_sends_challenge() {
     let server = MockServer::start().await;
     Mock::given(method("POST"))
-        .and(path("/auth/v1/token"))
-        .and(query_param("grant_type", "refresh_token"))
-        .respond_with(ResponseTemplate::new(200).set_body_json(make_session_body()))
+        .and(path("/auth/v1/recover"))
+        .respond_with(ResponseTemplate::new(200).set_body_string(""))
+        .expect(1)
         .mount(&server)
         .await;
-    let c = client(&server);
-    let mut session: rust_supabase_sdk::auth::Session =
-        serde_json::from_value(make_session_body()).unwrap();
-    session.expires_at = chrono::Utc::now().timestamp() + 10; // 10s — within threshold
-    c.auth().set_session(session);
-
-    let result = c.auth().refresh_session_if_needed(60).await.unwrap();
-    assert!(result.is_some(), "should have refreshed");
+    let client = SupabaseClient::builder(server.uri(), "test-key")
+        .auth_flow(AuthFlow::Pkce)
+        .build();
+    client
+        .auth()
+        .reset_password_for_email("a@b.co", ResetPasswordOptions::default())
+        .await
+        .unwrap();
+    let requests = server.received_requests().await.unwrap();
+    let body: Value = serde_json::from_slice(&requests[0].body).unwrap();
+    assert_eq!(body["email"], "a@b.co");
+    assert_eq!(body["code_challenge_method"], "s256");
+    assert_eq!(body["code_challenge"].as_str().unwrap().len(), 43);
+    server.verify().await;
 }
 
 #[tokio::test]
-async fn auth_get_user_with_no_session_falls_back_to_api_key() {
+async fn auth_mfa_enroll_verify_and_unenroll() {
     let server = MockServer::start().await;
-    Mock::given(method("GET"))
-        .and(path("/auth/v1/user"))
-        // The fallback bearer is the api_key set in builder.
-        .and(header("authorization", "Bearer test-key"))
+    Mock::given(method("POST"))
+        .and(path("/auth/v1/factors"))
+        .and(header("authorization", "Bearer tok"))
+        .and(body_json(json!({"factor_type": "totp", "issuer": "Console"})))
         .respond_with(ResponseTemplate::new(200).set_body_json(json!({
-            "id": "u1", "aud": "auth", "role": "auth",
-            "created_at": "2024-01-01T00:00:00Z"
+            "id": "f1", "type": "totp",
+            "totp": {"qr_code": "data:image/svg+xml;...", "secret": "S3CR3T", "uri": "otpauth://totp/x"}
         })))
+        .expect(1)
         .mount(&server)
         .await;
-    let u = client(&server).auth().get_user().await.unwrap();
-    assert_eq!(u.id, "u1");
+    Mock::given(method("POST"))
+        .and(path("/auth/v1/factors/f1/challenge"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "id": "c1", "type": "totp", "expires_at": 1700000300
+        })))
+        .expect(1)
+        .mount(&server)
+        .await;
+    let mut upgraded = make_session_body();
+    upgraded["access_token"] = json!("aal2-tok");
+    Mock::given(method("POST"))
+        .and(path("/auth/v1/factors/f1/verify"))
+        .and(body_json(json!({"challenge_id": "c1", "code": "123456"})))
+        .respond_with(ResponseTemplate::new(200).set_body_json(upgraded))
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("DELETE"))
+        .and(path("/auth/v1/factors/f1"))
+        .and(header("authorization", "Bearer aal2-tok"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({"id": "f1"})))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let client = client(&server);
+    let mfa = client.auth().mfa();
+    let err = mfa.unenroll("f1").await.unwrap_err();
+    assert!(matches!(err, SupabaseError::Auth(_)), "no session yet: {err:?}");
+
+    client.auth().set_session(serde_json::from_value(make_session_body()).unwrap());
+    let factor = mfa
+        .enroll(EnrollParams::Totp { friendly_name: None, issuer: Some("Console".into()) })
+        .await
+        .unwrap();
+    assert_eq!(factor.totp.unwrap().secret, "S3CR3T");
+    let session = mfa.challenge_and_verify(&factor.id, "123456").await.unwrap();
+    assert_eq!(session.access_token, "aal2-tok");
+    assert_eq!(client.auth().get_session().unwrap().access_token, "aal2-tok");
+    mfa.unenroll("f1").await.unwrap();
+    server.verify().await;
 }
 
 #[tokio::test]
-async fn auth_update_user_with_no_session() {
-    // Exercises the `None` arm of `update_user`'s session match.
+async fn auth_mfa_list_factors_groups_verified_ones() {
     let server = MockServer::start().await;
-    Mock::given(method("PUT"))
+    Mock::given(method("GET"))
         .and(path("/auth/v1/user"))
         .respond_with(ResponseTemplate::new(200).set_body_json(json!({
-            "id": "u1", "aud": "auth", "role": "auth",
-            "created_at": "2024-01-01T00:00:00Z"
+            "id": "u1", "created_at": "2024-01-01T00:00:00Z",
+            "factors": [
+                {"id": "f1", "factor_type": "totp", "status": "verified"},
+                {"id": "f2", "factor_type": "phone", "status": "unverified", "phone": "+1555"}
+            ]
         })))
+        .expect(1)
         .mount(&server)
         .await;
-    let u = client(&server)
-        .auth()
-        .update_user(UpdateUserAttributes::default())
-        .await
-        .unwrap();
-    assert_eq!(u.id, "u1");
+    let factors = client(&server).auth().mfa().list_factors().await.unwrap();
+    assert_eq!(factors.all.len(), 2);
+    assert_eq!(factors.totp[0].id, "f1");
+    assert!(factors.phone.is_empty());
+    server.verify().await;
+}
+
+/// One session store shared by several clients.
+#[derive(Debug, Clone, Default)]
+struct SharedStore(std::sync::Arc<InMemorySessionStore>);
+
+impl SessionStore for SharedStore {
+    fn get(&self) -> Option<Session> {
+        self.0.get()
+    }
+    fn set(&self, session: Session) {
+        self.0.set(session)
+    }
+    fn clear(&self) {
+        self.0.clear()
+    }
 }
 
 #[tokio::test]
-async fn auth_sign_out_global_hits_logout_endpoint() {
+async fn auth_auto_refresh_refreshes_once_for_a_shared_store() {
     let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/auth/v1/token"))
+        .and(query_param("grant_type", "refresh_token"))
+        .and(body_json(json!({"refresh_token": "rtok-old"})))
+        .respond_with(ResponseTemplate::new(200).set_body_json(make_session_body()))
+        .expect(1)
+        .mount(&server)
+        .await;
     Mock::given(method("POST"))
         .and(path("/auth/v1/logout"))
-        .respond_with(ResponseTemplate::new(200).set_body_string(""))
+        .respond_with(ResponseTemplate::new(204))
         .expect(1)
         .mount(&server)
         .await;
-    let c = client(&server);
-    let session: rust_supabase_sdk::auth::Session =
-        serde_json::from_value(make_session_body()).unwrap();
-    c.auth().set_session(session);
-    c.auth().sign_out(SignOutScope::Global).await.unwrap();
-    assert!(c.auth().get_session().is_none());
+
+    let store = SharedStore::default();
+    let a = SupabaseClient::builder(server.uri(), "test-key").session_store(store.clone()).build();
+    let b = SupabaseClient::builder(server.uri(), "test-key").session_store(store).build();
+    let mut expiring: Session = serde_json::from_value(make_session_body()).unwrap();
+    expiring.access_token = "stale".into();
+    expiring.refresh_token = "rtok-old".into();
+    expiring.expires_in = 2;
+    expiring.expires_at = chrono::Utc::now().timestamp() + 2;
+    a.auth().set_session(expiring);
+
+    let config = AutoRefreshConfig {
+        threshold: Duration::from_secs(1),
+        jitter: Duration::ZERO,
+    };
+    let on_a = a.auth().start_auto_refresh(config).unwrap();
+    let on_b = b.auth().start_auto_refresh(config).unwrap();
+    tokio::time::sleep(Duration::from_millis(2500)).await;
+    assert_eq!(b.auth().get_session().unwrap().access_token, "tok");
+    assert!(on_a.is_running() && on_b.is_running());
+
+    a.auth().sign_out(SignOutScope::Local).await.unwrap();
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    assert!(!on_a.is_running(), "sign_out stops the client's task");
+    on_b.cancel();
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    assert!(!on_b.is_running());
+    server.verify().await;
 }
 
 #[tokio::test]
-async fn auth_sign_out_others_scope() {
+async fn auth_concurrent_manual_refreshes_spend_the_token_once() {
     let server = MockServer::start().await;
     Mock::given(method("POST"))
-        .and(path("/auth/v1/logout"))
-        .and(query_param("scope", "others"))
-        .respond_with(ResponseTemplate::new(200).set_body_string(""))
+        .and(path("/auth/v1/token"))
+        .and(query_param("grant_type", "refresh_token"))
+        .and(body_json(json!({"refresh_token": "rtok-old"})))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_json(make_session_body())
+                .set_delay(Duration::from_millis(300)),
+        )
         .expect(1)
         .mount(&server)
         .await;
-    let c = client(&server);
-    let session: rust_supabase_sdk::auth::Session =
-        serde_json::from_value(make_session_body()).unwrap();
-    c.auth().set_session(session);
-    c.auth().sign_out(SignOutScope::Others).await.unwrap();
-    // `sign_out` always clears the local session regardless of scope —
-    // the scope only changes which sessions the server revokes.
-    assert!(c.auth().get_session().is_none());
+
+    let store = SharedStore::default();
+    let a = SupabaseClient::builder(server.uri(), "test-key").session_store(store.clone()).build();
+    let b = SupabaseClient::builder(server.uri(), "test-key").session_store(store).build();
+    let mut expiring: Session = serde_json::from_value(make_session_body()).unwrap();
+    expiring.refresh_token = "rtok-old".into();
+    a.auth().set_session(expiring);
+
+    let (auth_a, auth_b) = (a.auth(), b.auth());
+    let (on_a, on_b) = tokio::join!(
+        auth_a.refresh_session(None),
+        auth_b.refresh_session_if_needed(i64::MAX),
+    );
+    assert_eq!(on_a.unwrap().refresh_token, "rtok");
+    assert_eq!(on_b.unwrap().unwrap().refresh_token, "rtok");
+    server.verify().await;
+}
+
+/// Counts reads, to tell a sleeping refresh task from a polling one.
+#[derive(Debug, Clone, Default)]
+struct CountingStore {
+    inner: SharedStore,
+    reads: std::sync::Arc<std::sync::atomic::AtomicUsize>,
+}
+
+impl SessionStore for CountingStore {
+    fn get(&self) -> Option<Session> {
+        self.reads.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+        self.inner.get()
+    }
+    fn set(&self, session: Session) {
+        self.inner.set(session)
+    }
+    fn clear(&self) {
+        self.inner.clear()
+    }
 }
 
 #[tokio::test]
-async fn auth_sign_in_with_oauth_returns_authorize_url() {
-    // No network call — just URL construction.
+async fn auth_auto_refresh_with_jitter_wakes_once_and_refreshes_once() {
     let server = MockServer::start().await;
-    let mut qp = std::collections::HashMap::new();
-    qp.insert("k".to_string(), "v".to_string());
-    let flow = client(&server).auth().sign_in_with_oauth(
-        "github",
-        OAuthOptions {
+    Mock::given(method("POST"))
+        .and(path("/auth/v1/token"))
+        .and(query_param("grant_type", "refresh_token"))
+        .and(body_json(json!({"refresh_token": "rtok-old"})))
+        .respond_with(ResponseTemplate::new(200).set_body_json(make_session_body()))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let store = CountingStore::default();
+    let c = SupabaseClient::builder(server.uri(), "test-key").session_store(store.clone()).build();
+    let mut expiring: Session = serde_json::from_value(make_session_body()).unwrap();
+    expiring.refresh_token = "rtok-old".into();
+    expiring.expires_at = chrono::Utc::now().timestamp() + 5;
+    c.auth().set_session(expiring);
+
+    // Refreshes between 1 and 4 seconds before expiry.
+    let config = AutoRefreshConfig {
+        threshold: Duration::from_secs(1),
+        jitter: Duration::from_secs(3),
+    };
+    let handle = c.auth().start_auto_refresh(config).unwrap();
+    tokio::time::sleep(Duration::from_millis(4500)).await;
+    assert_eq!(c.auth().get_session().unwrap().refresh_token, "rtok");
+    // Reads: the plan, the single wake-up, the re-check after claiming the
+    // token, the refresh request's bearer, the plan for the new token, and
+    // the `get_session` above. Any extra wake-up would add two more.
+    let reads = store.reads.load(std::sync::atomic::Ordering::SeqCst);
+    assert_eq!(reads, 6, "refresh task woke up more than once");
+    handle.cancel();
+    server.verify().await;
+}
+
+#[tokio::test]
+async fn auth_state_changes_follow_the_session() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/auth/v1/token"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(make_session_body()))
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/auth/v1/verify"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(make_session_body()))
+        .mount(&server)
+        .await;
+    Mock::given(method("PUT"))
+        .and(path("/auth/v1/user"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "id": "u1", "aud": "auth", "role": "auth", "email": "new@example.com",
+            "created_at": "2024-01-01T00:00:00Z"
+        })))
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/auth/v1/logout"))
+        .respond_with(ResponseTemplate::new(204))
+        .mount(&server)
+        .await;
+
+    let c = client(&server);
+    let mut events = c.auth().on_state_change();
+    c.auth().sign_in_with_password("a@b.c", "pw").await.unwrap();
+    c.auth().refresh_session(None).await.unwrap();
+    c.auth()
+        .update_user(UpdateUserAttributes::default())
+        .await
+        .unwrap();
+    c.auth()
+        .verify_otp(VerifyOtpParams::TokenHash {
+            token_hash: "hashed".into(),
+            otp_type: OtpType::Recovery,
+        })
+        .await
+        .unwrap();
+    c.auth().sign_out(SignOutScope::Local).await.unwrap();
+
+    assert!(matches!(events.recv().await.unwrap(), AuthEvent::SignedIn(_)));
+    assert!(matches!(events.recv().await.unwrap(), AuthEvent::TokenRefreshed(_)));
+    match events.recv().await.unwrap() {
+        AuthEvent::UserUpdated(user) => assert_eq!(user.email.as_deref(), Some("new@example.com")),
+        other => panic!("expected UserUpdated, got {other:?}"),
+    }
+    assert!(matches!(events.recv().await.unwrap(), AuthEvent::PasswordRecovery(_)));
+    assert!(matches!(events.recv().await.unwrap(), AuthEvent::SignedOut));
+    assert!(events.try_recv().is_err());
+}
+
+#[tokio::test]
+async fn auth_update_user_refreshes_the_stored_user() {
+    let server = MockServer::start().await;
+    Mock::given(method("PUT"))
+        .and(path("/auth/v1/user"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "id": "u1", "aud": "auth", "role": "auth", "email": "new@example.com",
+            "created_at": "2024-01-01T00:00:00Z"
+        })))
+        .mount(&server)
+        .await;
+    let c = client(&server);
+    c.auth()
+        .set_session(serde_json::from_value(make_session_body()).unwrap());
+    c.auth()
+        .update_user(UpdateUserAttributes::default())
+        .await
+        .unwrap();
+    let session = c.auth().get_session().unwrap();
+    assert_eq!(session.user.email.as_deref(), Some("new@example.com"));
+    assert_eq!(session.access_token, "tok");
+}
+
+#[tokio::test]
+async fn auth_refresh_session_uses_stored_refresh_token() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/auth/v1/token"))
+        .and(query_param("grant_type", "refresh_token"))
+        .and(body_json(json!({"refresh_token": "rtok"})))
+        .respond_with(ResponseTemplate::new(200).set_body_json(make_session_body()))
+        .mount(&server)
+        .await;
+    let c = client(&server);
+    // Pre-seed the session store so refresh_session(None) uses the stored rtok.
+    let initial: rust_supabase_sdk::auth::Session =
+        serde_json::from_value(make_session_body()).unwrap();
+    c.auth().set_session(initial);
+    let s = c.auth().refresh_session(None).await.unwrap();
+    assert_eq!(s.access_token, "tok");
+}
+
+#[tokio::test]
+async fn auth_refresh_session_with_no_session_or_token_returns_auth_error() {
+    let server = MockServer::start().await;
+    let c = client(&server);
+    let err = c.auth().refresh_session(None).await.unwrap_err();
+    assert!(matches!(err, SupabaseError::Auth(_)));
+}
+
+#[tokio::test]
+async fn auth_refresh_session_if_needed_returns_current_when_not_expiring() {
+    let server = MockServer::start().await;
+    let c = client(&server);
+    let mut session: rust_supabase_sdk::auth::Session =
+        serde_json::from_value(make_session_body()).unwrap();
+    session.expires_at = chrono::Utc::now().timestamp() + 7200; // 2h away
+    c.auth().set_session(session);
+
+    // No /token mock — proves refresh_session was NOT called.
+    let result = c.auth().refresh_session_if_needed(60).await.unwrap();
+    assert!(result.is_some(), "should return the existing session");
+}
+
+#[tokio::test]
+async fn auth_refresh_session_if_needed_returns_none_with_no_session() {
+    let server = MockServer::start().await;
+    let c = client(&server);
+    let result = c.auth().refresh_session_if_needed(60).await.unwrap();
+    assert!(result.is_none(), "should return None when no session stored");
+}
+
+#[tokio::test]
+async fn auth_refresh_session_if_needed_triggers_refresh_when_expiring() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/auth/v1/token"))
+        .and(query_param("grant_type", "refresh_token"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(make_session_body()))
+        .mount(&server)
+        .await;
+    let c = client(&server);
+    let mut session: rust_supabase_sdk::auth::Session =
+        serde_json::from_value(make_session_body()).unwrap();
+    session.expires_at = chrono::Utc::now().timestamp() + 10; // 10s — within threshold
+    c.auth().set_session(session);
+
+    let result = c.auth().refresh_session_if_needed(60).await.unwrap();
+    assert!(result.is_some(), "should have refreshed");
+}
+
+#[tokio::test]
+async fn auth_get_user_with_no_session_falls_back_to_api_key() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/auth/v1/user"))
+        // The fallback bearer is the api_key set in builder.
+        .and(header("authorization", "Bearer test-key"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "id": "u1", "aud": "auth", "role": "auth",
+            "created_at": "2024-01-01T00:00:00Z"
+        })))
+        .mount(&server)
+        .await;
+    let u = client(&server).auth().get_user().await.unwrap();
+    assert_eq!(u.id, "u1");
+}
+
+#[tokio::test]
+async fn auth_update_user_with_no_session() {
+    // Exercises the `None` arm of `update_user`'s session match.
+    let server = MockServer::start().await;
+    Mock::given(method("PUT"))
+        .and(path("/auth/v1/user"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "id": "u1", "aud": "auth", "role": "auth",
+            "created_at": "2024-01-01T00:00:00Z"
+        })))
+        .mount(&server)
+        .await;
+    let u = client(&server)
+        .auth()
+        .update_user(UpdateUserAttributes::default())
+        .await
+        .unwrap();
+    assert_eq!(u.id, "u1");
+}
+
+#[tokio::test]
+async fn auth_sign_out_global_hits_logout_endpoint() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/auth/v1/logout"))
+        .respond_with(ResponseTemplate::new(200).set_body_string(""))
+        .expect(1)
+        .mount(&server)
+        .await;
+    let c = client(&server);
+    let session: rust_supabase_sdk::auth::Session =
+        serde_json::from_value(make_session_body()).unwrap();
+    c.auth().set_session(session);
+    c.auth().sign_out(SignOutScope::Global).await.unwrap();
+    assert!(c.auth().get_session().is_none());
+}
+
+#[tokio::test]
+async fn auth_sign_out_others_scope() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/auth/v1/logout"))
+        .and(query_param("scope", "others"))
+        .respond_with(ResponseTemplate::new(200).set_body_string(""))
+        .expect(1)
+        .mount(&server)
+        .await;
+    let c = client(&server);
+    let session: rust_supabase_sdk::auth::Session =
+        serde_json::from_value(make_session_body()).unwrap();
+    c.auth().set_session(session);
+    c.auth().sign_out(SignOutScope::Others).await.unwrap();
+    // `sign_out` always clears the local session regardless of scope —
+    // the scope only changes which sessions the server revokes.
+    assert!(c.auth().get_session().is_none());
+}
+
+#[tokio::test]
+async fn auth_sign_in_with_oauth_returns_authorize_url() {
+    // No network call — just URL construction.
+    let server = MockServer::start().await;
+    let mut qp = std::collections::HashMap::new();
+    qp.insert("k".to_string(), "v".to_string());
+    let flow = client(&server).auth().sign_in_with_oauth(
+        "github",
+        OAuthOptions {
             redirect_to: Some("https://x.co/cb".into()),
             scopes: Some("read:user".into()),
             query_params: qp,
@@ -1127,6 +1733,31 @@ async fn upload_to_signed_url_malformed_json() {
 // auth/admin.rs — invite_user_by_email + generate_link
 // ===========================================================================
 
+#[tokio::test]
+async fn admin_list_and_delete_factors() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/auth/v1/admin/users/u1/factors"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!([
+            {"id": "f1", "factor_type": "totp", "status": "verified", "friendly_name": "phone app"}
+        ])))
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("DELETE"))
+        .and(path("/auth/v1/admin/users/u1/factors/f1"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({"id": "f1"})))
+        .expect(1)
+        .mount(&server)
+        .await;
+    let admin = client(&server).auth().admin();
+    let factors = admin.list_factors("u1").await.unwrap();
+    assert_eq!(factors[0].friendly_name.as_deref(), Some("phone app"));
+    assert!(factors[0].is_verified());
+    admin.delete_factor("u1", "f1").await.unwrap();
+    server.verify().await;
+}
+
 #[tokio::test]
 async fn admin_invite_user_by_email_minimal() {
     let server = MockServer::start().await;
@@ -1558,3 +2189,1308 @@ async fn auth_token_response_decode_error_in_parse_session() {
     assert!(matches!(err, SupabaseError::Decode { .. }));
 }
 
+
+#[tokio::test]
+async fn head_counts_without_downloading_rows() {
+    let server = MockServer::start().await;
+    Mock::given(method("HEAD"))
+        .and(path("/rest/v1/articles"))
+        .and(query_param("published", "eq.true"))
+        .and(header("prefer", "count=exact"))
+        .respond_with(ResponseTemplate::new(200).insert_header("content-range", "0-24/42"))
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("HEAD"))
+        .and(path("/rest/v1/articles"))
+        .and(header("prefer", "count=planned"))
+        .respond_with(ResponseTemplate::new(200).insert_header("content-range", "*/1000"))
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/rest/v1/articles"))
+        .and(query_param("limit", "1"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!([])))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let client = client(&server);
+    let n = client.from("articles").select("*").eq("published", true).head().await.unwrap();
+    assert_eq!(n, 42);
+    let n = client.from_row::<Article>().count_only(CountMode::Planned).await.unwrap();
+    assert_eq!(n, 1000);
+    assert!(!client.from_row::<Article>().exists().await.unwrap());
+    // Writes can't be counted with HEAD.
+    let err = client.from("articles").delete().head().await.unwrap_err();
+    assert!(matches!(err, SupabaseError::Unexpected(_)), "{err:?}");
+    server.verify().await;
+}
+
+// ===========================================================================
+// postgrest/paginate.rs — offset + keyset page streams
+// ===========================================================================
+
+#[tokio::test]
+async fn paginate_offset_fetches_until_short_page_and_reports_total() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/rest/v1/t"))
+        .and(query_param("offset", "0"))
+        .and(query_param("limit", "2"))
+        .and(header("prefer", "count=exact"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .insert_header("content-range", "0-1/3")
+                .set_body_json(json!([{"id": 1}, {"id": 2}])),
+        )
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/rest/v1/t"))
+        .and(query_param("offset", "2"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!([{"id": 3}])))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let mut pages = client(&server)
+        .from("t")
+        .select("*")
+        .count(CountMode::Exact)
+        .paginate(2);
+    assert_eq!(pages.total().await.unwrap(), Some(3));
+    let rows: Vec<Value> = pages.map(|r| r.unwrap()).collect().await;
+    assert_eq!(rows, vec![json!({"id": 1}), json!({"id": 2}), json!({"id": 3})]);
+    server.verify().await;
+}
+
+#[tokio::test]
+async fn paginate_keyset_filters_on_last_seen_value() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/rest/v1/t"))
+        .and(query_param("id", "gt.2"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!([])))
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/rest/v1/t"))
+        .and(query_param("order", "id.asc"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!([{"id": 1}, {"id": 2}])))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let rows: Vec<Value> = client(&server)
+        .from("t")
+        .select("*")
+        .paginate_keyset(2, "id")
+        .map(|r| r.unwrap())
+        .collect()
+        .await;
+    assert_eq!(rows.len(), 2);
+    server.verify().await;
+}
+
+#[tokio::test]
+async fn paginate_stops_after_first_error() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/rest/v1/t"))
+        .respond_with(ResponseTemplate::new(400).set_body_json(json!({
+            "code": "42703", "message": "column t.nope does not exist"
+        })))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let items: Vec<_> = client(&server)
+        .from("t")
+        .select("nope")
+        .paginate(10)
+        .collect()
+        .await;
+    assert_eq!(items.len(), 1);
+    assert!(matches!(items[0], Err(SupabaseError::Postgrest(_))));
+}
+
+// ===========================================================================
+// postgrest/cursor.rs — typed keyset pages
+// ===========================================================================
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+struct Post {
+    id: i64,
+    created_at: String,
+}
+
+impl Row for Post {
+    const TABLE: &'static str = "posts";
+}
+
+#[allow(non_upper_case_globals)]
+impl Post {
+    const id: Column<Post, i64> = Column::new("id");
+    const created_at: Column<Post, String> = Column::new("created_at");
+}
+
+#[tokio::test]
+async fn cursor_page_round_trips_next_cursor_into_following_request() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/rest/v1/posts"))
+        .and(query_param("order", "created_at.asc,id.asc"))
+        .and(query_param("limit", "2"))
+        .and(query_param(
+            "or",
+            "(created_at.gt.\"2024-01-02\",and(created_at.eq.\"2024-01-02\",id.gt.\"2\"))",
+        ))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!([
+            {"id": 3, "created_at": "2024-01-03"}
+        ])))
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/rest/v1/posts"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!([
+            {"id": 1, "created_at": "2024-01-01"},
+            {"id": 2, "created_at": "2024-01-02"}
+        ])))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let c = client(&server);
+    let first = c
+        .from_row::<Post>()
+        .after(Post::created_at, None)
+        .tie_breaker(Post::id)
+        .limit(2)
+        .await
+        .unwrap();
+    assert_eq!(first.rows.len(), 2);
+    // The cursor survives a trip through an API response body.
+    let token = serde_json::to_value(&first).unwrap()["next_cursor"]
+        .as_str()
+        .unwrap()
+        .to_string();
+
+    let second = c
+        .from_row::<Post>()
+        .after(Post::created_at, Cursor::decode(&token).unwrap())
+        .tie_breaker(Post::id)
+        .limit(2)
+        .await
+        .unwrap();
+    assert_eq!(second.rows[0].id, 3);
+    assert!(second.next_cursor.is_none());
+    server.verify().await;
+}
+
+// ===========================================================================
+// postgrest/embed.rs — typed embeds
+// ===========================================================================
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+struct Comment {
+    id: i64,
+    post_id: i64,
+}
+
+impl Row for Comment {
+    const TABLE: &'static str = "comments";
+}
+
+#[allow(non_upper_case_globals)]
+impl Comment {
+    const id: Column<Comment, i64> = Column::new("id");
+}
+
+impl Related<Comment> for Post {
+    const RELATION: Relation<Self, Comment> = Relation::to_many("comments", Some("post_id"));
+}
+
+#[derive(Debug, serde::Deserialize)]
+struct PostWithRelations {
+    #[serde(flatten)]
+    row: Post,
+    #[serde(default)]
+    comments: Option<Vec<Comment>>,
+}
+
+impl Embeddable for Post {
+    type WithRelations = PostWithRelations;
+}
+
+#[tokio::test]
+async fn embed_with_decodes_into_with_relations() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/rest/v1/posts"))
+        .and(query_param("select", "*,comments:comments!post_id(*)"))
+        .and(query_param("comments.order", "id.desc"))
+        .and(query_param("comments.limit", "1"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!([
+            {"id": 1, "created_at": "2024-01-01", "comments": [{"id": 9, "post_id": 1}]}
+        ])))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let rows = client(&server)
+        .from_row::<Post>()
+        .embed_with::<Comment>(|c| c.order(Comment::id, false).limit(1))
+        .await
+        .unwrap();
+    assert_eq!(rows[0].row.id, 1);
+    let comments = rows[0].comments.as_ref().unwrap();
+    assert_eq!((comments[0].id, comments[0].post_id), (9, 1));
+    server.verify().await;
+}
+
+// ===========================================================================
+// postgrest/batch.rs — chunked inserts
+// ===========================================================================
+
+#[tokio::test]
+async fn insert_batched_reports_failed_chunk_and_keeps_going() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/rest/v1/events"))
+        .and(body_json(json!([{"n": 2}, {"n": 3}])))
+        .respond_with(ResponseTemplate::new(409).set_body_json(json!({
+            "code": "23505",
+            "message": "duplicate key value violates unique constraint"
+        })))
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/rest/v1/events"))
+        .and(header("prefer", "return=minimal"))
+        .respond_with(ResponseTemplate::new(201))
+        .expect(2)
+        .mount(&server)
+        .await;
+
+    let rows: Vec<Value> = (0..5).map(|n| json!({"n": n})).collect();
+    let report = client(&server)
+        .from("events")
+        .insert_batched(&rows, BatchOptions { chunk_size: 2, concurrency: 2, ..Default::default() })
+        .await
+        .unwrap();
+
+    assert_eq!(report.chunks, vec![0..2, 2..4, 4..5]);
+    assert_eq!(report.succeeded, vec![0, 2]);
+    assert_eq!(report.rows_written(), 3);
+    assert_eq!(report.failed.len(), 1);
+    assert_eq!(report.failed[0].rows, 2..4);
+    let pg = report.failed[0].postgrest_error().unwrap();
+    assert_eq!(pg.code.as_deref(), Some("23505"));
+    assert!(report.skipped.is_empty());
+    server.verify().await;
+}
+
+#[tokio::test]
+async fn insert_batched_upserts_and_stops_on_first_failure() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/rest/v1/events"))
+        .and(query_param("on_conflict", "id"))
+        .and(headers("prefer", vec!["resolution=merge-duplicates", "return=minimal"]))
+        .respond_with(ResponseTemplate::new(400).set_body_json(json!({"message": "bad row"})))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let rows: Vec<Value> = (0..6).map(|id| json!({"id": id})).collect();
+    let report = client(&server)
+        .from("events")
+        .insert_batched(
+            &rows,
+            BatchOptions {
+                chunk_size: 2,
+                concurrency: 1,
+                on_conflict: Some("id".into()),
+                stop_on_first_failure: true,
+            },
+        )
+        .await
+        .unwrap();
+
+    assert!(!report.is_complete());
+    assert_eq!(report.failed[0].chunk, 0);
+    assert_eq!(report.skipped, vec![1, 2]);
+    server.verify().await;
+}
+
+// ===========================================================================
+// postgrest/aggregate.rs — grouped aggregates
+// ===========================================================================
+
+#[derive(Debug, serde::Deserialize)]
+struct DailyPosts {
+    created_at: String,
+    posts: i64,
+    max_id: i64,
+}
+
+#[tokio::test]
+async fn aggregate_groups_and_decodes_into_struct() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/rest/v1/posts"))
+        .and(query_param("select", "created_at,posts:count(),max_id:id.max()"))
+        .and(query_param("id", "gt.0"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!([
+            {"created_at": "2024-01-01", "posts": 2, "max_id": 7}
+        ])))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let rows = client(&server)
+        .from_row::<Post>()
+        .gt(Post::id, 0)
+        .group_by(Post::created_at)
+        .aggregate(Agg::count().alias("posts"))
+        .aggregate(Agg::max(Post::id).alias("max_id"))
+        .aggregate_as::<DailyPosts>()
+        .await
+        .unwrap();
+    assert_eq!(rows[0].created_at, "2024-01-01");
+    assert_eq!((rows[0].posts, rows[0].max_id), (2, 7));
+    server.verify().await;
+}
+
+// ===========================================================================
+// postgrest/logic.rs — filter expression trees
+// ===========================================================================
+
+#[tokio::test]
+async fn filter_tree_renders_quoted_or_param() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/rest/v1/posts"))
+        .and(query_param(
+            "or",
+            "(and(id.gt.10,created_at.like.2024-01-0*),created_at.not.eq.\"a,b\")",
+        ))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!([
+            {"id": 11, "created_at": "2024-01-02"}
+        ])))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let rows: Vec<Post> = client(&server)
+        .from_row::<Post>()
+        .where_(
+            (Filter::gt(Post::id, 10) & Filter::like(Post::created_at, "2024-01-0*"))
+                | !Filter::eq(Post::created_at, "a,b".to_string()),
+        )
+        .await
+        .unwrap();
+    assert_eq!(rows[0].id, 11);
+    server.verify().await;
+}
+
+// ===========================================================================
+// postgrest/json_path.rs — JSON column paths
+// ===========================================================================
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+struct Doc {
+    id: i64,
+    data: Value,
+}
+
+impl Row for Doc {
+    const TABLE: &'static str = "docs";
+}
+
+#[allow(non_upper_case_globals)]
+impl Doc {
+    const data: Column<Doc, Value> = Column::new("data");
+}
+
+#[derive(Debug, serde::Deserialize)]
+struct DocTheme {
+    id: i64,
+    theme: String,
+}
+
+#[tokio::test]
+async fn json_paths_select_filter_and_order() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/rest/v1/docs"))
+        .and(query_param("select", "id,theme:data->settings->>theme"))
+        .and(query_param("data->>status", "eq.active"))
+        .and(query_param("data->stats->views", "gte.10"))
+        .and(query_param("order", "data->stats->views.desc"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!([
+            {"id": 1, "theme": "dark"}
+        ])))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let views = Doc::data.path("stats").path("views").typed::<i64>();
+    let rows = client(&server)
+        .from_row::<Doc>()
+        .select("id")
+        .select_json_as("theme", Doc::data.path("settings").text("theme"))
+        .where_(Filter::eq(Doc::data.text("status"), "active".to_string()))
+        .where_(Filter::gte(views.clone(), 10))
+        .order(views, false)
+        .returns::<DocTheme>()
+        .await
+        .unwrap();
+    assert_eq!((rows[0].id, rows[0].theme.as_str()), (1, "dark"));
+    server.verify().await;
+}
+
+// ===========================================================================
+// postgrest/types.rs — ranges and arrays
+// ===========================================================================
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+struct Booking {
+    id: i64,
+    seats: PgRange<i32>,
+    tags: Vec<String>,
+}
+
+impl Row for Booking {
+    const TABLE: &'static str = "bookings";
+}
+
+#[allow(non_upper_case_globals)]
+impl Booking {
+    const seats: Column<Booking, PgRange<i32>> = Column::new("seats");
+    const tags: Column<Booking, Vec<String>> = Column::new("tags");
+}
+
+#[tokio::test]
+async fn range_and_array_filters_decode_ranges() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/rest/v1/bookings"))
+        .and(query_param("seats", "ov.[1,5)"))
+        .and(query_param("tags", "cs.{vip,\"late night\"}"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!([
+            {"id": 1, "seats": "[2,4)", "tags": ["vip", "late night"]}
+        ])))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let rows = client(&server)
+        .from_row::<Booking>()
+        .range_overlaps(Booking::seats, PgRange::from(1..5))
+        .array_contains(Booking::tags, ["vip".to_string(), "late night".to_string()])
+        .await
+        .unwrap();
+    assert_eq!(rows[0].seats, PgRange::from(2..4));
+    assert_eq!((rows[0].id, rows[0].tags.len()), (1, 2));
+    server.verify().await;
+}
+
+// ===========================================================================
+// postgrest/typed_builder.rs — typed writes
+// ===========================================================================
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+struct Note {
+    id: i64,
+    body: String,
+    pinned: Option<bool>,
+}
+
+impl Row for Note {
+    const TABLE: &'static str = "notes";
+}
+
+#[allow(non_upper_case_globals)]
+impl Note {
+    const id: Column<Note, i64> = Column::new("id");
+}
+
+#[derive(Debug, Clone, serde::Serialize)]
+struct NoteInsert {
+    body: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pinned: Option<bool>,
+}
+
+#[derive(Debug, Clone, Default, serde::Serialize)]
+struct NoteUpdate {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    body: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pinned: Option<Option<bool>>,
+}
+
+impl Writable for Note {
+    type Insert = NoteInsert;
+    type Update = NoteUpdate;
+}
+
+#[tokio::test]
+async fn typed_insert_many_and_update_return_rows() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/rest/v1/notes"))
+        .and(query_param("columns", "body,pinned"))
+        .and(headers("prefer", vec!["missing=default", "return=representation"]))
+        .and(body_json(json!([{"body": "a"}, {"body": "b", "pinned": true}])))
+        .respond_with(ResponseTemplate::new(201).set_body_json(json!([
+            {"id": 1, "body": "a", "pinned": null},
+            {"id": 2, "body": "b", "pinned": true}
+        ])))
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("PATCH"))
+        .and(path("/rest/v1/notes"))
+        .and(query_param("id", "eq.2"))
+        .and(header("prefer", "return=representation"))
+        .and(body_json(json!({"pinned": null})))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!([
+            {"id": 2, "body": "b", "pinned": null}
+        ])))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let created = client(&server)
+        .from_row::<Note>()
+        .insert_many([
+            NoteInsert { body: "a".into(), pinned: None },
+            NoteInsert { body: "b".into(), pinned: Some(true) },
+        ])
+        .await
+        .unwrap();
+    assert_eq!(created.iter().map(|n| n.id).collect::<Vec<_>>(), [1, 2]);
+
+    let updated = client(&server)
+        .from_row::<Note>()
+        .update(NoteUpdate { pinned: Some(None), ..Default::default() })
+        .eq(Note::id, 2)
+        .await
+        .unwrap();
+    assert_eq!((updated[0].body.as_str(), updated[0].pinned), ("b", None));
+    server.verify().await;
+}
+
+// ===========================================================================
+// postgrest/tracked.rs — change-tracked saves
+// ===========================================================================
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+struct Article {
+    id: i64,
+    title: String,
+    updated_at: String,
+}
+
+impl Row for Article {
+    const TABLE: &'static str = "articles";
+    const PRIMARY_KEY: &'static [&'static str] = &["id"];
+}
+
+#[allow(non_upper_case_globals)]
+impl Article {
+    const id: Column<Article, i64> = Column::new("id");
+    const updated_at: Column<Article, String> = Column::new("updated_at");
+}
+
+#[tokio::test]
+async fn save_patches_dirty_columns_and_reports_conflicts() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/rest/v1/articles"))
+        .and(query_param("id", "eq.7"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!([
+            {"id": 7, "title": "Draft", "updated_at": "2024-05-01T10:00:00+00:00"}
+        ])))
+        .mount(&server)
+        .await;
+    Mock::given(method("PATCH"))
+        .and(path("/rest/v1/articles"))
+        .and(query_param("id", "eq.7"))
+        .and(query_param("updated_at", "eq.2024-05-01T10:00:00+00:00"))
+        .and(header("prefer", "return=representation"))
+        .and(body_json(json!({"title": "Final"})))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!([
+            {"id": 7, "title": "Final", "updated_at": "2024-05-01T10:05:00+00:00"}
+        ])))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let client = client(&server);
+    let mut article: Tracked<Article> = client
+        .from_row::<Article>()
+        .eq(Article::id, 7)
+        .execute_tracked()
+        .await
+        .unwrap()
+        .remove(0);
+    article.title = "Final".into();
+    article.guard(Article::updated_at);
+    let saved = client.save(&article).await.unwrap();
+    assert_eq!(saved.updated_at, "2024-05-01T10:05:00+00:00");
+    assert!(!saved.is_dirty());
+    server.verify().await;
+
+    // A second writer got there first: the guarded PATCH matches nothing.
+    server.reset().await;
+    Mock::given(method("PATCH"))
+        .and(path("/rest/v1/articles"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!([])))
+        .expect(1)
+        .mount(&server)
+        .await;
+    let mut stale = Tracked::new(article.into_inner());
+    stale.title = "Other".into();
+    stale.guard(Article::updated_at);
+    let err = client.save(&stale).await.unwrap_err();
+    assert!(matches!(err, SupabaseError::Conflict { .. }), "{err:?}");
+    server.verify().await;
+}
+
+// ===========================================================================
+// postgrest/lookup.rs — primary-key lookups
+// ===========================================================================
+
+impl Keyed for Article {
+    type PrimaryKey = i64;
+}
+
+#[tokio::test]
+async fn primary_key_lookups_filter_by_key() {
+    let server = MockServer::start().await;
+    let row = json!({"id": 7, "title": "T", "updated_at": "2024-05-01T10:00:00+00:00"});
+    Mock::given(method("GET"))
+        .and(path("/rest/v1/articles"))
+        .and(query_param("id", "eq.7"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!([row])))
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/rest/v1/articles"))
+        .and(query_param("id", "in.(7,8)"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!([row])))
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("DELETE"))
+        .and(path("/rest/v1/articles"))
+        .and(query_param("id", "eq.8"))
+        .and(header("prefer", "return=representation"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!([])))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let client = client(&server);
+    let found = client.find::<Article>(7).await.unwrap().unwrap();
+    assert_eq!(found.title, "T");
+    let many = client.find_many::<Article>([7, 8]).await.unwrap();
+    assert_eq!(many.len(), 1);
+    assert!(client.delete_by_pk::<Article>(8).await.unwrap().is_none());
+    // An empty key list never reaches the server.
+    assert!(client.find_many::<Article>([]).await.unwrap().is_empty());
+    server.verify().await;
+}
+
+// ===========================================================================
+// postgrest/cache.rs — query cache
+// ===========================================================================
+
+#[tokio::test]
+async fn query_cache_serves_repeat_selects_until_a_write() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/rest/v1/articles"))
+        .and(header("authorization", "Bearer test-key"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!([{"id": 1}])))
+        .expect(3)
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/rest/v1/articles"))
+        .and(header("authorization", "Bearer user-jwt"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!([{"id": 2}])))
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("PATCH"))
+        .and(path("/rest/v1/articles"))
+        .respond_with(ResponseTemplate::new(204))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let client = SupabaseClient::builder(server.uri(), "test-key")
+        .query_cache(CacheConfig::default())
+        .build();
+    let select = || client.from("articles").select("*").eq("id", 1);
+    // Miss, then hit.
+    assert_eq!(select().await.unwrap(), vec![json!({"id": 1})]);
+    assert_eq!(select().await.unwrap(), vec![json!({"id": 1})]);
+    // Another bearer gets its own entry.
+    let user = client.with_access_token("user-jwt");
+    let rows = user.from("articles").select("*").eq("id", 1).await.unwrap();
+    assert_eq!(rows, vec![json!({"id": 2})]);
+    // `no_cache` always goes to the server.
+    select().no_cache().await.unwrap();
+    // A write drops the table's entries, for every client sharing the cache.
+    client.from("articles").update(json!({"title": "x"})).eq("id", 1).await.unwrap();
+    assert!(client.query_cache().unwrap().is_empty());
+    select().await.unwrap();
+    select().await.unwrap();
+    server.verify().await;
+}
+
+#[tokio::test]
+async fn query_cache_drops_a_select_that_raced_a_write() {
+    let server = MockServer::start().await;
+    // The first select is slow, so the write lands while it is in flight.
+    Mock::given(method("GET"))
+        .and(path("/rest/v1/articles"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_json(json!([{"id": 1, "title": "old"}]))
+                .set_delay(Duration::from_millis(300)),
+        )
+        .up_to_n_times(1)
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/rest/v1/articles"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!([{"id": 1, "title": "new"}])))
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("PATCH"))
+        .and(path("/rest/v1/articles"))
+        .respond_with(ResponseTemplate::new(204))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let client = SupabaseClient::builder(server.uri(), "test-key")
+        .query_cache(CacheConfig::default())
+        .build();
+    let reader = client.clone();
+    let slow = tokio::spawn(async move { reader.from("articles").select("*").await });
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    client.from("articles").update(json!({"title": "new"})).eq("id", 1).await.unwrap();
+
+    let stale = slow.await.unwrap().unwrap();
+    assert_eq!(stale[0]["title"], "old");
+    assert!(client.query_cache().unwrap().is_empty(), "stale response was cached");
+    let fresh = client.from("articles").select("*").await.unwrap();
+    assert_eq!(fresh[0]["title"], "new");
+    server.verify().await;
+}
+
+#[tokio::test]
+async fn query_cache_skips_selects_with_embeds() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/rest/v1/articles"))
+        .and(query_param("select", "*,author:authors(*)"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!([{"id": 1, "author": {"name": "a"}}])))
+        .expect(1)
+        .up_to_n_times(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/rest/v1/articles"))
+        .and(query_param("select", "*,author:authors(*)"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!([{"id": 1, "author": {"name": "b"}}])))
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("PATCH"))
+        .and(path("/rest/v1/authors"))
+        .respond_with(ResponseTemplate::new(204))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let client = SupabaseClient::builder(server.uri(), "test-key")
+        .query_cache(CacheConfig::default())
+        .build();
+    let select = || client.from("articles").select("*,author:authors(*)");
+    assert_eq!(select().await.unwrap()[0]["author"]["name"], "a");
+    assert!(client.query_cache().unwrap().is_empty());
+    client.from("authors").update(json!({"name": "b"})).eq("id", 1).await.unwrap();
+    assert_eq!(select().await.unwrap()[0]["author"]["name"], "b");
+    server.verify().await;
+}
+
+// ===========================================================================
+// postgrest/explain.rs — query plans
+// ===========================================================================
+
+#[tokio::test]
+async fn explain_sends_plan_accept_header_with_builder_filters() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/rest/v1/posts"))
+        .and(query_param("id", "eq.7"))
+        .and(header("accept", "application/vnd.pgrst.plan+json; options=analyze"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!([{
+            "Plan": {
+                "Node Type": "Index Scan", "Relation Name": "posts",
+                "Index Name": "posts_pkey", "Startup Cost": 0.15, "Total Cost": 8.17,
+                "Plan Rows": 1, "Plan Width": 72,
+                "Actual Startup Time": 0.01, "Actual Total Time": 0.02,
+                "Actual Rows": 1, "Actual Loops": 1
+            },
+            "Planning Time": 0.08,
+            "Execution Time": 0.03
+        }])))
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/rest/v1/posts"))
+        .and(header("accept", "application/vnd.pgrst.plan+text"))
+        .respond_with(ResponseTemplate::new(200).set_body_string("Seq Scan on posts"))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let c = client(&server);
+    let explain = c
+        .from_row::<Post>()
+        .eq(Post::id, 7)
+        .explain(ExplainOptions { analyze: true, ..Default::default() })
+        .await
+        .unwrap();
+    let plan = explain.plan().unwrap();
+    assert!(plan.uses_index("posts_pkey"));
+    assert_eq!(plan.root.actual_rows, Some(1.0));
+    assert_eq!(plan.execution_time, Some(0.03));
+
+    let text = c
+        .from("posts")
+        .select("*")
+        .explain(ExplainOptions { format: ExplainFormat::Text, ..Default::default() })
+        .await
+        .unwrap();
+    assert_eq!(text.text(), Some("Seq Scan on posts"));
+    server.verify().await;
+}
+
+// ===========================================================================
+// postgrest/format.rs — CSV / GeoJSON
+// ===========================================================================
+
+#[tokio::test]
+async fn execute_csv_streams_body_with_csv_accept() {
+    let server = MockServer::start().await;
+    let csv = "id,total\n1,9.5\n2,12\n";
+    Mock::given(method("GET"))
+        .and(path("/rest/v1/orders"))
+        .and(query_param("select", "id,total"))
+        .and(header("accept", "text/csv"))
+        .respond_with(ResponseTemplate::new(200).set_body_string(csv))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let mut stream = client(&server)
+        .from("orders")
+        .select("id,total")
+        .execute_csv()
+        .await
+        .unwrap();
+    let mut body = Vec::new();
+    while let Some(chunk) = stream.next().await {
+        body.extend_from_slice(&chunk.unwrap());
+    }
+    assert_eq!(String::from_utf8(body).unwrap(), csv);
+    server.verify().await;
+}
+
+#[tokio::test]
+async fn execute_geojson_decodes_feature_collection() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/rest/v1/places"))
+        .and(header("accept", "application/geo+json"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "type": "FeatureCollection",
+            "features": [{
+                "type": "Feature",
+                "geometry": {"type": "Point", "coordinates": [151.2, -33.8]},
+                "properties": {"name": "Sydney"}
+            }]
+        })))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let fc = client(&server)
+        .from("places")
+        .select("name,location")
+        .execute_geojson()
+        .await
+        .unwrap();
+    assert_eq!(fc.features[0].properties["name"], "Sydney");
+    assert_eq!(
+        fc.features[0].geometry,
+        Some(Geometry::Point { coordinates: vec![151.2, -33.8] })
+    );
+    server.verify().await;
+}
+
+rust_supabase_sdk::projection! {
+    #[derive(serde::Serialize, serde::Deserialize)]
+    struct RenamedPostFilter: Post {
+        #[serde(rename = "when")]
+        created_at: String,
+    }
+}
+
+#[tokio::test]
+async fn csv_and_geojson_skip_the_request_for_empty_and_invalid_queries() {
+    let server = MockServer::start().await;
+    let c = client(&server);
+
+    let csv = c
+        .from("orders")
+        .select("id")
+        .is_in("id", Vec::<i64>::new())
+        .execute_csv()
+        .await
+        .unwrap()
+        .collect_bytes()
+        .await
+        .unwrap();
+    assert!(csv.is_empty());
+
+    let fc = c
+        .from("places")
+        .select("name,location")
+        .is_in("id", Vec::<i64>::new())
+        .execute_geojson()
+        .await
+        .unwrap();
+    assert!(fc.features.is_empty());
+
+    let err = c
+        .from_row::<Post>()
+        .match_(&RenamedPostFilter { created_at: "a".to_string() })
+        .execute_csv()
+        .await
+        .unwrap_err();
+    match err {
+        SupabaseError::Unexpected(msg) => assert!(msg.starts_with("invalid query: "), "{msg}"),
+        other => panic!("expected Unexpected, got {other:?}"),
+    }
+
+    assert!(server.received_requests().await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn insert_csv_sends_text_csv_body() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/rest/v1/orders"))
+        .and(header("content-type", "text/csv"))
+        .and(body_string("id,total\n3,4.5\n"))
+        .respond_with(ResponseTemplate::new(201).set_body_json(json!([{"id": 3, "total": 4.5}])))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let rows = client(&server)
+        .from("orders")
+        .insert_csv("id,total\n3,4.5\n")
+        .select_returning("*")
+        .await
+        .unwrap();
+    assert_eq!(rows, vec![json!({"id": 3, "total": 4.5})]);
+    server.verify().await;
+}
+
+// ===========================================================================
+// postgrest/stream.rs — incremental row decoding
+// ===========================================================================
+
+#[tokio::test]
+async fn execute_stream_yields_typed_rows() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/rest/v1/posts"))
+        .respond_with(ResponseTemplate::new(200).set_body_string(
+            r#"[{"id":1,"created_at":"a"},{"id":2,"created_at":"b, [c]"}]"#,
+        ))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let rows: Vec<Post> = client(&server)
+        .from_row::<Post>()
+        .execute_stream()
+        .await
+        .unwrap()
+        .map(|r| r.unwrap())
+        .collect()
+        .await;
+    assert_eq!(rows.len(), 2);
+    assert_eq!(rows[1].created_at, "b, [c]");
+    server.verify().await;
+}
+
+#[tokio::test]
+async fn execute_stream_maps_errors_and_enforces_row_cap() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/rest/v1/missing"))
+        .respond_with(ResponseTemplate::new(404).set_body_json(json!({
+            "code": "42P01", "message": "relation does not exist"
+        })))
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/rest/v1/big"))
+        .respond_with(ResponseTemplate::new(200).set_body_string(
+            r#"[{"n":1},{"blob":"0123456789012345678901234567890123456789"},{"n":3}]"#,
+        ))
+        .mount(&server)
+        .await;
+
+    let c = client(&server);
+    let err = c.from("missing").select("*").execute_stream().await.unwrap_err();
+    match err {
+        SupabaseError::Postgrest(e) => assert_eq!(e.code.as_deref(), Some("42P01")),
+        other => panic!("expected Postgrest, got {other:?}"),
+    }
+
+    let results: Vec<_> = c
+        .from("big")
+        .select("*")
+        .execute_stream()
+        .await
+        .unwrap()
+        .max_row_bytes(16)
+        .collect()
+        .await;
+    assert_eq!(results.len(), 2);
+    assert_eq!(results[0].as_ref().unwrap(), &json!({"n": 1}));
+    match &results[1] {
+        Err(SupabaseError::Decode { message, .. }) => assert!(message.contains("max_row_bytes"), "{message}"),
+        other => panic!("expected Decode, got {other:?}"),
+    }
+}
+
+#[tokio::test]
+async fn execute_stream_skips_the_request_for_empty_and_invalid_queries() {
+    let server = MockServer::start().await;
+    let c = client(&server);
+
+    let rows: Vec<_> = c
+        .from_row::<Post>()
+        .is_in(Post::id, Vec::new())
+        .execute_stream()
+        .await
+        .unwrap()
+        .collect()
+        .await;
+    assert!(rows.is_empty());
+
+    let err = c
+        .from_row::<Post>()
+        .match_(&RenamedPostFilter { created_at: "a".to_string() })
+        .execute_stream()
+        .await
+        .unwrap_err();
+    assert!(matches!(err, SupabaseError::Unexpected(msg) if msg.starts_with("invalid query: ")));
+
+    assert!(server.received_requests().await.unwrap().is_empty());
+}
+
+// ===========================================================================
+// postgrest/transaction.rs — atomic multi-statement RPC
+// ===========================================================================
+
+#[tokio::test]
+async fn transaction_ships_statements_in_one_rpc_call() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/rest/v1/rpc/sdk_transaction"))
+        .and(body_json(json!({"statements": [
+            {
+                "op": "update", "schema": "public", "table": "accounts",
+                "body": {"balance": 90}, "columns": null, "returning": false,
+                "filters": [{"column": "id", "op": "eq", "value": "1", "negate": false}]
+            },
+            {
+                "op": "insert", "schema": "public", "table": "posts",
+                "body": {"id": 7, "created_at": "now"}, "columns": null, "returning": true,
+                "filters": []
+            }
+        ]})))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!([
+            {"count": 1, "rows": []},
+            {"count": 1, "rows": [{"id": 7, "created_at": "now"}]}
+        ])))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let c = client(&server);
+    let results = c
+        .transaction()
+        .push(c.from("accounts").update(json!({"balance": 90})).eq("id", 1))
+        .push(
+            c.from("posts")
+                .insert(json!({"id": 7, "created_at": "now"}))
+                .select_returning("*"),
+        )
+        .execute()
+        .await
+        .unwrap();
+    assert_eq!(results.len(), 2);
+    assert_eq!(results[0].count, 1);
+    let posts: Vec<Post> = results[1].decode().unwrap();
+    assert_eq!(posts[0].id, 7);
+    server.verify().await;
+}
+
+#[tokio::test]
+async fn transaction_failure_surfaces_postgrest_error() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/rest/v1/rpc/tx"))
+        .respond_with(ResponseTemplate::new(409).set_body_json(json!({
+            "code": "23505",
+            "message": "duplicate key value violates unique constraint \"posts_pkey\"",
+            "details": "statement 1: Key (id)=(7) already exists.",
+            "hint": null
+        })))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let c = client(&server);
+    let err = c
+        .transaction()
+        .function("tx")
+        .push(c.from("posts").delete().eq("id", 1))
+        .push(c.from("posts").insert(json!({"id": 7})))
+        .execute()
+        .await
+        .unwrap_err();
+    match err {
+        SupabaseError::Postgrest(e) => {
+            assert_eq!(e.code.as_deref(), Some("23505"));
+            assert!(e.details.as_deref().unwrap().starts_with("statement 1:"));
+        }
+        other => panic!("expected Postgrest, got {other:?}"),
+    }
+}
+
+// ===========================================================================
+// jwt/verify.rs — local verification against the project JWKS
+// ===========================================================================
+
+#[cfg(feature = "jwt")]
+mod jwt {
+    use super::*;
+    use base64::engine::general_purpose::STANDARD;
+    use base64::Engine;
+    use jsonwebtoken::{Algorithm, EncodingKey, Header};
+    use rust_supabase_sdk::jwt::JwtVerifier;
+
+    /// P-256 keys as PKCS#8 DER, with the matching public JWK coordinates.
+    const KEY_A: &str = "MIGHAgEAMBMGByqGSM49AgEGCCqGSM49AwEHBG0wawIBAQQgNgMMMwz8S5QemtePFm53VEf74W9nMuBx1M5dG9uf/yKhRANCAATNiV85t4vK2kzQiNOmOY30kveeR8GDK+Mc3BO74CivMCLJ1zFpgtoujrT5uRog2HpahdxhXvH8k164+w5sD9T+";
+    const KEY_B: &str = "MIGHAgEAMBMGByqGSM49AgEGCCqGSM49AwEHBG0wawIBAQQgbyGI9coIk/PVMdcvsa4usjcFQ99kAKjRm5OWwXRgYL2hRANCAATxf5JkI6aR4eT4f1rU24i7+jI1W7S4VsNur0BrcUexZYHMA3Go1FZpfms5V1OLH/VT1O6vGDns/CSjGqzNSETn";
+
+    fn jwk(kid: &str, x: &str, y: &str) -> Value {
+        json!({ "kty": "EC", "crv": "P-256", "alg": "ES256", "use": "sig", "kid": kid, "x": x, "y": y })
+    }
+
+    fn key_a() -> Value {
+        jwk(
+            "key-a",
+            "zYlfObeLytpM0IjTpjmN9JL3nkfBgyvjHNwTu-AorzA",
+            "IsnXMWmC2i6OtPm5GiDYelqF3GFe8fyTXrj7DmwP1P4",
+        )
+    }
+
+    fn key_b() -> Value {
+        jwk(
+            "key-b",
+            "8X-SZCOmkeHk-H9a1NuIu_oyNVu0uFbDbq9Aa3FHsWU",
+            "gcwDcajUVml-azlXU4sf9VPU7q8YOez8JKMarM1IROc",
+        )
+    }
+
+    fn sign(kid: &str, der: &str) -> String {
+        let mut header = Header::new(Algorithm::ES256);
+        header.kid = Some(kid.into());
+        let claims = json!({
+            "sub": "u1", "role": "authenticated", "aud": "authenticated", "aal": "aal1",
+            "exp": chrono::Utc::now().timestamp() + 3600
+        });
+        let der = STANDARD.decode(der).unwrap();
+        jsonwebtoken::encode(&header, &claims, &EncodingKey::from_ec_der(&der)).unwrap()
+    }
+
+    #[tokio::test]
+    async fn verifier_caches_the_jwks_and_picks_up_rotated_keys() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/auth/v1/.well-known/jwks.json"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "keys": [key_a()] })))
+            .up_to_n_times(1)
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/auth/v1/.well-known/jwks.json"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(json!({ "keys": [key_a(), key_b(), { "kty": "oct", "k": "c2VjcmV0" }] })),
+            )
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let verifier = JwtVerifier::new(&client(&server));
+        let claims = verifier.verify(&sign("key-a", KEY_A)).await.unwrap();
+        assert_eq!(claims.sub, "u1");
+        // Served from the cache.
+        verifier.clone().verify(&sign("key-a", KEY_A)).await.unwrap();
+
+        // An unknown kid refetches the set once; the new key is then known.
+        let claims = verifier.verify(&sign("key-b", KEY_B)).await.unwrap();
+        assert_eq!(claims.role, "authenticated");
+        verifier.verify(&sign("key-b", KEY_B)).await.unwrap();
+
+        // A kid that still isn't published fails without another fetch.
+        let err = verifier.verify(&sign("key-c", KEY_B)).await.unwrap_err();
+        assert!(err.to_string().contains("key-c"), "{err}");
+        // A known kid with the wrong key fails the signature check.
+        let err = verifier.verify(&sign("key-a", KEY_B)).await.unwrap_err();
+        assert!(err.to_string().contains("signature"), "{err}");
+        server.verify().await;
+    }
+}
@@ -1,7 +1,7 @@
 //! Misuse: `.text_search` on a non-string column.
 //!
-//! Same string-only constraint as `.like` — the signature pins the column
-//! to `Column<R, String>`.
+//! Full-text search needs a `tsvector` column — the column's value type
+//! must implement `TextSearchable`.
 
 use rust_supabase_sdk::{
     postgrest::{Column, TextSearchType},
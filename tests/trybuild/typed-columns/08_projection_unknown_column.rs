@@ -0,0 +1,32 @@
+//! Misuse: a `projection!` field naming a column `Posts` doesn't have.
+//!
+//! Each field resolves to a `Posts::<column>` constant, so a typo (or a
+//! column from another table) fails to compile instead of failing at
+//! runtime with a PostgREST 400.
+
+use rust_supabase_sdk::{postgrest::Column, Row};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Posts {
+    id: String,
+    status: String,
+}
+impl Row for Posts {
+    const TABLE: &'static str = "posts";
+}
+#[allow(non_upper_case_globals)]
+impl Posts {
+    pub const id: Column<Posts, String> = Column::new("id");
+    pub const status: Column<Posts, String> = Column::new("status");
+}
+
+rust_supabase_sdk::projection! {
+    #[derive(Debug, Deserialize)]
+    struct PostSummary: Posts {
+        id: String,
+        state: String = stauts,
+    }
+}
+
+fn main() {}
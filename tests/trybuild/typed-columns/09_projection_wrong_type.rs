@@ -0,0 +1,31 @@
+//! Misuse: a `projection!` field whose type differs from the column's.
+//!
+//! Un-cast fields must use the column's declared Rust type; only an explicit
+//! `as "<cast>"` may change it.
+
+use rust_supabase_sdk::{postgrest::Column, Row};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Posts {
+    id: String,
+    view_count: i32,
+}
+impl Row for Posts {
+    const TABLE: &'static str = "posts";
+}
+#[allow(non_upper_case_globals)]
+impl Posts {
+    pub const id: Column<Posts, String> = Column::new("id");
+    pub const view_count: Column<Posts, i32> = Column::new("view_count");
+}
+
+rust_supabase_sdk::projection! {
+    #[derive(Debug, Deserialize)]
+    struct PostSummary: Posts {
+        id: String,
+        view_count: String,
+    }
+}
+
+fn main() {}
@@ -0,0 +1,31 @@
+//! Misuse: `Agg::sum` over a non-numeric column.
+//!
+//! `sum` / `avg` require the column's value type to implement `Numeric`;
+//! `Posts::status` is `Column<Posts, String>`.
+
+use rust_supabase_sdk::{
+    postgrest::{Agg, Column},
+    Row, SupabaseClient,
+};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Posts {
+    id: String,
+    status: String,
+}
+impl Row for Posts {
+    const TABLE: &'static str = "posts";
+}
+#[allow(non_upper_case_globals)]
+impl Posts {
+    pub const id: Column<Posts, String> = Column::new("id");
+    pub const status: Column<Posts, String> = Column::new("status");
+}
+
+fn main() {
+    let client = SupabaseClient::new("https://x", "k", None);
+    let _ = client
+        .from_row::<Posts>()
+        .aggregate(Agg::sum(Posts::status));
+}
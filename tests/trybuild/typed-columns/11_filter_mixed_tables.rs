@@ -0,0 +1,41 @@
+//! Misuse: combining `Filter`s built from two different row types.
+//!
+//! `Filter::eq(Posts::id, ..)` is a `Filter<Posts>`; `Users::id` gives a
+//! `Filter<Users>`, and `|` only joins filters over the same rows.
+
+use rust_supabase_sdk::{
+    postgrest::{Column, Filter},
+    Row, SupabaseClient,
+};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Posts {
+    id: String,
+}
+impl Row for Posts {
+    const TABLE: &'static str = "posts";
+}
+#[allow(non_upper_case_globals)]
+impl Posts {
+    pub const id: Column<Posts, String> = Column::new("id");
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Users {
+    id: String,
+}
+impl Row for Users {
+    const TABLE: &'static str = "users";
+}
+#[allow(non_upper_case_globals)]
+impl Users {
+    pub const id: Column<Users, String> = Column::new("id");
+}
+
+fn main() {
+    let client = SupabaseClient::new("https://x", "k", None);
+    let _ = client.from_row::<Posts>().where_(
+        Filter::eq(Posts::id, "a".to_string()) | Filter::eq(Users::id, "b".to_string()),
+    );
+}
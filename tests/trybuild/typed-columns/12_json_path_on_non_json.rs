@@ -0,0 +1,31 @@
+//! Misuse: starting a JSON path on a column that doesn't hold JSON.
+//!
+//! `path` / `text` require the column's value type to implement `Json`;
+//! `Posts::status` is `Column<Posts, String>`.
+
+use rust_supabase_sdk::{
+    postgrest::{Column, Filter},
+    Row, SupabaseClient,
+};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Posts {
+    status: String,
+    metadata: serde_json::Value,
+}
+impl Row for Posts {
+    const TABLE: &'static str = "posts";
+}
+#[allow(non_upper_case_globals)]
+impl Posts {
+    pub const status: Column<Posts, String> = Column::new("status");
+    pub const metadata: Column<Posts, serde_json::Value> = Column::new("metadata");
+}
+
+fn main() {
+    let client = SupabaseClient::new("https://x", "k", None);
+    let _ = client
+        .from_row::<Posts>()
+        .where_(Filter::eq(Posts::status.text("theme"), "dark".to_string()));
+}
@@ -0,0 +1,36 @@
+//! Misuse: a range whose bounds don't match the column's range type.
+//!
+//! `Bookings::during` is `PgRange<i32>`; the bounds must be `i32`.
+
+use rust_supabase_sdk::{
+    postgrest::{Column, PgRange},
+    Row, SupabaseClient,
+};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Bookings {
+    id: i64,
+    room: String,
+    during: PgRange<i32>,
+    tags: Vec<String>,
+    note: Option<String>,
+}
+impl Row for Bookings {
+    const TABLE: &'static str = "bookings";
+}
+#[allow(non_upper_case_globals)]
+impl Bookings {
+    pub const id: Column<Bookings, i64> = Column::new("id");
+    pub const room: Column<Bookings, String> = Column::new("room");
+    pub const during: Column<Bookings, PgRange<i32>> = Column::new("during");
+    pub const tags: Column<Bookings, Vec<String>> = Column::new("tags");
+    pub const note: Column<Bookings, Option<String>> = Column::new("note");
+}
+
+fn main() {
+    let client = SupabaseClient::new("https://x", "k", None);
+    let _ = client
+        .from_row::<Bookings>()
+        .range_overlaps(Bookings::during, PgRange::from("a".to_string().."b".to_string()));
+}
@@ -0,0 +1,37 @@
+//! Misuse: array containment with elements of the wrong type.
+//!
+//! `Bookings::tags` is `Column<Bookings, Vec<String>>`; elements must be
+//! `String`.
+
+use rust_supabase_sdk::{
+    postgrest::{Column, PgRange},
+    Row, SupabaseClient,
+};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Bookings {
+    id: i64,
+    room: String,
+    during: PgRange<i32>,
+    tags: Vec<String>,
+    note: Option<String>,
+}
+impl Row for Bookings {
+    const TABLE: &'static str = "bookings";
+}
+#[allow(non_upper_case_globals)]
+impl Bookings {
+    pub const id: Column<Bookings, i64> = Column::new("id");
+    pub const room: Column<Bookings, String> = Column::new("room");
+    pub const during: Column<Bookings, PgRange<i32>> = Column::new("during");
+    pub const tags: Column<Bookings, Vec<String>> = Column::new("tags");
+    pub const note: Column<Bookings, Option<String>> = Column::new("note");
+}
+
+fn main() {
+    let client = SupabaseClient::new("https://x", "k", None);
+    let _ = client
+        .from_row::<Bookings>()
+        .array_contains(Bookings::tags, [1i32, 2]);
+}
@@ -0,0 +1,41 @@
+//! Misuse: inserting the row struct instead of its insert payload.
+//!
+//! `insert` only accepts `<R as Writable>::Insert` — `PostsInsert` here.
+
+use rust_supabase_sdk::{
+    postgrest::{Column, Writable},
+    Row, SupabaseClient,
+};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Posts {
+    id: i64,
+    title: String,
+}
+impl Row for Posts {
+    const TABLE: &'static str = "posts";
+}
+#[allow(non_upper_case_globals)]
+impl Posts {
+    pub const id: Column<Posts, i64> = Column::new("id");
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct PostsInsert {
+    title: String,
+}
+#[derive(Debug, Clone, Default, Serialize)]
+struct PostsUpdate {
+    title: Option<String>,
+}
+impl Writable for Posts {
+    type Insert = PostsInsert;
+    type Update = PostsUpdate;
+}
+
+fn main() {
+    let client = SupabaseClient::new("https://x", "k", None);
+    let row = Posts { id: 1, title: "t".to_string() };
+    let _ = client.from_row::<Posts>().insert(row);
+}
@@ -0,0 +1,33 @@
+//! Misuse: `.match_` with a struct that isn't a projection of the row.
+//!
+//! `match_` only accepts a `Projection<R>` — declared with `projection!` —
+//! so the criteria can only name `R`'s columns.
+
+use rust_supabase_sdk::{postgrest::Column, Row, SupabaseClient};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Posts {
+    id: i64,
+    title: String,
+}
+impl Row for Posts {
+    const TABLE: &'static str = "posts";
+}
+#[allow(non_upper_case_globals)]
+impl Posts {
+    pub const id: Column<Posts, i64> = Column::new("id");
+    pub const title: Column<Posts, String> = Column::new("title");
+}
+
+#[derive(Serialize, Deserialize)]
+struct UserFilter {
+    email: String,
+}
+
+fn main() {
+    let client = SupabaseClient::new("https://x", "k", None);
+    let _ = client
+        .from_row::<Posts>()
+        .match_(&UserFilter { email: "a@b.c".to_string() });
+}
@@ -0,0 +1,35 @@
+//! Misuse: `.text_search` on a text column.
+//!
+//! Only `tsvector` columns implement `TextSearchable`; search text through
+//! a generated `tsvector` column instead.
+
+use rust_supabase_sdk::{
+    postgrest::{Column, TextSearchType},
+    Row, SupabaseClient,
+};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Posts {
+    id: String,
+    status: String,
+    view_count: i32,
+    archived: Option<bool>,
+}
+impl Row for Posts {
+    const TABLE: &'static str = "posts";
+}
+#[allow(non_upper_case_globals)]
+impl Posts {
+    pub const id: Column<Posts, String> = Column::new("id");
+    pub const status: Column<Posts, String> = Column::new("status");
+    pub const view_count: Column<Posts, i32> = Column::new("view_count");
+    pub const archived: Column<Posts, Option<bool>> = Column::new("archived");
+}
+
+fn main() {
+    let client = SupabaseClient::new("https://x", "k", None);
+    let _ = client
+        .from_row::<Posts>()
+        .text_search(Posts::status, "rust", TextSearchType::Plain, None);
+}
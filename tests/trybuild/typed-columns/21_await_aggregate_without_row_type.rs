@@ -0,0 +1,36 @@
+//! Misuse: awaiting an aggregated query without naming its result row.
+//!
+//! `group_by` / `aggregate` switch the output to `Aggregated`, which can't
+//! be decoded; `aggregate_as::<T>()` must pick the row type first.
+
+use rust_supabase_sdk::{
+    postgrest::{Agg, Column},
+    Row, SupabaseClient,
+};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Posts {
+    id: String,
+    status: String,
+}
+impl Row for Posts {
+    const TABLE: &'static str = "posts";
+}
+#[allow(non_upper_case_globals)]
+impl Posts {
+    pub const id: Column<Posts, String> = Column::new("id");
+    pub const status: Column<Posts, String> = Column::new("status");
+}
+
+async fn totals(client: SupabaseClient) {
+    let _ = client
+        .from_row::<Posts>()
+        .group_by(Posts::status)
+        .aggregate(Agg::count())
+        .await;
+}
+
+fn main() {
+    let _ = totals(SupabaseClient::new("https://x", "k", None));
+}